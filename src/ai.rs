@@ -1,7 +1,17 @@
-use crate::config::AppConfig;
+use crate::auth;
+use crate::cache::Cache;
+use crate::config::{AppConfig, Display, DEFAULT_HISTORY_SAMPLES};
+use crate::convention;
 use crate::error::{Error, Result};
-use crate::llm::{self, ChatMessage};
-use crate::repo::Repo;
+use crate::history;
+use crate::language::{self, Language};
+use crate::llm::{self, Api, ChatMessage};
+use crate::prompt::{Kind, Prompt};
+use crate::repo::{FixupTarget, Repo};
+use crate::ticket::{self, Ticket};
+use crate::trailer;
+use crate::usage;
+use crate::Output;
 use std::env;
 use std::fs;
 use std::io::{self, IsTerminal, Write};
@@ -9,17 +19,50 @@ use std::process::Command;
 
 use crate::style;
 
-/// Report a successful commit as `✓ Committed <short-sha> on <branch>`.
-fn report_commit(repo: &Repo) -> Result<()> {
+/// How many first-parent commits `--fixup`/`--squash` consider as targets.
+const FIXUP_DEPTH: usize = 20;
+/// Candidates kept for picking; one keypress (1–9) must address each.
+const MAX_CANDIDATES: usize = 9;
+
+/// Report a successful commit as `✓ Committed <short-sha> on <branch>` (or
+/// `Amended …`, per `verb`).
+fn report_commit(repo: &Repo, verb: &str) -> Result<()> {
     let short = repo.head_short_id()?;
     let line = match repo.current_branch() {
-        Ok(branch) => format!("Committed {} {}", style::cyan(&short), style::dim(&format!("on {branch}"))),
-        Err(_) => format!("Committed {}", style::cyan(&short)),
+        Ok(branch) => format!(
+            "{verb} {} {}",
+            style::cyan(&short),
+            style::dim(&format!("on {branch}"))
+        ),
+        Err(_) => format!("{verb} {}", style::cyan(&short)),
     };
     style::success(&line);
     Ok(())
 }
 
+/// Report the outcome per `output`: the styled line for a person, or just the
+/// final message (plus the new commit's id, as JSON) for scripts. `commit` is
+/// whether a commit was made.
+fn finish(repo: &Repo, verb: &str, output: Output, message: &str, commit: bool) -> Result<()> {
+    match output {
+        Output::Normal if commit => report_commit(repo, verb)?,
+        Output::Normal => {}
+        Output::Quiet => println!("{}", message.trim()),
+        Output::Json => {
+            let id = if commit {
+                Some(repo.head_commit_id()?)
+            } else {
+                None
+            };
+            println!(
+                "{}",
+                serde_json::json!({ "message": message.trim(), "commit": id })
+            );
+        }
+    }
+    Ok(())
+}
+
 /// How much of the working tree to stage before generating a commit message.
 pub enum StageMode {
     /// Leave the index as-is.
@@ -30,9 +73,8 @@ pub enum StageMode {
     Tracked,
 }
 
-/// Shared setup for every commit entry point: open the repo, optionally stage,
-/// and load a config with a usable API key.
-fn prepare(stage: StageMode) -> Result<(Repo, String, AppConfig)> {
+/// Open the repository in the current directory and stage per `stage`.
+fn open_and_stage(stage: StageMode) -> Result<Repo> {
     let path = env::current_dir().map_err(|_| Error::NoCurrentDir)?;
     let repo = Repo::new(&path)?;
 
@@ -42,11 +84,21 @@ fn prepare(stage: StageMode) -> Result<(Repo, String, AppConfig)> {
         StageMode::Tracked => repo.stage_tracked()?,
     }
 
-    let changes = repo.get_staged_git_changes()?;
-    let config = crate::config::load_config()?;
-    if config.deepseek.api_key.is_empty() {
-        return Err(Error::NoApiKey);
-    }
+    Ok(repo)
+}
+
+/// Shared setup for every commit entry point: open the repo, optionally stage,
+/// collect the changes the message describes, and load a config with a usable
+/// API key.
+fn prepare(stage: StageMode, kind: &CommitKind) -> Result<(Repo, String, AppConfig)> {
+    let repo = open_and_stage(stage)?;
+
+    let changes = match kind {
+        CommitKind::Amend => repo.get_amend_changes()?,
+        _ => repo.get_staged_git_changes()?,
+    };
+    let mut config = crate::config::load_config()?;
+    auth::require_api_key(&mut config)?;
 
     Ok((repo, changes, config))
 }
@@ -58,6 +110,43 @@ pub enum CommitMode {
     Editor,
 }
 
+/// Which commit the generated message is for.
+pub enum CommitKind {
+    /// A new commit on top of `HEAD`.
+    New,
+    /// Rewrite `HEAD`, folding in the staged changes (`--amend`). The message
+    /// describes `HEAD^` → index, i.e. the combined changes.
+    Amend,
+    /// A `fixup!` commit for the recent commit the staged hunks belong to, for
+    /// `rebase --autosquash`. Git writes the message; no model is involved.
+    Fixup,
+    /// A `squash!` commit for that same target, with a generated message to be
+    /// melded into the target's when autosquashing.
+    Squash,
+}
+
+/// Everything `git ac` was asked to do.
+pub struct Options {
+    pub stage: StageMode,
+    pub mode: CommitMode,
+    pub kind: CommitKind,
+    /// Add a `Signed-off-by` trailer for the committer.
+    pub signoff: bool,
+    /// Co-author aliases (team roster / `.mailmap`) or literal `Name <email>`.
+    pub co_authors: Vec<String>,
+    /// Alternatives to generate per round; more than one shows a picker.
+    pub candidates: usize,
+    /// Start from the last message generated for the same staged tree
+    /// instead of asking the model.
+    pub last: bool,
+    /// Always ask the model, bypassing the completion cache.
+    pub no_cache: bool,
+    /// What to print; anything but `Normal` also rules out prompts.
+    pub output: Output,
+    /// Language to write the message in, overriding the config's.
+    pub lang: Option<String>,
+}
+
 /// What the user chose to do with a generated message in interactive mode.
 enum Action {
     Commit,
@@ -66,18 +155,128 @@ enum Action {
     Abort,
 }
 
-/// Generate a commit message from the staged changes and act on it per `mode`.
-pub fn run(stage: StageMode, mode: CommitMode) -> Result<()> {
-    let (repo, changes, config) = prepare(stage)?;
+/// What the user chose in the `--candidates` picker.
+enum Pick {
+    /// Index into the candidate pool.
+    Chosen(usize),
+    Regenerate,
+    Abort,
+}
+
+/// Generate a commit message from the staged changes and act on it per `mode`,
+/// then account for the tokens spent.
+pub fn run(opts: Options) -> Result<()> {
+    let show_usage = opts.output == Output::Normal;
+    let result = generate_and_commit(opts);
+    usage::settle("ac", show_usage);
+    result
+}
+
+fn generate_and_commit(opts: Options) -> Result<()> {
+    let Options {
+        stage,
+        mode,
+        kind,
+        candidates,
+        last,
+        no_cache,
+        output,
+        lang,
+        ..
+    } = opts;
+    if matches!(kind, CommitKind::Fixup) {
+        return fixup(stage);
+    }
+    let (repo, changes, config) = prepare(stage, &kind)?;
+
+    // Settle what is being committed before spending any tokens.
+    let mut extra_args = Vec::new();
+    let verb = match kind {
+        CommitKind::Amend => {
+            extra_args.push("--amend".to_string());
+            "Amended"
+        }
+        CommitKind::Squash => {
+            let Some(target) = confirm_target(&repo)? else {
+                return Ok(());
+            };
+            extra_args.push(format!("--squash={}", target.id));
+            "Committed"
+        }
+        _ => "Committed",
+    };
     let model = config.deepseek.model.as_deref().unwrap_or("deepseek-chat");
 
     // In Apply mode on a TTY, let the user review the message before it lands
     // (accept / edit / regenerate / abort). Piped input keeps the old
     // commit-immediately behavior so scripts are unaffected.
-    let interactive = matches!(mode, CommitMode::Apply) && io::stdin().is_terminal();
+    // `--quiet`/`--json` print nothing but the result, so never prompt either.
+    let chatty = output == Output::Normal;
+    let interactive = matches!(mode, CommitMode::Apply) && chatty && io::stdin().is_terminal();
+    let display = if chatty {
+        config.ui.display
+    } else {
+        Display::Quiet
+    };
+
+    let language = Language::choose(lang.as_deref(), config.language.as_deref());
+    let prompt = load_prompt(&repo, &config)?;
+    let values = prompt_values(&prompt, &repo, &config, language.as_ref());
+    let conventions = conventions(&repo, &config);
+
+    // A ticket named in the branch (`fix/567-bug`) becomes a trailer on the
+    // message; detached HEADs simply have none.
+    let ticket = match repo.current_branch() {
+        Ok(branch) => ticket::from_branch(&branch, &config.tickets)?,
+        Err(_) => None,
+    };
+
+    // Trailers are resolved once, up front (so an unknown co-author fails
+    // before any tokens are spent), and re-applied to every regenerated
+    // message. Sign-off goes last, as git itself places it.
+    let mut trailers = config.commit.trailers.clone();
+    let mailmap = repo.mailmap();
+    for alias in &opts.co_authors {
+        trailers.push(trailer::co_author(alias, &config.team, mailmap.as_deref())?);
+    }
+    if opts.signoff || config.commit.signoff {
+        trailers.push(format!("Signed-off-by: {}", repo.identity()?));
+    }
+
+    // Every generated message is logged against the staged tree, before it
+    // is committed, so none is lost to an abort or a failing hook.
+    let tree = repo.staged_tree_id()?;
+    let mut reused = if last {
+        let entries = history::load(&repo)?;
+        Some(
+            history::latest_for(&entries, &tree)
+                .cloned()
+                .ok_or(Error::NoHistory)?,
+        )
+    } else {
+        None
+    };
+    let remember = |guidance: &[String], message: &str| {
+        // History is a safety net; failing to write it never blocks a commit.
+        let _ = history::record(&repo, history::Entry::new(&tree, model, guidance, message));
+    };
+
+    // Only the first request may be answered from the cache: regenerating
+    // (or starting from `--last`) asks for something new.
+    let mut cache = if no_cache {
+        None
+    } else {
+        Cache::open(&config.cache)
+    };
+    if last {
+        cache = cache.map(|c| c.write_only());
+    }
 
     // Extra instructions accumulated from "regenerate" guidance.
     let mut guidance: Vec<String> = Vec::new();
+    // Alternatives from `--candidates`, kept across regenerations so earlier
+    // ones stay selectable.
+    let mut pool: Vec<String> = Vec::new();
 
     loop {
         let title = match mode {
@@ -85,55 +284,124 @@ pub fn run(stage: StageMode, mode: CommitMode) -> Result<()> {
             _ => "Thinking",
         };
 
-        let mut messages = build_prompt_messages(&changes, config.deepseek.prompt.clone());
+        let mut messages = build_prompt_messages(
+            &prompt,
+            &values,
+            &changes,
+            conventions.as_deref(),
+            language.as_ref(),
+        );
         for hint in &guidance {
             messages.push(ChatMessage::user(format!(
                 "Please revise the commit message: {hint}"
             )));
         }
 
-        let message = llm::stream_and_collect(
-            title,
-            &config.deepseek.api_key,
-            model,
-            messages,
-            config.deepseek.temperature,
-        )?;
-
-        if !matches!(mode, CommitMode::Preview) && message.trim().is_empty() {
-            return Err(Error::EmptyMessage);
-        }
+        let message =
+            if let Some(entry) = reused.take() {
+                let (message, added) = with_trailers(
+                    entry.message.clone(),
+                    ticket.as_ref(),
+                    &trailers,
+                    &config.commit.trailers,
+                );
+                if chatty {
+                    show_reused(&entry);
+                    echo_trailers(&added);
+                }
+                message
+            } else if candidates > 1 {
+                let batch = llm::collect_many(
+                    title,
+                    &Api::new(&config),
+                    model,
+                    messages,
+                    config.deepseek.temperature,
+                    candidates,
+                )?;
+                let before = pool.len();
+                pool.extend(batch.into_iter().filter(|m| !m.trim().is_empty()).map(|m| {
+                    with_trailers(m, ticket.as_ref(), &trailers, &config.commit.trailers).0
+                }));
+                if pool.len() == before {
+                    return Err(Error::EmptyMessage);
+                }
+                for message in &pool[before..] {
+                    remember(&guidance, message);
+                }
+                // Forget the oldest once the pool outgrows single-key picking.
+                let overflow = pool.len().saturating_sub(MAX_CANDIDATES);
+                pool.drain(..overflow);
+                let fresh = before.saturating_sub(overflow);
+                show_candidates(&pool, fresh);
+
+                match mode {
+                    CommitMode::Preview => return Ok(()),
+                    CommitMode::Apply if interactive => match pick_candidate(pool.len())? {
+                        Pick::Chosen(idx) => pool[idx].clone(),
+                        Pick::Regenerate => {
+                            ask_guidance(&mut guidance)?;
+                            continue;
+                        }
+                        Pick::Abort => {
+                            println!("Aborted; nothing committed.");
+                            return Ok(());
+                        }
+                    },
+                    // Nobody to ask: take the first of the fresh batch.
+                    _ => pool[fresh].clone(),
+                }
+            } else {
+                let message = llm::stream_and_collect(
+                    title,
+                    &Api::new(&config),
+                    model,
+                    messages,
+                    config.deepseek.temperature,
+                    cache.as_ref(),
+                    display,
+                )?;
+                cache = cache.map(|c| c.write_only());
+
+                if !matches!(mode, CommitMode::Preview) && message.trim().is_empty() {
+                    return Err(Error::EmptyMessage);
+                }
+                if chatty {
+                    language::warn_if_mismatched(language.as_ref(), &message);
+                }
+                let (message, added) =
+                    with_trailers(message, ticket.as_ref(), &trailers, &config.commit.trailers);
+                if chatty {
+                    echo_trailers(&added);
+                }
+                if !message.trim().is_empty() {
+                    remember(&guidance, &message);
+                }
+                message
+            };
 
         match mode {
-            CommitMode::Preview => return Ok(()),
+            CommitMode::Preview => return finish(&repo, verb, output, &message, false),
             CommitMode::Editor => {
-                commit_via_git(&message, true)?;
-                report_commit(&repo)?;
-                return Ok(());
+                commit_via_git(&message, true, &extra_args)?;
+                return finish(&repo, verb, output, &repo.head_message()?, true);
             }
             CommitMode::Apply if !interactive => {
-                commit_via_git(&message, false)?;
-                report_commit(&repo)?;
-                return Ok(());
+                commit_via_git(&message, false, &extra_args)?;
+                return finish(&repo, verb, output, &message, true);
             }
             CommitMode::Apply => match prompt_action()? {
                 Action::Commit => {
-                    commit_via_git(&message, false)?;
-                    report_commit(&repo)?;
+                    commit_via_git(&message, false, &extra_args)?;
+                    report_commit(&repo, verb)?;
                     return Ok(());
                 }
                 Action::Edit => {
-                    commit_via_git(&message, true)?;
-                    report_commit(&repo)?;
+                    commit_via_git(&message, true, &extra_args)?;
+                    report_commit(&repo, verb)?;
                     return Ok(());
                 }
-                Action::Regenerate => {
-                    let hint = prompt_line("Any guidance for the rewrite? (optional): ")?
-                        .unwrap_or_default();
-                    if !hint.is_empty() {
-                        guidance.push(hint);
-                    }
-                }
+                Action::Regenerate => ask_guidance(&mut guidance)?,
                 Action::Abort => {
                     println!("Aborted; nothing committed.");
                     return Ok(());
@@ -143,6 +411,189 @@ pub fn run(stage: StageMode, mode: CommitMode) -> Result<()> {
     }
 }
 
+/// Learn the repository's message conventions for the prompt. History is a
+/// nicety: an unreadable log just means the generic prompt is used.
+pub fn conventions(repo: &Repo, config: &AppConfig) -> Option<String> {
+    let samples = repo
+        .recent_commit_messages(
+            config
+                .commit
+                .history_samples
+                .unwrap_or(DEFAULT_HISTORY_SAMPLES),
+            &config.commit.ignore_authors,
+        )
+        .unwrap_or_default();
+    convention::analyze(&samples).prompt()
+}
+
+/// Create a `fixup!` commit for the recent commit the staged hunks belong to,
+/// after confirming the target on a TTY.
+fn fixup(stage: StageMode) -> Result<()> {
+    let repo = open_and_stage(stage)?;
+    // Fails with a helpful message when nothing is staged.
+    repo.get_staged_git_changes()?;
+
+    let Some(target) = confirm_target(&repo)? else {
+        return Ok(());
+    };
+    let status = Command::new("git")
+        .args(["commit", "-q", &format!("--fixup={}", target.id)])
+        .status()?;
+    if !status.success() {
+        return Err(Error::CommitCancelled);
+    }
+    report_commit(&repo, "Committed")
+}
+
+/// Find the `--fixup`/`--squash` target and, on a TTY, ask before using it.
+/// `None` means the user declined.
+fn confirm_target(repo: &Repo) -> Result<Option<FixupTarget>> {
+    let target = repo
+        .fixup_target(FIXUP_DEPTH)?
+        .ok_or(Error::NoFixupTarget(FIXUP_DEPTH))?;
+    println!(
+        "Staged changes belong to {} {} {}",
+        style::cyan(&target.short_id),
+        style::bold(&target.summary),
+        style::dim(&format!(
+            "({} of {} touched lines)",
+            target.lines, target.total
+        ))
+    );
+    if io::stdin().is_terminal() && !confirm("Use this commit")? {
+        println!("Aborted; nothing committed.");
+        return Ok(None);
+    }
+    Ok(Some(target))
+}
+
+/// Ask a yes/no question with a single keypress; Enter means yes, EOF no.
+pub fn confirm(question: &str) -> Result<bool> {
+    loop {
+        print!("{}", style::prompt(question, "[Y]es / [n]o"));
+        io::stdout().flush()?;
+        let Some(key) = read_key()? else {
+            println!();
+            return Ok(false);
+        };
+        let key = key.to_ascii_lowercase();
+        let shown = if key == '\r' || key == '\n' { 'Y' } else { key };
+        println!("{shown}");
+        match key {
+            '\r' | '\n' | 'y' => return Ok(true),
+            'n' | 'a' | 'q' => return Ok(false),
+            _ => println!("Please press Y or n."),
+        }
+    }
+}
+
+/// Replace any trailers the model wrote with ours (the branch's ticket
+/// reference, configured trailers, co-authors, sign-off). Returns the message
+/// and the lines that were added.
+fn with_trailers(
+    message: String,
+    ticket: Option<&Ticket>,
+    trailers: &[String],
+    configured: &[String],
+) -> (String, Vec<String>) {
+    if message.trim().is_empty() {
+        return (message, Vec::new());
+    }
+    let stripped = trailer::strip_managed(&message, configured);
+    trailer::apply(&stripped, ticket, trailers)
+}
+
+/// Echo added trailer lines under the revealed message, so what is reviewed
+/// is exactly what gets committed.
+fn echo_trailers(added: &[String]) {
+    if !added.is_empty() {
+        println!("{}", style::gutter());
+        for line in added {
+            println!("{} {}", style::gutter(), style::dim(line));
+        }
+    }
+}
+
+/// Print a message taken from history, the way a generated one is revealed.
+fn show_reused(entry: &history::Entry) {
+    let when = style::dim(&format!("(from {})", history::age(entry.time)));
+    style::header(&format!("Reusing the last generated message {when}"));
+    for line in entry.message.lines() {
+        println!("{} {line}", style::gutter());
+    }
+}
+
+/// Ask for optional guidance before regenerating, remembering any given.
+fn ask_guidance(guidance: &mut Vec<String>) -> Result<()> {
+    let hint = prompt_line("Any guidance for the rewrite? (optional): ")?.unwrap_or_default();
+    if !hint.is_empty() {
+        guidance.push(hint);
+    }
+    Ok(())
+}
+
+/// List the candidates, numbered for picking: those from earlier rounds
+/// (before index `fresh`) as dimmed subjects, the fresh batch in full.
+fn show_candidates(pool: &[String], fresh: usize) {
+    style::header(&format!("{} candidate messages", pool.len()));
+    for (idx, message) in pool.iter().enumerate() {
+        let number = style::cyan(&format!("{:>2}", idx + 1));
+        let mut lines = message.lines();
+        let subject = lines.next().unwrap_or_default();
+        if idx < fresh {
+            println!("{number} {}", style::dim(subject));
+            continue;
+        }
+        println!("{number} {}", style::bold(subject));
+        for line in lines {
+            println!("   {line}");
+        }
+        if idx + 1 < pool.len() {
+            println!();
+        }
+    }
+}
+
+/// Pick a candidate with one keypress (`1`–`count`), or regenerate / abort.
+/// The prompt block is erased once answered, like [`prompt_action`].
+fn pick_candidate(count: usize) -> Result<Pick> {
+    println!();
+    let mut drawn = 1;
+    loop {
+        print!(
+            "{}",
+            style::prompt(
+                "Pick a message",
+                &format!("[1-{count}] / [r]egenerate / [a]bort"),
+            )
+        );
+        io::stdout().flush()?;
+
+        let Some(key) = read_key()? else {
+            println!();
+            return Ok(Pick::Abort);
+        };
+        let key = key.to_ascii_lowercase();
+        println!("{key}");
+        drawn += 1;
+
+        let pick = match key {
+            'r' => Pick::Regenerate,
+            'a' | 'q' => Pick::Abort,
+            digit => match digit.to_digit(10).map(|d| d as usize) {
+                Some(n) if (1..=count).contains(&n) => Pick::Chosen(n - 1),
+                _ => {
+                    println!("Please press 1-{count}, r, or a.");
+                    drawn += 1;
+                    continue;
+                }
+            },
+        };
+        style::erase_lines(drawn);
+        return Ok(pick);
+    }
+}
+
 /// Ask what to do with the generated message, repeating on invalid input.
 /// Reads a single keypress (no Enter needed); the prompt block is erased once
 /// the user decides, leaving only the outcome on screen.
@@ -252,8 +703,9 @@ fn prompt_line(prompt: &str) -> Result<Option<String>> {
 
 /// Commit the staged changes via `git commit -F`, so pre-commit/commit-msg
 /// hooks and signing run (libgit2 would skip them). When `edit` is set, `-e`
-/// opens the editor on the seeded message first.
-fn commit_via_git(message: &str, edit: bool) -> Result<()> {
+/// opens the editor on the seeded message first. `extra` carries
+/// kind-specific flags such as `--amend`.
+fn commit_via_git(message: &str, edit: bool, extra: &[String]) -> Result<()> {
     // Process-unique name so concurrent runs don't clobber each other.
     let temp_file = env::temp_dir().join(format!("git-github-commit-{}.txt", std::process::id()));
     fs::write(&temp_file, message.trim())?;
@@ -261,7 +713,7 @@ fn commit_via_git(message: &str, edit: bool) -> Result<()> {
     let mut command = Command::new("git");
     // `-q` suppresses git's own `[branch sha] summary` + diffstat; we print a
     // clean success line ourselves.
-    command.args(["commit", "-q"]);
+    command.args(["commit", "-q"]).args(extra);
     if edit {
         style::header("Opening editor for review");
         // `-e` opens the editor to edit the seeded message; `-F` (below) does
@@ -280,36 +732,96 @@ fn commit_via_git(message: &str, edit: bool) -> Result<()> {
     let _ = fs::remove_file(&temp_file);
 
     if !status?.success() {
+        eprintln!(
+            "{}",
+            style::dim("The message was kept: `git ac --last` reuses it.")
+        );
         return Err(Error::CommitCancelled);
     }
 
     Ok(())
 }
 
-fn build_prompt_messages(changes: &str, prompt_opt: Option<String>) -> Vec<ChatMessage> {
-    let prompt = prompt_opt.unwrap_or_else(|| {
-        r#"
-You are an AI commit message assistant.
-
-Please generate a commit message with the following format:
-1. Title (one short sentence, 50-72 characters max).
-2. A clear bullet-point list of changes (start each line with "- ").
-3. Each line, including bullets, should be under 100 characters.
-4. Keep it concise, consistent, and professional.
-
-Example:
+/// The commit template for `repo`: its own, the user's, the legacy
+/// `[deepseek] prompt`, or the built-in one (see [`crate::prompt`]).
+pub fn load_prompt(repo: &Repo, config: &AppConfig) -> Result<Prompt> {
+    Prompt::load(
+        Kind::Commit,
+        repo.workdir(),
+        config.deepseek.prompt.as_deref(),
+    )
+}
 
-Improve error handling in user authentication
+/// Values for the commit template's variables other than the per-commit
+/// `{diff}` and `{conventions}`. Recent commits are only read when used.
+pub fn prompt_values(
+    prompt: &Prompt,
+    repo: &Repo,
+    config: &AppConfig,
+    language: Option<&Language>,
+) -> Vec<(&'static str, String)> {
+    let branch = repo.current_branch().ok();
+    let ticket = branch
+        .as_deref()
+        .and_then(|branch| ticket::from_branch(branch, &config.tickets).ok().flatten());
+    let mut values = vec![
+        ("branch", branch.unwrap_or_default()),
+        (
+            "language",
+            language.map_or("English", |l| &l.name).to_string(),
+        ),
+        ("ticket", ticket.map(|t| t.display()).unwrap_or_default()),
+    ];
+    if prompt.uses("recent_commits") {
+        let subjects = repo
+            .recent_commit_messages(RECENT_COMMITS, &config.commit.ignore_authors)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|message| message.lines().next())
+            .map(|subject| format!("- {subject}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        values.push(("recent_commits", subjects));
+    }
+    values
+}
 
-- Add detailed error messages for login failures
-- Handle timeout errors gracefully
-- Refactor error propagation logic for clarity
-"#
-        .to_string()
-    });
+/// Commit subjects offered as `{recent_commits}`.
+const RECENT_COMMITS: usize = 10;
+
+/// The rendered template as the system prompt, the repository's learned
+/// conventions when there are any, the language instruction, then the staged
+/// changes. What the template places itself is not repeated; a template
+/// that includes the diff is sent as the user's message.
+pub fn build_prompt_messages(
+    prompt: &Prompt,
+    values: &[(&str, String)],
+    changes: &str,
+    conventions: Option<&str>,
+    language: Option<&Language>,
+) -> Vec<ChatMessage> {
+    let mut values = values.to_vec();
+    values.push(("diff", changes.to_string()));
+    values.push(("conventions", conventions.unwrap_or_default().to_string()));
+    let rendered = prompt.render(&values);
+
+    let mut guides = Vec::new();
+    if let Some(conventions) = conventions.filter(|_| !prompt.uses("conventions")) {
+        guides.push(ChatMessage::system(conventions));
+    }
+    if let Some(language) = language {
+        guides.push(ChatMessage::system(language.instruction()));
+    }
 
-    vec![
-        ChatMessage::system(prompt),
-        ChatMessage::user(format!("Here are my current Git changes:\n{}", changes)),
-    ]
+    if prompt.uses("diff") {
+        guides.push(ChatMessage::user(rendered));
+        return guides;
+    }
+    let mut messages = vec![ChatMessage::system(rendered)];
+    messages.extend(guides);
+    messages.push(ChatMessage::user(format!(
+        "Here are my current Git changes:\n{}",
+        changes
+    )));
+    messages
 }
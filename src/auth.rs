@@ -0,0 +1,502 @@
+//! Where secrets come from, so the DeepSeek key needn't sit in a plain-text
+//! config file. The key is looked up in order: `DEEPSEEK_API_KEY`, `api_key`
+//! in the config, the output of `api_key_command` (a password manager), the
+//! Secret Service keyring (Linux desktops, through `secret-tool`), then
+//! `git credential fill` for the API host, so whichever credential helper
+//! git is set up with works too. `git-github auth` stores and inspects it.
+
+use crate::config::{load_config, AppConfig, DEFAULT_DEEPSEEK_URL};
+use crate::error::{Error, Result};
+use crate::github;
+use crate::settings;
+use crate::style;
+use std::env;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// The username a DeepSeek key is filed under in credential stores.
+const USERNAME: &str = "api-key";
+/// `secret-tool` attributes identify our items by this service name.
+const SERVICE: &str = "git-github";
+
+/// Where a secret was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Env(&'static str),
+    /// A config file (or `git config`), named by its layer.
+    Config(String),
+    /// A command configured by `setting`, e.g. `api_key_command`.
+    Command {
+        setting: &'static str,
+        command: String,
+    },
+    SecretService,
+    /// `hosts.toml`, where `auth login --github` keeps tokens without a
+    /// keyring.
+    HostsFile(PathBuf),
+    /// gh's `hosts.yml`.
+    GhHosts(PathBuf),
+    GitCredential,
+    GhCli,
+}
+
+impl Source {
+    pub fn describe(&self) -> String {
+        match self {
+            Source::Env(var) => format!("the {var} environment variable"),
+            Source::Config(origin) => format!("the config ({origin})"),
+            Source::Command { setting, command } => format!("{setting} `{command}`"),
+            Source::SecretService => "the Secret Service keyring".to_string(),
+            Source::HostsFile(path) => format!("`auth login --github` ({})", path.display()),
+            Source::GhHosts(path) => format!("the gh CLI's {}", path.display()),
+            Source::GitCredential => "git's credential helper".to_string(),
+            Source::GhCli => "the gh CLI".to_string(),
+        }
+    }
+}
+
+/// A secret and where it came from.
+#[derive(Debug, Clone)]
+pub struct Secret {
+    pub value: String,
+    pub source: Source,
+}
+
+/// The stores `auth login` can save the key in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Store {
+    SecretService,
+    GitCredential,
+}
+
+impl Store {
+    fn describe(self) -> &'static str {
+        match self {
+            Store::SecretService => "the Secret Service keyring",
+            Store::GitCredential => "git's credential helper",
+        }
+    }
+}
+
+/// Fill in `config.deepseek.api_key` from the first source that has one.
+/// Commands that call the model run this once, after loading the config,
+/// so a password manager is only asked when a key is actually needed.
+pub fn require_api_key(config: &mut AppConfig) -> Result<()> {
+    if !config.deepseek.api_key.is_empty() {
+        return Ok(());
+    }
+    let secret = deepseek_key(config)?.ok_or(Error::NoApiKey)?;
+    config.deepseek.api_key = secret.value;
+    Ok(())
+}
+
+/// The DeepSeek key, trying each source in order. A failing
+/// `api_key_command` is an error rather than a reason to look elsewhere: it
+/// is configured on purpose, and silently using another key would surprise.
+pub fn deepseek_key(config: &AppConfig) -> Result<Option<Secret>> {
+    let found = |value: String, source: Source| Ok(Some(Secret { value, source }));
+    if let Some(key) = env::var("DEEPSEEK_API_KEY").ok().filter(|k| !k.is_empty()) {
+        return found(key, Source::Env("DEEPSEEK_API_KEY"));
+    }
+    if !config.deepseek.api_key.is_empty() {
+        let origin = settings::origin_of("deepseek.api_key").unwrap_or_else(|| "config".into());
+        return found(config.deepseek.api_key.clone(), Source::Config(origin));
+    }
+    let command = config.deepseek.api_key_command.as_deref();
+    if let Some(command) = command.filter(|c| !c.trim().is_empty()) {
+        let source = Source::Command {
+            setting: "api_key_command",
+            command: command.to_string(),
+        };
+        return found(run_command("api_key_command", command)?, source);
+    }
+    let host = deepseek_host(config);
+    if let Some(key) = secret_tool_lookup(&[("host", &host)]) {
+        return found(key, Source::SecretService);
+    }
+    if let Some(key) = credential_fill(&host, Some(USERNAME)) {
+        return found(key, Source::GitCredential);
+    }
+    Ok(None)
+}
+
+/// The host the key belongs to: DeepSeek's, or that of `base_url`.
+fn deepseek_host(config: &AppConfig) -> String {
+    host_of(
+        config
+            .deepseek
+            .base_url
+            .as_deref()
+            .unwrap_or(DEFAULT_DEEPSEEK_URL),
+    )
+}
+
+/// `https://api.example.com:8443/v1` → `api.example.com:8443`.
+pub(crate) fn host_of(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.split('/').next().unwrap_or(rest).to_string()
+}
+
+/// Run `command`, configured as `setting`, through the shell and take the
+/// secret it prints.
+pub(crate) fn run_command(setting: &'static str, command: &str) -> Result<String> {
+    let failed = |reason: String| Error::SecretCommand {
+        setting,
+        command: command.to_string(),
+        reason,
+    };
+    let output = shell(command)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| failed(e.to_string()))?;
+    if !output.status.success() {
+        return Err(failed(match output.status.code() {
+            Some(code) => format!("it exited with code {code}"),
+            None => "it was killed".into(),
+        }));
+    }
+    // Password managers like `pass` put the secret on the first line.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let key = stdout.lines().next().unwrap_or_default().trim().to_string();
+    if key.is_empty() {
+        return Err(failed("it printed nothing".into()));
+    }
+    Ok(key)
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd
+}
+
+#[cfg(not(unix))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command]);
+    cmd
+}
+
+/// `secret-tool` with our item's attributes appended to `args`: the
+/// service name, then `attributes` (`host`, and `user` for GitHub tokens).
+pub(crate) fn secret_tool(args: &[&str], attributes: &[(&str, &str)]) -> Command {
+    let mut cmd = Command::new("secret-tool");
+    cmd.args(args).args(["service", SERVICE]);
+    for (name, value) in attributes {
+        cmd.args([name, value]);
+    }
+    cmd
+}
+
+pub(crate) fn secret_tool_lookup(attributes: &[(&str, &str)]) -> Option<String> {
+    let output = secret_tool(&["lookup"], attributes)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let key = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !key.is_empty()).then_some(key)
+}
+
+/// Save `secret` in the keyring under `attributes`.
+pub(crate) fn secret_tool_store(
+    label: &str,
+    attributes: &[(&str, &str)],
+    secret: &str,
+) -> Result<()> {
+    let failed = |reason: String| Error::SecretStore(Store::SecretService.describe(), reason);
+    let mut child = secret_tool(&["store", &format!("--label={label}")], attributes)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| failed(e.to_string()))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(secret.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(failed(format!("secret-tool exited with {status}")));
+    }
+    Ok(())
+}
+
+/// Whether a Secret Service keyring is reachable: `secret-tool` is
+/// installed and a session bus is running.
+pub(crate) fn secret_service_available() -> bool {
+    cfg!(target_os = "linux")
+        && env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some()
+        && Command::new("secret-tool")
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok()
+}
+
+/// Run `git credential <action>` for `https://<host>`, never prompting: a
+/// helper either has the credential or the lookup fails. Returns the
+/// password `fill` found.
+fn credential(
+    action: &str,
+    host: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+) -> Option<String> {
+    let mut input = format!("protocol=https\nhost={host}\n");
+    if let Some(username) = username {
+        input.push_str(&format!("username={username}\n"));
+    }
+    if let Some(password) = password {
+        input.push_str(&format!("password={password}\n"));
+    }
+    input.push('\n');
+
+    let mut child = Command::new("git")
+        .args(["credential", action])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_ASKPASS", "")
+        .env("SSH_ASKPASS", "")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    child.stdin.take()?.write_all(input.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("password="))
+        .map(str::to_string)
+        .filter(|password| !password.is_empty())
+}
+
+/// A password for `host` from git's credential helpers, if any has one.
+pub(crate) fn credential_fill(host: &str, username: Option<&str>) -> Option<String> {
+    credential("fill", host, username, None)
+}
+
+/// Whether git has a credential helper configured to store into.
+fn credential_helper_configured() -> bool {
+    Command::new("git")
+        .args(["config", "--get-all", "credential.helper"])
+        .output()
+        .is_ok_and(|output| output.status.success() && !output.stdout.trim_ascii().is_empty())
+}
+
+/// `auth login`: read the DeepSeek key (hidden on a terminal, else from
+/// stdin) and save it in `store`, by default the keyring when there is
+/// one and git's credential helper otherwise.
+pub fn login(store: Option<Store>) -> Result<()> {
+    let config = load_config()?;
+    let host = deepseek_host(&config);
+    let store = match store {
+        Some(store) => store,
+        None if secret_service_available() => Store::SecretService,
+        None if credential_helper_configured() => Store::GitCredential,
+        None => return Err(Error::NoSecretStore),
+    };
+
+    let key = read_secret(&format!("DeepSeek API key for {host}: "))?;
+    if key.is_empty() {
+        return Err(Error::NoApiKey);
+    }
+    match store {
+        Store::SecretService => {
+            let label = "git-github: DeepSeek API key";
+            secret_tool_store(label, &[("host", &host)], &key)?;
+        }
+        Store::GitCredential => {
+            if !credential_helper_configured() {
+                return Err(Error::SecretStore(
+                    store.describe(),
+                    "no credential.helper is configured".into(),
+                ));
+            }
+            credential("approve", &host, Some(USERNAME), Some(&key));
+            // `approve` succeeds whatever the helpers do with the key, so
+            // read it back to be sure one kept it.
+            if credential_fill(&host, Some(USERNAME)).as_deref() != Some(key.as_str()) {
+                return Err(Error::SecretStore(
+                    store.describe(),
+                    "no credential helper kept the key (is it read-only?)".into(),
+                ));
+            }
+        }
+    }
+
+    style::success(&format!(
+        "Saved the DeepSeek API key in {}",
+        store.describe()
+    ));
+    if let Ok(Some(secret)) = deepseek_key(&config) {
+        if secret.value != key {
+            println!(
+                "Note: {} still takes precedence over it.",
+                secret.source.describe()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `auth logout`: remove the key from the keyring and git's credential
+/// helpers. Keys in the config or the environment are left alone, but
+/// pointed out.
+pub fn logout() -> Result<()> {
+    let config = load_config()?;
+    let host = deepseek_host(&config);
+    let mut removed = Vec::new();
+    let attributes = [("host", host.as_str())];
+    if secret_tool_lookup(&attributes).is_some()
+        && secret_tool(&["clear"], &attributes).status()?.success()
+    {
+        removed.push(Store::SecretService);
+    }
+    if credential_fill(&host, Some(USERNAME)).is_some() {
+        credential("reject", &host, Some(USERNAME), None);
+        removed.push(Store::GitCredential);
+    }
+
+    if removed.is_empty() {
+        println!("No stored DeepSeek API key for {host}.");
+    }
+    for store in removed {
+        style::success(&format!(
+            "Removed the DeepSeek API key from {}",
+            store.describe()
+        ));
+    }
+    if let Some(secret) = deepseek_key(&config)? {
+        println!("A key is still provided by {}.", secret.source.describe());
+    }
+    Ok(())
+}
+
+/// `auth status`: where the DeepSeek key and the GitHub token come from.
+pub fn status() -> Result<()> {
+    let config = load_config()?;
+    let deepseek = deepseek_key(&config)?;
+    let github = github::find_token(&config)?;
+    for (name, secret) in [("DeepSeek API key", &deepseek), ("GitHub token", &github)] {
+        match secret {
+            Some(secret) => println!(
+                "{} {} from {}",
+                style::bold(&format!("{name}:")),
+                mask(&secret.value),
+                secret.source.describe()
+            ),
+            None => println!(
+                "{} {}",
+                style::bold(&format!("{name}:")),
+                style::dim("not found")
+            ),
+        }
+    }
+    if let Some(Source::Config(origin)) = deepseek.as_ref().map(|s| &s.source) {
+        println!(
+            "{}",
+            style::dim(&format!(
+                "The key is stored in plain text ({origin}); `git-github auth login` keeps it in \
+                 a keyring or credential helper instead."
+            ))
+        );
+    }
+    Ok(())
+}
+
+/// `sk-abc…wxyz` → `****wxyz`.
+pub(crate) fn mask(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("****{tail}")
+}
+
+/// Prompt for a secret without echoing it, or read a line from piped stdin.
+fn read_secret(prompt: &str) -> Result<String> {
+    let stdin = io::stdin();
+    if !stdin.is_terminal() {
+        let mut line = String::new();
+        stdin.lock().read_line(&mut line)?;
+        return Ok(line.trim().to_string());
+    }
+    eprint!("{prompt}");
+    io::stderr().flush()?;
+    let line = without_echo(|| {
+        let mut line = String::new();
+        stdin.lock().read_line(&mut line).map(|_| line)
+    })?;
+    eprintln!();
+    Ok(line.trim().to_string())
+}
+
+/// Run `read` with terminal echo turned off.
+#[cfg(unix)]
+fn without_echo<T>(read: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+    use std::os::unix::io::AsRawFd;
+
+    let fd = io::stdin().as_raw_fd();
+    let mut orig: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(fd, &mut orig) } != 0 {
+        return read();
+    }
+    let mut quiet = orig;
+    quiet.c_lflag &= !libc::ECHO;
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &quiet) };
+    let result = read();
+    // Always restore the terminal before returning.
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &orig) };
+    result
+}
+
+#[cfg(not(unix))]
+fn without_echo<T>(read: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+    read()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hosts_of_api_urls() {
+        assert_eq!(host_of("https://api.deepseek.com"), "api.deepseek.com");
+        assert_eq!(
+            host_of("https://llm.example.com:8443/v1/"),
+            "llm.example.com:8443"
+        );
+        assert_eq!(host_of("api.deepseek.com"), "api.deepseek.com");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn key_commands_take_the_first_line() {
+        assert_eq!(
+            run_command("api_key_command", "printf 'sk-1\\nurl: x\\n'").unwrap(),
+            "sk-1"
+        );
+        let err = run_command("api_key_command", "exit 3")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "api_key_command `exit 3` failed: it exited with code 3"
+        );
+        let err = run_command("api_key_command", "true")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("printed nothing"), "{err}");
+    }
+
+    #[test]
+    fn masks_secrets() {
+        assert_eq!(mask("sk-1234567890abcd"), "****abcd");
+        assert_eq!(mask("short"), "*****");
+    }
+}
@@ -1,6 +1,7 @@
-use clap::Parser;
-use git_github::ai::{self, CommitMode, StageMode};
-use git_github::report;
+use clap::{Parser, Subcommand, ValueEnum};
+use git_github::ai::{self, CommitKind, CommitMode, Options, StageMode};
+use git_github::{history, hook, profile, prompt, report, reword, usage, Output};
+use std::path::PathBuf;
 use std::process::ExitCode;
 
 /// AI commit. Commits the staged changes with an AI-generated message.
@@ -9,6 +10,9 @@ use std::process::ExitCode;
 #[derive(Parser, Debug)]
 #[clap(name = "git-ac", version)]
 struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// Stage all changes before committing (like `git add -A`)
     #[clap(short, long)]
     all: bool,
@@ -24,10 +28,177 @@ struct Cli {
     /// Only preview the message; do not stage or commit
     #[clap(short, long)]
     preview: bool,
+
+    /// Regenerate the message for HEAD, folding in any staged changes
+    #[clap(long, conflicts_with_all = ["fixup", "squash"])]
+    amend: bool,
+
+    /// Create a fixup! commit for the recent commit the staged changes belong to
+    #[clap(long, conflicts_with_all = ["squash", "preview", "edit"])]
+    fixup: bool,
+
+    /// Like --fixup, but a squash! commit with a generated message
+    #[clap(long)]
+    squash: bool,
+
+    /// Regenerate the messages of every commit in BASE..HEAD and rewrite them
+    #[clap(
+        long,
+        value_name = "RANGE",
+        conflicts_with_all = ["all", "update", "edit", "preview", "amend", "fixup", "squash"]
+    )]
+    reword_range: Option<String>,
+
+    /// With --reword-range, rewrite commits even if they are already pushed
+    #[clap(short, long, requires = "reword_range")]
+    force: bool,
+
+    /// With --reword-range, rewrite without asking (required when not on a terminal)
+    #[clap(short, long, requires = "reword_range")]
+    yes: bool,
+
+    /// Reuse the last message generated for the same staged changes
+    #[clap(long, conflicts_with_all = ["candidates", "fixup", "reword_range"])]
+    last: bool,
+
+    /// Always ask the model, ignoring cached completions
+    #[clap(long)]
+    no_cache: bool,
+
+    /// Language to write the message in, e.g. `zh-CN` (default: config)
+    #[clap(long, value_name = "LANG")]
+    lang: Option<String>,
+
+    /// Generate N alternative messages and pick one (1-9)
+    #[clap(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u8).range(1..=9)
+    )]
+    candidates: u8,
+
+    /// Print only the final message; never prompt
+    #[clap(
+        short,
+        long,
+        conflicts_with_all = ["json", "candidates", "reword_range", "fixup", "squash"]
+    )]
+    quiet: bool,
+
+    /// Print only the final message and commit id, as JSON; never prompt
+    #[clap(long, conflicts_with_all = ["candidates", "reword_range", "fixup", "squash"])]
+    json: bool,
+
+    /// Add a Signed-off-by trailer for the committer
+    #[clap(short, long)]
+    signoff: bool,
+
+    /// Add a Co-authored-by trailer; an alias from [team] or .mailmap, or "Name <email>"
+    #[clap(long = "co-author", value_name = "ALIAS")]
+    co_authors: Vec<String>,
+
+    /// Apply the settings of this [profiles.<name>] section
+    #[clap(long, value_name = "NAME", global = true)]
+    profile: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Manage a prepare-commit-msg hook that drafts messages for plain `git commit`
+    Hook {
+        #[clap(subcommand)]
+        action: HookAction,
+    },
+    /// Summarize recorded token usage and estimated cost
+    Usage {
+        /// How to group the report
+        #[clap(long, value_enum, default_value_t = By::Day)]
+        by: By,
+
+        /// Only the last N days (0 for everything)
+        #[clap(long, default_value_t = 30)]
+        days: u64,
+    },
+    /// List generated messages, newest first, or print one in full
+    History {
+        /// Entry to print (1 is the newest)
+        index: Option<usize>,
+
+        /// How many entries to list
+        #[clap(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// List the prompt templates in effect, or print one to customize
+    Prompt {
+        /// Template to print: commit, pr, review or release-notes
+        name: Option<String>,
+    },
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum By {
+    Day,
+    Repo,
+    Model,
+}
+
+impl From<By> for usage::GroupBy {
+    fn from(by: By) -> Self {
+        match by {
+            By::Day => usage::GroupBy::Day,
+            By::Repo => usage::GroupBy::Repo,
+            By::Model => usage::GroupBy::Model,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum HookAction {
+    /// Install the hook in this repository, chaining any existing one
+    Install,
+    /// Remove the hook, restoring any hook it chained
+    Uninstall,
+    /// Invoked by the hook itself with git's prepare-commit-msg arguments
+    #[clap(hide = true)]
+    Run {
+        message_file: PathBuf,
+        source: Option<String>,
+        commit: Option<String>,
+    },
 }
 
 fn main() -> ExitCode {
     let cli = Cli::parse();
+    profile::select("ac", cli.profile, "origin");
+
+    match cli.command {
+        Some(Command::Hook { action }) => {
+            return report(match action {
+                HookAction::Install => hook::install(),
+                HookAction::Uninstall => hook::uninstall(),
+                HookAction::Run {
+                    message_file,
+                    source,
+                    ..
+                } => hook::run(&message_file, source.as_deref()),
+            })
+        }
+        Some(Command::History { index, limit }) => return report(history::show(index, limit)),
+        Some(Command::Usage { by, days }) => return report(usage::report(by.into(), days)),
+        Some(Command::Prompt { name }) => return report(prompt::show(name.as_deref())),
+        None => {}
+    }
+
+    if let Some(range) = &cli.reword_range {
+        return report(reword::run(
+            range,
+            cli.force,
+            cli.yes,
+            cli.no_cache,
+            cli.lang.as_deref(),
+        ));
+    }
 
     let stage = if cli.preview {
         StageMode::None
@@ -47,5 +218,32 @@ fn main() -> ExitCode {
         CommitMode::Apply
     };
 
-    report(ai::run(stage, mode))
+    let kind = if cli.amend {
+        CommitKind::Amend
+    } else if cli.fixup {
+        CommitKind::Fixup
+    } else if cli.squash {
+        CommitKind::Squash
+    } else {
+        CommitKind::New
+    };
+
+    report(ai::run(Options {
+        stage,
+        mode,
+        kind,
+        signoff: cli.signoff,
+        co_authors: cli.co_authors,
+        candidates: cli.candidates.into(),
+        last: cli.last,
+        no_cache: cli.no_cache,
+        output: if cli.json {
+            Output::Json
+        } else if cli.quiet {
+            Output::Quiet
+        } else {
+            Output::Normal
+        },
+        lang: cli.lang,
+    }))
 }
@@ -0,0 +1,66 @@
+use clap::{Parser, Subcommand};
+use git_github::ci::{self, Options};
+use git_github::{profile, report};
+use std::process::ExitCode;
+
+/// Show the GitHub Actions runs and checks of a commit, follow them until
+/// they finish, print the logs of failed jobs, or re-run them. Usable as
+/// `git ci`.
+#[derive(Parser, Debug)]
+#[clap(name = "git-ci", version, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    /// A branch, commit, or pull request (`#12`); defaults to HEAD
+    target: Option<String>,
+
+    /// Refresh until every check has finished; exits non-zero if any failed
+    #[clap(short, long)]
+    watch: bool,
+
+    /// Seconds between refreshes with --watch
+    #[clap(long, value_name = "SECS", default_value_t = 10)]
+    interval: u64,
+
+    /// Remote name
+    #[clap(short, long, default_value = "origin", global = true)]
+    remote: String,
+
+    /// Apply the settings of this [profiles.<name>] section
+    #[clap(long, value_name = "NAME", global = true)]
+    profile: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the logs of the failed jobs
+    Logs {
+        /// A branch, commit, or pull request (`#12`); defaults to HEAD
+        target: Option<String>,
+
+        /// Print only the last N lines of each log (0 for all)
+        #[clap(short = 'n', long, value_name = "N", default_value_t = 100)]
+        tail: usize,
+    },
+    /// Re-run the failed jobs of the failed workflow runs
+    Rerun {
+        /// A branch, commit, or pull request (`#12`); defaults to HEAD
+        target: Option<String>,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    profile::select("ci", cli.profile, &cli.remote);
+    report(match cli.command {
+        None => ci::status(Options {
+            remote: cli.remote,
+            target: cli.target,
+            watch: cli.watch,
+            interval: cli.interval,
+        }),
+        Some(Command::Logs { target, tail }) => ci::logs(&cli.remote, target, tail),
+        Some(Command::Rerun { target }) => ci::rerun(&cli.remote, target),
+    })
+}
@@ -0,0 +1,164 @@
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use git_github::auth::{self, Store};
+use git_github::doctor;
+use git_github::settings::{self, Scope};
+use git_github::{oauth, profile, report};
+use std::process::ExitCode;
+
+/// Settings and credentials shared by the git-github tools (`git ac`,
+/// `git pr`, …). Usable as `git github`.
+#[derive(Parser, Debug)]
+#[clap(name = "git-github", version)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+
+    /// Apply the settings of this [profiles.<name>] section
+    #[clap(long, value_name = "NAME", global = true)]
+    profile: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Read and change settings. Later layers override earlier ones: system,
+    /// user, repo (git-github.toml at the repository root), local
+    /// (.git/git-github.toml), `git config github-ai.*`, `GIT_GITHUB_*`
+    /// environment variables, then command-line flags
+    Config {
+        #[clap(subcommand)]
+        action: ConfigAction,
+    },
+    /// Check the configuration, API key, GitHub token, remotes and commit
+    /// hook, and report what is wrong
+    Doctor,
+    /// Store and inspect the DeepSeek API key outside the config files, and
+    /// sign in to GitHub
+    Auth {
+        #[clap(subcommand)]
+        action: AuthAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// List every setting that is set, with secrets masked
+    List {
+        /// Show the layer each value comes from
+        #[clap(long)]
+        show_origin: bool,
+    },
+    /// Print the effective value of a setting, e.g. `deepseek.model`
+    Get {
+        key: String,
+
+        /// Show the layer the value comes from
+        #[clap(long)]
+        show_origin: bool,
+    },
+    /// Set a value in one layer's file (the user config by default)
+    #[clap(group(ArgGroup::new("scope").multiple(false)))]
+    Set {
+        key: String,
+        value: String,
+
+        /// In /etc/git-github/config.toml
+        #[clap(long, group = "scope")]
+        system: bool,
+
+        /// In git-github.toml at the repository root
+        #[clap(long, group = "scope")]
+        repo: bool,
+
+        /// In .git/git-github.toml, private to this clone
+        #[clap(long, group = "scope")]
+        local: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum AuthAction {
+    /// Save the DeepSeek API key (prompted for, or read from stdin)
+    Login {
+        /// Where to keep it; by default the keyring when there is one, else
+        /// git's credential helper
+        #[clap(long, value_enum, conflicts_with = "github")]
+        store: Option<StoreArg>,
+
+        /// Sign in to GitHub in the browser instead (OAuth device flow;
+        /// GitHub Enterprise Server needs `github.oauth_client_id`)
+        #[clap(long)]
+        github: bool,
+
+        /// The GitHub host to sign in to (default: github.com, or that of
+        /// `github.api_url`)
+        #[clap(long, value_name = "HOST", requires = "github")]
+        hostname: Option<String>,
+    },
+    /// Remove the stored DeepSeek API key
+    Logout {
+        /// Forget the GitHub token from `auth login --github` instead
+        #[clap(long)]
+        github: bool,
+
+        /// The GitHub host to sign out of
+        #[clap(long, value_name = "HOST", requires = "github")]
+        hostname: Option<String>,
+    },
+    /// Show where the DeepSeek API key and the GitHub token come from
+    Status,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum StoreArg {
+    /// The freedesktop Secret Service (GNOME Keyring, KWallet), via secret-tool
+    SecretService,
+    /// `git credential approve`, into git's configured credential.helper
+    GitCredential,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    profile::select("github", cli.profile, "origin");
+    report(match cli.command {
+        Command::Config { action } => match action {
+            ConfigAction::List { show_origin } => settings::list(show_origin),
+            ConfigAction::Get { key, show_origin } => settings::get(&key, show_origin),
+            ConfigAction::Set {
+                key,
+                value,
+                system,
+                repo,
+                local,
+            } => {
+                let scope = if system {
+                    Scope::System
+                } else if repo {
+                    Scope::Repo
+                } else if local {
+                    Scope::Local
+                } else {
+                    Scope::User
+                };
+                settings::set(&key, &value, scope)
+            }
+        },
+        Command::Doctor => doctor::run(),
+        Command::Auth { action } => match action {
+            AuthAction::Login {
+                github: true,
+                hostname,
+                ..
+            } => oauth::login(hostname),
+            AuthAction::Login { store, .. } => auth::login(store.map(|store| match store {
+                StoreArg::SecretService => Store::SecretService,
+                StoreArg::GitCredential => Store::GitCredential,
+            })),
+            AuthAction::Logout {
+                github: true,
+                hostname,
+            } => oauth::logout(hostname),
+            AuthAction::Logout { .. } => auth::logout(),
+            AuthAction::Status => auth::status(),
+        },
+    })
+}
@@ -1,5 +1,5 @@
 use clap::{Parser, ValueEnum};
-use git_github::{issue, report};
+use git_github::{issue, profile, report};
 use std::process::ExitCode;
 
 /// List the repository's GitHub issues. Usable as `git issues`.
@@ -13,6 +13,10 @@ struct Cli {
     /// Remote name
     #[clap(short, long, default_value = "origin")]
     remote: String,
+
+    /// Apply the settings of this [profiles.<name>] section
+    #[clap(long, value_name = "NAME")]
+    profile: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
@@ -34,5 +38,6 @@ impl From<State> for octocrab::params::State {
 
 fn main() -> ExitCode {
     let cli = Cli::parse();
+    profile::select("issues", cli.profile, &cli.remote);
     report(issue::list_issues(&cli.remote, cli.state.into()))
 }
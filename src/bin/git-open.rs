@@ -1,6 +1,6 @@
 use clap::Parser;
 use git_github::open::{self, OpenTarget};
-use git_github::report;
+use git_github::{profile, report};
 use std::process::ExitCode;
 
 /// Open the GitHub repo page in your browser. Usable as `git open`.
@@ -22,10 +22,15 @@ struct Cli {
     /// Remote name
     #[clap(short, long, default_value = "origin")]
     remote: String,
+
+    /// Apply the settings of this [profiles.<name>] section
+    #[clap(long, value_name = "NAME")]
+    profile: Option<String>,
 }
 
 fn main() -> ExitCode {
     let cli = Cli::parse();
+    profile::select("open", cli.profile, &cli.remote);
 
     let target = if let Some(path) = cli.path {
         let (path, start, end) = open::parse_file_arg(&path);
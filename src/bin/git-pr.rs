@@ -1,13 +1,17 @@
-use clap::Parser;
-use git_github::pr::{self, Options};
-use git_github::report;
+use clap::{Parser, Subcommand};
+use git_github::pr::{self, ListOptions, MergeOptions, Method, Options};
+use git_github::{profile, report, Output};
 use std::process::ExitCode;
 
 /// Create a GitHub pull request for the current branch with an AI-generated
-/// title and description. Usable as `git pr`.
+/// title and description; list, review the status of, or merge pull
+/// requests. Usable as `git pr`.
 #[derive(Parser, Debug)]
-#[clap(name = "git-pr", version)]
+#[clap(name = "git-pr", version, args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// Base branch to merge into (defaults to the repo's default branch)
     #[clap(short, long)]
     base: Option<String>,
@@ -24,18 +28,181 @@ struct Cli {
     #[clap(long)]
     no_push: bool,
 
+    /// Always ask the model, ignoring cached drafts
+    #[clap(long)]
+    no_cache: bool,
+
     /// Remote name
-    #[clap(short, long, default_value = "origin")]
+    #[clap(short, long, default_value = "origin", global = true)]
     remote: String,
+
+    /// Apply the settings of this [profiles.<name>] section
+    #[clap(long, value_name = "NAME", global = true)]
+    profile: Option<String>,
+
+    /// Language for the AI-written text, e.g. `zh-CN` (default: config)
+    #[clap(long, value_name = "LANG", global = true)]
+    lang: Option<String>,
+
+    /// Print only the pull request's URL
+    #[clap(short, long, conflicts_with = "json")]
+    quiet: bool,
+
+    /// Print only the created pull request, as JSON
+    #[clap(long)]
+    json: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List the repository's pull requests
+    List {
+        /// Only those by this user (`@me` for yourself)
+        #[clap(short, long, value_name = "USER")]
+        author: Option<String>,
+
+        /// Only those awaiting a review from this user (`@me` for yourself)
+        #[clap(long, value_name = "USER")]
+        review_requested: Option<String>,
+
+        /// Only drafts
+        #[clap(short, long)]
+        draft: bool,
+
+        /// Only those with this label (repeatable)
+        #[clap(short, long = "label", value_name = "LABEL")]
+        labels: Vec<String>,
+
+        /// Only those into this branch
+        #[clap(short, long)]
+        base: Option<String>,
+
+        /// open, closed, merged or all
+        #[clap(
+            short,
+            long,
+            default_value = "open",
+            value_parser = ["open", "closed", "merged", "all"]
+        )]
+        state: String,
+
+        /// Show at most this many
+        #[clap(short = 'L', long, default_value_t = 30)]
+        limit: usize,
+    },
+    /// Show the current branch's pull request, yours, and those awaiting
+    /// your review, with their checks and reviews
+    Status,
+    /// Merge a pull request once its checks pass, then delete its branch
+    /// and switch back to the updated base branch
+    Merge {
+        /// The pull request; defaults to the current branch's
+        number: Option<u64>,
+
+        /// Create a merge commit (the default when the repository allows it)
+        #[clap(long, group = "method")]
+        merge: bool,
+
+        /// Squash the commits into one, with an AI-written message
+        #[clap(short, long, group = "method")]
+        squash: bool,
+
+        /// Rebase the commits onto the base branch
+        #[clap(long, group = "method")]
+        rebase: bool,
+
+        /// Have GitHub merge it once its requirements are met
+        #[clap(long)]
+        auto: bool,
+
+        /// Keep GitHub's squash message instead of writing one
+        #[clap(long)]
+        no_ai: bool,
+
+        /// Edit the commit message before merging
+        #[clap(short, long)]
+        edit: bool,
+
+        /// Don't ask for confirmation
+        #[clap(short, long)]
+        yes: bool,
+
+        /// Keep the branch, on GitHub and locally
+        #[clap(long)]
+        keep_branch: bool,
+
+        /// Seconds between refreshes while waiting for checks
+        #[clap(long, value_name = "SECS", default_value_t = 10)]
+        interval: u64,
+    },
 }
 
 fn main() -> ExitCode {
     let cli = Cli::parse();
-    report(pr::create(Options {
-        remote: cli.remote,
-        base: cli.base,
-        draft: cli.draft,
-        edit: cli.edit,
-        no_push: cli.no_push,
-    }))
+    profile::select("pr", cli.profile, &cli.remote);
+    report(match cli.command {
+        Some(Command::List {
+            author,
+            review_requested,
+            draft,
+            labels,
+            base,
+            state,
+            limit,
+        }) => pr::list(ListOptions {
+            remote: cli.remote,
+            state,
+            author,
+            review_requested,
+            draft,
+            labels,
+            base,
+            limit,
+        }),
+        Some(Command::Status) => pr::status(&cli.remote),
+        Some(Command::Merge {
+            number,
+            merge,
+            squash,
+            rebase,
+            auto,
+            no_ai,
+            edit,
+            yes,
+            keep_branch,
+            interval,
+        }) => pr::merge(MergeOptions {
+            remote: cli.remote,
+            number,
+            method: match (merge, squash, rebase) {
+                (true, _, _) => Some(Method::Merge),
+                (_, true, _) => Some(Method::Squash),
+                (_, _, true) => Some(Method::Rebase),
+                _ => None,
+            },
+            auto,
+            no_ai,
+            edit,
+            yes,
+            keep_branch,
+            interval,
+            lang: cli.lang,
+        }),
+        None => pr::create(Options {
+            remote: cli.remote,
+            base: cli.base,
+            draft: cli.draft,
+            edit: cli.edit,
+            no_push: cli.no_push,
+            no_cache: cli.no_cache,
+            output: if cli.json {
+                Output::Json
+            } else if cli.quiet {
+                Output::Quiet
+            } else {
+                Output::Normal
+            },
+            lang: cli.lang,
+        }),
+    })
 }
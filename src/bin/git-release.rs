@@ -0,0 +1,91 @@
+use clap::Parser;
+use git_github::release::{self, Options};
+use git_github::{profile, report};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Tag a release: notes from the commits and merged pull requests since the
+/// previous tag, added to CHANGELOG.md, an annotated tag, and a GitHub
+/// release; or, with --suggest, propose the next version. Usable as
+/// `git release`.
+#[derive(Parser, Debug)]
+#[clap(name = "git-release", version)]
+struct Cli {
+    /// The new tag, e.g. v1.2.0
+    #[clap(required_unless_present = "suggest")]
+    tag: Option<String>,
+
+    /// Suggest the next semantic version from the commits instead
+    #[clap(long, conflicts_with = "tag")]
+    suggest: bool,
+
+    /// With --suggest, set it as the version in Cargo.toml / package.json
+    #[clap(long, requires = "suggest")]
+    bump: bool,
+
+    /// The previous release; defaults to the nearest tag
+    #[clap(long, value_name = "TAG")]
+    since: Option<String>,
+
+    /// Publish the release instead of creating a draft
+    #[clap(long)]
+    publish: bool,
+
+    /// Attach a file to the release (repeatable)
+    #[clap(short, long = "asset", value_name = "FILE")]
+    assets: Vec<PathBuf>,
+
+    /// Have the AI write the notes (or classify commits) even for
+    /// Conventional Commits
+    #[clap(long)]
+    ai: bool,
+
+    /// Edit the notes before tagging
+    #[clap(short, long)]
+    edit: bool,
+
+    /// Don't ask for confirmation
+    #[clap(short, long)]
+    yes: bool,
+
+    /// Leave CHANGELOG.md alone
+    #[clap(long)]
+    no_changelog: bool,
+
+    /// Only tag locally: don't push or create the GitHub release
+    #[clap(long)]
+    no_push: bool,
+
+    /// Language for AI-written notes (e.g. zh-CN, Japanese)
+    #[clap(long, value_name = "LANG")]
+    lang: Option<String>,
+
+    /// Remote name
+    #[clap(short, long, default_value = "origin")]
+    remote: String,
+
+    /// Apply the settings of this [profiles.<name>] section
+    #[clap(long, value_name = "NAME")]
+    profile: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    profile::select("release", cli.profile, &cli.remote);
+    let Some(tag) = cli.tag else {
+        return report(release::suggest(cli.since, cli.ai, cli.bump));
+    };
+    report(release::release(Options {
+        remote: cli.remote,
+        tag,
+        since: cli.since,
+        publish: cli.publish,
+        assets: cli.assets,
+        ai: cli.ai,
+        edit: cli.edit,
+        yes: cli.yes,
+        no_changelog: cli.no_changelog,
+        no_push: cli.no_push,
+        lang: cli.lang,
+    }))
+}
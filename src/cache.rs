@@ -0,0 +1,214 @@
+//! On-disk cache of model completions, so `git ac -p` followed by `git ac` on
+//! the same staged changes (or a retry after a failed commit hook) costs one
+//! request instead of two. Entries live in `$XDG_CACHE_HOME/git-github` (else
+//! `~/.cache/git-github`), one file per request, keyed by a hash of the model,
+//! temperature and messages. Caching is best-effort throughout: an unreadable
+//! or unwritable cache just means a real request.
+
+use crate::config::CacheConfig;
+use crate::llm::ChatMessage;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// Where and how long completions are kept.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+    max_bytes: u64,
+    /// Whether hits are served; a write-only cache still stores new results.
+    read: bool,
+}
+
+impl Cache {
+    /// The cache described by `config`, or `None` when it is disabled or no
+    /// cache directory can be determined.
+    pub fn open(config: &CacheConfig) -> Option<Cache> {
+        if !config.enabled {
+            return None;
+        }
+        Some(Cache {
+            dir: cache_dir()?,
+            ttl: Duration::from_secs(config.ttl_hours * 3600),
+            max_bytes: config.max_size_kb * 1024,
+            read: true,
+        })
+    }
+
+    /// A handle that records completions without serving cached ones, for
+    /// regenerating: the user asked for something new, and the next run
+    /// should get the latest result.
+    pub fn write_only(&self) -> Cache {
+        Cache {
+            read: false,
+            ..self.clone()
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.txt"))
+    }
+
+    /// The completion stored under `key`, if it is younger than the TTL.
+    pub fn get(&self, key: &str) -> Option<String> {
+        if !self.read {
+            return None;
+        }
+        let path = self.path(key);
+        let age = fs::metadata(&path).ok()?.modified().ok()?.elapsed().ok()?;
+        if age >= self.ttl {
+            return None;
+        }
+        fs::read_to_string(path).ok()
+    }
+
+    /// Store `text` under `key`, then trim the cache to its limits.
+    pub fn put(&self, key: &str, text: &str) {
+        if fs::create_dir_all(&self.dir).is_ok() && fs::write(self.path(key), text).is_ok() {
+            self.prune();
+        }
+    }
+
+    /// Drop expired entries, then the oldest ones until the total size fits.
+    fn prune(&self) {
+        let Ok(dir) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut entries: Vec<(PathBuf, SystemTime, u64)> = dir
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "txt" {
+                    return None;
+                }
+                let metadata = fs::metadata(&path).ok()?;
+                Some((path, metadata.modified().ok()?, metadata.len()))
+            })
+            .collect();
+        entries.sort_by_key(|&(_, modified, _)| std::cmp::Reverse(modified));
+
+        let mut total = 0;
+        for (path, modified, len) in entries {
+            let expired = modified.elapsed().is_ok_and(|age| age >= self.ttl);
+            total += len;
+            if expired || total > self.max_bytes {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// `$XDG_CACHE_HOME/git-github`, falling back to `~/.cache/git-github`. A
+/// relative `XDG_CACHE_HOME` is invalid per the spec and ignored.
+fn cache_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| {
+            env::var_os("HOME")
+                .or_else(|| env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".cache"))
+        })?;
+    Some(base.join("git-github"))
+}
+
+/// The cache key for a request: FNV-1a over everything that shapes the
+/// completion, including the provider's `base_url` (two providers may share
+/// a model name). Stable across runs and builds, unlike `std`'s hasher.
+pub fn key(
+    base_url: &str,
+    model: &str,
+    temperature: Option<f32>,
+    messages: &[ChatMessage],
+) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        // Separator, so ("ab", "c") and ("a", "bc") differ.
+        hash ^= 0xff;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    };
+    feed(base_url.as_bytes());
+    feed(model.as_bytes());
+    feed(format!("{temperature:?}").as_bytes());
+    for message in messages {
+        feed(message.role.as_bytes());
+        feed(message.content.as_bytes());
+    }
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn scratch(name: &str, ttl: Duration, max_bytes: u64) -> Cache {
+        let dir = env::temp_dir().join(format!("git-github-cache-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Cache {
+            dir,
+            ttl,
+            max_bytes,
+            read: true,
+        }
+    }
+
+    #[test]
+    fn keys_depend_on_every_input() {
+        let messages = vec![ChatMessage::system("be brief"), ChatMessage::user("diff")];
+        let url = "https://api.deepseek.com";
+        let base = key(url, "deepseek-chat", Some(0.7), &messages);
+        assert_eq!(base, key(url, "deepseek-chat", Some(0.7), &messages));
+        let local = "http://localhost:11434/v1";
+        assert_ne!(base, key(local, "deepseek-chat", Some(0.7), &messages));
+        assert_ne!(base, key(url, "deepseek-chat", None, &messages));
+        assert_ne!(base, key(url, "deepseek-reasoner", Some(0.7), &messages));
+        assert_ne!(base, key(url, "deepseek-chat", Some(0.7), &messages[..1]));
+        let split = |a, b| {
+            key(
+                url,
+                "m",
+                None,
+                &[ChatMessage::user(a), ChatMessage::user(b)],
+            )
+        };
+        assert_ne!(split("ab", "c"), split("a", "bc"));
+    }
+
+    #[test]
+    fn serves_fresh_entries_only() {
+        let cache = scratch("ttl", Duration::from_secs(3600), 1024);
+        cache.put("k", "Add a thing");
+        assert_eq!(cache.get("k").as_deref(), Some("Add a thing"));
+        assert_eq!(cache.write_only().get("k"), None);
+
+        let expired = Cache {
+            ttl: Duration::ZERO,
+            ..cache.clone()
+        };
+        assert_eq!(expired.get("k"), None);
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn prunes_beyond_the_size_limit() {
+        let cache = scratch("size", Duration::from_secs(3600), 10);
+        cache.put("old", "123456");
+        // Make the first entry measurably older than the second.
+        let old = fs::File::options()
+            .write(true)
+            .open(cache.path("old"))
+            .unwrap();
+        old.set_modified(SystemTime::now() - Duration::from_secs(60))
+            .unwrap();
+        cache.put("new", "123456");
+
+        assert_eq!(cache.get("old"), None);
+        assert_eq!(cache.get("new").as_deref(), Some("123456"));
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+}
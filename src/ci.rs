@@ -0,0 +1,749 @@
+//! `git ci`: the GitHub Actions workflow runs and check runs of a commit —
+//! HEAD, a branch or a pull request's head — as a table that can follow them
+//! until they finish, with the logs of failed jobs and a way to re-run them.
+
+use crate::config::load_github_config;
+use crate::error::{Error, Result};
+use crate::github;
+use crate::remote::Remote;
+use crate::repo::Repo;
+use crate::style;
+use octocrab::Octocrab;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How long `--watch` waits for the first checks of a fresh push to show up.
+const START_TIMEOUT: Duration = Duration::from_secs(120);
+/// Check runs by GitHub Actions, whose ids are job ids.
+const ACTIONS_APP: &str = "github-actions";
+
+pub struct Options {
+    pub remote: String,
+    /// A branch, commit, or pull request (`#12`); HEAD by default.
+    pub target: Option<String>,
+    /// Refresh until every check has finished.
+    pub watch: bool,
+    /// Seconds between refreshes.
+    pub interval: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CheckRun {
+    id: u64,
+    name: String,
+    status: String,
+    conclusion: Option<String>,
+    started_at: Option<String>,
+    completed_at: Option<String>,
+    html_url: Option<String>,
+    check_suite: Option<Suite>,
+    app: Option<App>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Suite {
+    id: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct App {
+    slug: Option<String>,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct CheckRuns {
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct WorkflowRun {
+    id: u64,
+    name: Option<String>,
+    status: Option<String>,
+    conclusion: Option<String>,
+    check_suite_id: Option<u64>,
+    run_number: u64,
+    event: String,
+}
+
+#[derive(Deserialize)]
+struct WorkflowRuns {
+    workflow_runs: Vec<WorkflowRun>,
+}
+
+#[derive(Deserialize)]
+struct CheckSuites {
+    total_count: u64,
+}
+
+/// A branch, for the checks its protection requires.
+#[derive(Deserialize)]
+struct Branch {
+    #[serde(default)]
+    protection: Option<Protection>,
+}
+
+#[derive(Deserialize)]
+struct Protection {
+    #[serde(default)]
+    required_status_checks: Option<RequiredChecks>,
+}
+
+#[derive(Deserialize)]
+struct RequiredChecks {
+    #[serde(default)]
+    contexts: Vec<String>,
+}
+
+/// Where a check stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Queued,
+    Running,
+    Passed,
+    Failed,
+    Skipped,
+}
+
+impl State {
+    fn of(status: &str, conclusion: Option<&str>) -> State {
+        match (status, conclusion) {
+            ("completed", Some("success")) => State::Passed,
+            ("completed", Some("skipped" | "neutral")) => State::Skipped,
+            ("completed", _) => State::Failed,
+            ("in_progress", _) => State::Running,
+            _ => State::Queued,
+        }
+    }
+
+    fn done(self) -> bool {
+        !matches!(self, State::Queued | State::Running)
+    }
+
+    fn mark(self) -> String {
+        match (self, style::enabled()) {
+            (State::Passed, true) => style::green("✓"),
+            (State::Failed, true) => style::red("✗"),
+            (State::Running, true) => style::yellow("●"),
+            (State::Queued, true) => style::dim("○"),
+            (State::Skipped, true) => style::dim("-"),
+            (State::Passed, false) => "pass".to_string(),
+            (State::Failed, false) => "FAIL".to_string(),
+            (State::Running, false) => "run ".to_string(),
+            (State::Queued, false) => "wait".to_string(),
+            (State::Skipped, false) => "skip".to_string(),
+        }
+    }
+}
+
+impl CheckRun {
+    fn state(&self) -> State {
+        State::of(&self.status, self.conclusion.as_deref())
+    }
+
+    fn is_job(&self) -> bool {
+        self.app.as_ref().and_then(|app| app.slug.as_deref()) == Some(ACTIONS_APP)
+    }
+}
+
+impl WorkflowRun {
+    fn state(&self) -> State {
+        State::of(
+            self.status.as_deref().unwrap_or("queued"),
+            self.conclusion.as_deref(),
+        )
+    }
+
+    fn title(&self) -> String {
+        let name = self.name.as_deref().unwrap_or("workflow");
+        format!("{name} · {} · run #{}", self.event, self.run_number)
+    }
+}
+
+/// The commit the checks are for, and how to call it.
+struct Commit {
+    sha: String,
+    label: String,
+}
+
+/// Everything GitHub reports for the commit at one moment.
+struct Snapshot {
+    workflows: Vec<WorkflowRun>,
+    checks: Vec<CheckRun>,
+}
+
+impl Snapshot {
+    async fn fetch(client: &Octocrab, remote: &Remote, sha: &str) -> Result<Snapshot> {
+        let repo = format!("/repos/{}/{}", remote.user, remote.repo);
+        let checks: CheckRuns = client
+            .get(
+                format!("{repo}/commits/{sha}/check-runs"),
+                Some(&[("per_page", "100"), ("filter", "latest")]),
+            )
+            .await?;
+        let workflows: WorkflowRuns = client
+            .get(
+                format!("{repo}/actions/runs"),
+                Some(&[("head_sha", sha), ("per_page", "100")]),
+            )
+            .await?;
+        // Each run already stands for its latest attempt; one workflow can still
+        // run more than once for a commit, e.g. on `push` and `pull_request`.
+        let mut workflows = workflows.workflow_runs;
+        workflows.sort_by(|a, b| (&a.name, &a.event).cmp(&(&b.name, &b.event)));
+        Ok(Snapshot {
+            workflows,
+            checks: checks.check_runs,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.workflows.is_empty() && self.checks.is_empty()
+    }
+
+    fn done(&self) -> bool {
+        !self.is_empty()
+            && self.workflows.iter().all(|run| run.state().done())
+            && self.checks.iter().all(|check| check.state().done())
+    }
+
+    fn failed(&self) -> Vec<&CheckRun> {
+        let mut failed: Vec<&CheckRun> = self
+            .checks
+            .iter()
+            .filter(|check| check.state() == State::Failed)
+            .collect();
+        failed.sort_by(|a, b| a.name.cmp(&b.name));
+        failed
+    }
+
+    /// How many things failed: the failed checks, plus workflow runs that
+    /// failed without a failed job to show for it, such as a workflow file
+    /// GitHub couldn't start.
+    fn failures(&self) -> usize {
+        let failed = self.failed();
+        let runs = self
+            .workflows
+            .iter()
+            .filter(|run| run.state() == State::Failed)
+            .filter(|run| {
+                !failed
+                    .iter()
+                    .any(|check| check.check_suite.as_ref().map(|s| s.id) == run.check_suite_id)
+            })
+            .count();
+        failed.len() + runs
+    }
+
+    /// The checks under a heading each: one per workflow run, then one per
+    /// app for checks from outside Actions (e.g. a code coverage service).
+    fn groups(&self) -> Vec<(String, State, Vec<&CheckRun>)> {
+        let mut groups = Vec::new();
+        let mut rest: Vec<&CheckRun> = self.checks.iter().collect();
+        for run in &self.workflows {
+            let (mut jobs, others): (Vec<&CheckRun>, Vec<&CheckRun>) = rest
+                .into_iter()
+                .partition(|check| check.check_suite.as_ref().map(|s| s.id) == run.check_suite_id);
+            rest = others;
+            jobs.sort_by(|a, b| a.name.cmp(&b.name));
+            groups.push((run.title(), run.state(), jobs));
+        }
+        let mut by_app: BTreeMap<String, Vec<&CheckRun>> = BTreeMap::new();
+        for check in rest {
+            let app = check.app.as_ref().map_or("checks", |app| app.name.as_str());
+            by_app.entry(app.to_string()).or_default().push(check);
+        }
+        for (app, mut checks) in by_app {
+            checks.sort_by(|a, b| a.name.cmp(&b.name));
+            let state = overall(checks.iter().map(|check| check.state()));
+            groups.push((app, state, checks));
+        }
+        groups
+    }
+
+    /// The table, one line per entry, as of `now` (seconds since the epoch).
+    fn render(&self, commit: &Commit, now: u64) -> Vec<String> {
+        let short = &commit.sha[..commit.sha.len().min(7)];
+        let mut lines = vec![style::bold(&format!(
+            "Checks for {} ({short})",
+            commit.label
+        ))];
+        if self.is_empty() {
+            lines.push(style::dim("  no checks yet"));
+            return lines;
+        }
+        let groups = self.groups();
+        let width = groups
+            .iter()
+            .flat_map(|(_, _, checks)| checks.iter().map(|check| check.name.chars().count()))
+            .max()
+            .unwrap_or(0)
+            .min(40);
+        for (title, state, checks) in &groups {
+            lines.push(format!("{} {}", state.mark(), style::bold(title)));
+            for check in checks {
+                let state = check.state();
+                let name = format!("{:<width$}", style::truncate(&check.name, width));
+                let mut line = format!("  {} {name}  {}", state.mark(), describe(check, now));
+                if state == State::Failed {
+                    if let Some(url) = &check.html_url {
+                        line.push_str(&format!("  {}", style::dim(url)));
+                    }
+                }
+                lines.push(line);
+            }
+        }
+        lines.push(summary(self.checks.iter().map(|check| check.state())));
+        lines
+    }
+}
+
+/// The state of a group from that of its checks: failed as soon as one
+/// fails, else running while any isn't done.
+fn overall(states: impl Iterator<Item = State>) -> State {
+    let states: Vec<State> = states.collect();
+    if states.contains(&State::Failed) {
+        State::Failed
+    } else if states.contains(&State::Running) {
+        State::Running
+    } else if states.contains(&State::Queued) {
+        State::Queued
+    } else if states.iter().all(|state| *state == State::Skipped) && !states.is_empty() {
+        State::Skipped
+    } else {
+        State::Passed
+    }
+}
+
+/// `failure 1m02s`, `running 0m40s`, `queued`.
+fn describe(check: &CheckRun, now: u64) -> String {
+    let started = check.started_at.as_deref().and_then(epoch);
+    let finished = check.completed_at.as_deref().and_then(epoch);
+    let elapsed = match (started, finished) {
+        (Some(start), Some(end)) => Some(end.saturating_sub(start)),
+        (Some(start), None) => Some(now.saturating_sub(start)),
+        _ => None,
+    };
+    let word = match check.state() {
+        State::Running => "running".to_string(),
+        State::Queued => "queued".to_string(),
+        _ => check.conclusion.clone().unwrap_or_default(),
+    };
+    match elapsed.filter(|_| check.state() != State::Queued) {
+        Some(secs) => style::dim(&format!("{word} {}m{:02}s", secs / 60, secs % 60)),
+        None => style::dim(&word),
+    }
+}
+
+/// `4 checks: 2 passed, 1 failed, 1 running`.
+fn summary(states: impl Iterator<Item = State>) -> String {
+    let states: Vec<State> = states.collect();
+    let count = |state| states.iter().filter(|s| **s == state).count();
+    let parts: Vec<String> = [
+        (State::Passed, "passed"),
+        (State::Failed, "failed"),
+        (State::Running, "running"),
+        (State::Queued, "queued"),
+        (State::Skipped, "skipped"),
+    ]
+    .into_iter()
+    .filter(|(state, _)| count(*state) > 0)
+    .map(|(state, word)| format!("{} {word}", count(state)))
+    .collect();
+    let noun = if states.len() == 1 { "check" } else { "checks" };
+    format!("{} {noun}: {}", states.len(), parts.join(", "))
+}
+
+/// Seconds since the epoch of an RFC 3339 UTC timestamp as GitHub writes
+/// them, `2024-05-01T12:00:00Z`.
+fn epoch(timestamp: &str) -> Option<u64> {
+    let (date, time) = timestamp.trim_end_matches('Z').split_once('T')?;
+    let mut date = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let mut time = time
+        .splitn(3, ':')
+        .map(|part| part.split('.').next()?.parse::<i64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    // Howard Hinnant's `days_from_civil`.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    u64::try_from(days * 86_400 + hour * 3600 + minute * 60 + second).ok()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// The repository's remote, and the commit `target` names: a pull request
+/// (`#12`), a branch or commit on GitHub, or the local HEAD.
+async fn resolve(
+    client: &Octocrab,
+    repo: &Repo,
+    remote: &Remote,
+    target: Option<&str>,
+) -> Result<Commit> {
+    let base = format!("/repos/{}/{}", remote.user, remote.repo);
+    let Some(target) = target else {
+        return Ok(Commit {
+            sha: repo.head_commit_id()?,
+            label: repo.current_branch().unwrap_or_else(|_| "HEAD".to_string()),
+        });
+    };
+    // Only `#12` is a pull request; a bare `12` may be a branch or an
+    // abbreviated commit id.
+    if let Some(number) = target.strip_prefix('#').and_then(|n| n.parse::<u64>().ok()) {
+        let pull: serde_json::Value = client
+            .get(format!("{base}/pulls/{number}"), None::<&()>)
+            .await?;
+        return Ok(Commit {
+            sha: pull["head"]["sha"].as_str().unwrap_or_default().to_string(),
+            label: format!("#{number}"),
+        });
+    }
+    let commit: serde_json::Value = client
+        .get(format!("{base}/commits/{target}"), None::<&()>)
+        .await?;
+    Ok(Commit {
+        sha: commit["sha"].as_str().unwrap_or_default().to_string(),
+        label: target.to_string(),
+    })
+}
+
+fn open_repo(remote: &str) -> Result<(Repo, Remote)> {
+    let path = env::current_dir().map_err(|_| Error::NoCurrentDir)?;
+    let repo = Repo::new(&path)?;
+    let remote = repo.remote(remote)?;
+    Ok((repo, remote))
+}
+
+/// Print the table of `commit`'s checks; with `watch`, redraw it until
+/// everything has finished (or no checks turned up in time).
+async fn follow(
+    client: &Octocrab,
+    remote: &Remote,
+    commit: &Commit,
+    watch: bool,
+    interval: Duration,
+) -> Result<Snapshot> {
+    let started = Instant::now();
+    let mut printed = 0;
+    loop {
+        let snapshot = Snapshot::fetch(client, remote, &commit.sha).await?;
+        let gave_up = snapshot.is_empty() && started.elapsed() >= START_TIMEOUT;
+        let finished = !watch || snapshot.done() || gave_up;
+        // On a terminal the table is redrawn in place; piped, only the
+        // final one is printed.
+        if style::enabled() || finished {
+            style::erase_lines(printed);
+            let lines = snapshot.render(commit, now());
+            for line in &lines {
+                println!("{line}");
+            }
+            printed = lines.len();
+        }
+        if finished {
+            return Ok(snapshot);
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Follow the checks of `sha` until they finish, for a command that must
+/// not go ahead before then; the number that failed. `None` when there is
+/// nothing to wait for: no check suites on the commit and none required by
+/// `base`'s protection, as in a repository without CI.
+pub(crate) async fn wait_for_checks(
+    client: &Octocrab,
+    remote: &Remote,
+    sha: &str,
+    base: &str,
+    label: &str,
+    interval: Duration,
+) -> Result<Option<usize>> {
+    let repo = format!("/repos/{}/{}", remote.user, remote.repo);
+    let suites: CheckSuites = client
+        .get(format!("{repo}/commits/{sha}/check-suites"), None::<&()>)
+        .await?;
+    if suites.total_count == 0 {
+        let branch: Branch = client
+            .get(format!("{repo}/branches/{base}"), None::<&()>)
+            .await?;
+        let required = branch
+            .protection
+            .and_then(|protection| protection.required_status_checks)
+            .is_some_and(|checks| !checks.contexts.is_empty());
+        if !required {
+            return Ok(None);
+        }
+    }
+    let commit = Commit {
+        sha: sha.to_string(),
+        label: label.to_string(),
+    };
+    let snapshot = follow(client, remote, &commit, true, interval).await?;
+    Ok(Some(snapshot.failures()))
+}
+
+/// `git ci`: print the table; with `watch`, redraw it until everything has
+/// finished. Fails when a check failed, so `git ci --watch && …` waits for
+/// green.
+pub fn status(opts: Options) -> Result<()> {
+    let (repo, remote) = open_repo(&opts.remote)?;
+    let interval = Duration::from_secs(opts.interval.max(1));
+    let (snapshot, commit) = github::run(|client| async move {
+        let commit = resolve(&client, &repo, &remote, opts.target.as_deref()).await?;
+        let snapshot = follow(&client, &remote, &commit, opts.watch, interval).await?;
+        Ok::<_, Error>((snapshot, commit))
+    })?;
+
+    if snapshot.is_empty() {
+        println!(
+            "{}",
+            style::dim(&format!(
+                "GitHub has no checks for {}; has it been pushed?",
+                commit.sha
+            ))
+        );
+    }
+    match snapshot.failures() {
+        0 => Ok(()),
+        n => Err(Error::ChecksFailed(n)),
+    }
+}
+
+/// `git ci logs`: the end of the log of each failed job.
+pub fn logs(remote: &str, target: Option<String>, tail: usize) -> Result<()> {
+    let (repo, remote) = open_repo(remote)?;
+    github::run(|client| async move {
+        let commit = resolve(&client, &repo, &remote, target.as_deref()).await?;
+        let snapshot = Snapshot::fetch(&client, &remote, &commit.sha).await?;
+        let failed = snapshot.failed();
+        if failed.is_empty() {
+            println!("No failed jobs for {}.", commit.label);
+        }
+        for check in failed {
+            println!();
+            println!(
+                "{}",
+                style::bold(&format!("{} ({})", check.name, describe(check, now())))
+            );
+            if !check.is_job() {
+                let url = check.html_url.as_deref().unwrap_or_default();
+                println!(
+                    "{}",
+                    style::dim(&format!("not a GitHub Actions job; see {url}"))
+                );
+                continue;
+            }
+            let route = format!(
+                "/repos/{}/{}/actions/jobs/{}/logs",
+                remote.user, remote.repo, check.id
+            );
+            let response = octocrab::map_github_error(client._get(route).await?).await?;
+            let text = client.body_to_string(response).await?;
+            let lines: Vec<&str> = text.lines().collect();
+            let skip = if tail == 0 {
+                0
+            } else {
+                lines.len().saturating_sub(tail)
+            };
+            if skip > 0 {
+                println!("{}", style::dim(&format!("… {skip} earlier lines")));
+            }
+            for line in &lines[skip..] {
+                if let Some(line) = log_line(line) {
+                    println!("{} {line}", style::gutter());
+                }
+            }
+        }
+        Ok::<_, Error>(())
+    })
+}
+
+/// A job log line without its timestamp, with the runner's `##[error]`-style
+/// markers turned into color; `None` for lines that only end a group.
+fn log_line(line: &str) -> Option<String> {
+    let line = match line.split_once(' ') {
+        Some((stamp, rest)) if epoch(stamp).is_some() => rest,
+        _ => line,
+    };
+    let line = line.trim_start_matches('\u{feff}');
+    if let Some(rest) = line.strip_prefix("##[error]") {
+        return Some(style::red(rest));
+    }
+    if let Some(rest) = line.strip_prefix("##[warning]") {
+        return Some(style::yellow(rest));
+    }
+    if let Some(rest) = line.strip_prefix("##[group]") {
+        return Some(style::bold(rest));
+    }
+    if line.starts_with("##[endgroup]") {
+        return None;
+    }
+    Some(line.to_string())
+}
+
+/// `git ci rerun`: re-run the failed jobs of every failed workflow run.
+pub fn rerun(remote: &str, target: Option<String>) -> Result<()> {
+    let (repo, remote) = open_repo(remote)?;
+    if github::token(&load_github_config()?)?.is_none() {
+        return Err(Error::NoGitHubToken("re-running jobs"));
+    }
+    github::run(|client| async move {
+        github::writable_repo::<IgnoredAny>(&client, &remote, "re-run jobs").await?;
+        let commit = resolve(&client, &repo, &remote, target.as_deref()).await?;
+        let snapshot = Snapshot::fetch(&client, &remote, &commit.sha).await?;
+        let failed: Vec<&WorkflowRun> = snapshot
+            .workflows
+            .iter()
+            .filter(|run| run.state() == State::Failed)
+            .collect();
+        if failed.is_empty() {
+            println!("No failed workflow runs for {}.", commit.label);
+            return Ok(());
+        }
+        for run in failed {
+            let route = format!(
+                "/repos/{}/{}/actions/runs/{}/rerun-failed-jobs",
+                remote.user, remote.repo, run.id
+            );
+            octocrab::map_github_error(client._post(route, None::<&()>).await?).await?;
+            style::success(&format!("Re-running the failed jobs of {}", run.title()));
+        }
+        println!("{}", style::dim("Follow them with `git ci --watch`."));
+        Ok::<_, Error>(())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check(name: &str, status: &str, conclusion: Option<&str>, suite: u64) -> CheckRun {
+        CheckRun {
+            id: 1,
+            name: name.to_string(),
+            status: status.to_string(),
+            conclusion: conclusion.map(str::to_string),
+            started_at: Some("2024-05-01T12:00:00Z".to_string()),
+            completed_at: conclusion.map(|_| "2024-05-01T12:02:13Z".to_string()),
+            html_url: None,
+            check_suite: Some(Suite { id: suite }),
+            app: None,
+        }
+    }
+
+    #[test]
+    fn parses_github_timestamps() {
+        assert_eq!(epoch("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(epoch("2024-05-01T12:00:00Z"), Some(1_714_564_800));
+        assert_eq!(epoch("2024-02-29T23:59:59.123Z"), Some(1_709_251_199));
+        assert_eq!(epoch("yesterday"), None);
+    }
+
+    #[test]
+    fn classifies_and_summarises_checks() {
+        let checks = [
+            check("build", "completed", Some("success"), 1),
+            check("test", "completed", Some("timed_out"), 1),
+            check("lint", "in_progress", None, 1),
+            check("docs", "completed", Some("skipped"), 2),
+        ];
+        let states: Vec<State> = checks.iter().map(CheckRun::state).collect();
+        assert_eq!(
+            states,
+            [State::Passed, State::Failed, State::Running, State::Skipped]
+        );
+        assert_eq!(overall(states.iter().copied()), State::Failed);
+        assert_eq!(
+            overall([State::Passed, State::Running].into_iter()),
+            State::Running
+        );
+        assert_eq!(
+            summary(states.into_iter()),
+            "4 checks: 1 passed, 1 failed, 1 running, 1 skipped"
+        );
+        assert_eq!(describe(&checks[0], 0), "success 2m13s");
+    }
+
+    #[test]
+    fn groups_jobs_under_their_workflow_run() {
+        let snapshot = Snapshot {
+            workflows: vec![WorkflowRun {
+                id: 7,
+                name: Some("CI".to_string()),
+                status: Some("completed".to_string()),
+                conclusion: Some("failure".to_string()),
+                check_suite_id: Some(1),
+                run_number: 42,
+                event: "push".to_string(),
+            }],
+            checks: vec![
+                check("test", "completed", Some("failure"), 1),
+                check("build", "completed", Some("success"), 1),
+                check("coverage", "completed", Some("success"), 9),
+            ],
+        };
+        let groups = snapshot.groups();
+        let names: Vec<(&str, Vec<&str>)> = groups
+            .iter()
+            .map(|(title, _, checks)| {
+                (
+                    title.as_str(),
+                    checks.iter().map(|c| c.name.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("CI · push · run #42", vec!["build", "test"]),
+                ("checks", vec!["coverage"])
+            ]
+        );
+        assert!(snapshot.done());
+        assert_eq!(snapshot.failed().len(), 1);
+        assert_eq!(snapshot.failures(), 1);
+    }
+
+    #[test]
+    fn counts_workflow_runs_that_failed_without_jobs() {
+        let run = |id: u64, conclusion: &str, suite: u64| WorkflowRun {
+            id,
+            name: Some("CI".to_string()),
+            status: Some("completed".to_string()),
+            conclusion: Some(conclusion.to_string()),
+            check_suite_id: Some(suite),
+            run_number: id,
+            event: "push".to_string(),
+        };
+        let snapshot = Snapshot {
+            workflows: vec![run(1, "startup_failure", 1), run(2, "success", 2)],
+            checks: vec![check("build", "completed", Some("success"), 2)],
+        };
+        assert!(snapshot.failed().is_empty());
+        assert_eq!(snapshot.failures(), 1);
+    }
+
+    #[test]
+    fn strips_log_timestamps_and_markers() {
+        let line = "2024-05-01T12:00:01.1234567Z ##[error]Process completed with exit code 1.";
+        assert_eq!(
+            log_line(line).as_deref(),
+            Some("Process completed with exit code 1.")
+        );
+        assert_eq!(log_line("2024-05-01T12:00:01Z ##[endgroup]"), None);
+        assert_eq!(log_line("plain").as_deref(), Some("plain"));
+    }
+}
@@ -1,22 +1,247 @@
 use crate::error::{Error, Result};
-use config::{Config, File};
+use crate::profile::{self, Profile, Reason};
+use crate::repo::Repo;
+use config::builder::DefaultState;
+use config::{Config, ConfigBuilder, ConfigError, Environment, File, FileFormat, Map, Value};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
+    #[serde(default)]
     pub deepseek: DeepSeekConfig,
+    #[serde(default)]
+    pub commit: CommitConfig,
+    #[serde(default)]
+    pub tickets: TicketConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    /// Language of generated messages, e.g. `zh-CN` or `Japanese`; English
+    /// when unset. Overridden by `--lang`.
+    pub language: Option<String>,
+    #[serde(default)]
+    pub http: HttpConfig,
+    #[serde(default)]
+    pub github: GitHubConfig,
+    /// Prices per model, for usage cost estimates.
+    #[serde(default = "default_pricing")]
+    pub pricing: HashMap<String, Price>,
+    /// Co-author roster for `git ac --co-author`: alias → `Name <email>`.
+    #[serde(default)]
+    pub team: HashMap<String, String>,
+    /// Named sets of settings applied over the rest (see [`crate::profile`]).
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+    /// The profile applied to every command.
+    pub profile: Option<String>,
+    /// The profile for each command (`ac`, `pr`, `issues`, `ci`, `release`, `open`).
+    #[serde(default)]
+    pub command_profiles: HashMap<String, String>,
+    /// The profiles in effect and why, lowest precedence first.
+    #[serde(skip)]
+    pub active_profiles: Vec<(String, Reason)>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct DeepSeekConfig {
+    /// Better kept out of config files; see [`crate::auth`].
+    #[serde(default)]
     pub api_key: String,
+    /// A command printing the API key, e.g. `pass show deepseek`.
+    pub api_key_command: Option<String>,
     pub model: Option<String>,
     pub temperature: Option<f32>,
     pub prompt: Option<String>,
+    /// API root, for a gateway or an internal mirror of the DeepSeek API.
+    /// Defaults to [`DEFAULT_DEEPSEEK_URL`].
+    pub base_url: Option<String>,
+}
+
+pub const DEFAULT_DEEPSEEK_URL: &str = "https://api.deepseek.com";
+
+/// GitHub API settings.
+#[derive(Debug, Default, Deserialize)]
+pub struct GitHubConfig {
+    /// API root, for GitHub Enterprise Server or a mirror; defaults to
+    /// `https://api.github.com`.
+    pub api_url: Option<String>,
+    /// The account to use when the `gh` CLI or git's credential helper hold
+    /// tokens for several.
+    pub user: Option<String>,
+    /// A command printing the token, e.g. `pass show github/work`.
+    pub token_command: Option<String>,
+    /// The client ID of a GitHub OAuth app with device flow enabled, for
+    /// `git-github auth login --github`.
+    pub oauth_client_id: Option<String>,
+}
+
+/// Settings for `git ac` that are independent of the model provider.
+#[derive(Debug, Default, Deserialize)]
+pub struct CommitConfig {
+    /// How many recent commits to sample when learning the repository's
+    /// message style; `0` disables it. Defaults to [`DEFAULT_HISTORY_SAMPLES`].
+    pub history_samples: Option<usize>,
+    /// Extra author names/emails (substring match) to skip when sampling, on
+    /// top of the built-in `[bot]` filter.
+    #[serde(default)]
+    pub ignore_authors: Vec<String>,
+    /// Always add a `Signed-off-by` trailer, as if `--signoff` were passed.
+    #[serde(default)]
+    pub signoff: bool,
+    /// Trailer lines added to every message, e.g. `"Reviewed-by: Team"`.
+    #[serde(default)]
+    pub trailers: Vec<String>,
+    /// Seconds the `prepare-commit-msg` hook waits for the model before
+    /// leaving the message empty. Defaults to [`DEFAULT_HOOK_TIMEOUT`].
+    pub hook_timeout: Option<u64>,
+}
+
+/// Commits sampled for style learning when `history_samples` is unset.
+pub const DEFAULT_HISTORY_SAMPLES: usize = 30;
+
+/// Seconds the commit hook waits for the model when `hook_timeout` is unset.
+pub const DEFAULT_HOOK_TIMEOUT: u64 = 15;
+
+/// How ticket keys / issue numbers are picked out of branch names.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct TicketConfig {
+    /// Set to `false` to never add ticket references.
+    pub enabled: bool,
+    /// Regexes tried in order against the branch name; the first capture group
+    /// is the reference. An all-digit capture is a GitHub issue number,
+    /// anything else a tracker key such as `PROJ-1234`.
+    pub patterns: Vec<String>,
+    /// Branch prefixes (the part before the first `/`) whose issue is closed
+    /// by the work, earning a `Fixes #N` instead of `Refs #N`.
+    pub close_prefixes: Vec<String>,
+}
+
+impl Default for TicketConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            patterns: vec![
+                r"\b([A-Z][A-Z0-9]+-\d+)\b".to_string(),
+                r"(?:^|/)(\d+)(?:[-_]|$)".to_string(),
+            ],
+            close_prefixes: vec![
+                "fix".to_string(),
+                "bugfix".to_string(),
+                "hotfix".to_string(),
+            ],
+        }
+    }
+}
+
+/// The completion cache (see [`crate::cache`]).
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Set to `false` to always ask the model, as if `--no-cache` were passed.
+    pub enabled: bool,
+    /// How long a cached completion is served.
+    pub ttl_hours: u64,
+    /// Total size the cache is trimmed to, oldest entries first.
+    pub max_size_kb: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_hours: 24,
+            max_size_kb: 1024,
+        }
+    }
+}
+
+/// Terminal presentation.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct UiConfig {
+    pub display: Display,
+}
+
+/// How model output appears while it is generated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Display {
+    /// A spinner while the model works, then the message typed out at once.
+    #[default]
+    Reveal,
+    /// Text printed as it arrives.
+    Stream,
+    /// Nothing at all; used for `--quiet` / `--json`.
+    Quiet,
+}
+
+/// Network settings shared by every API client (see [`crate::http`]).
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Proxy for all requests, e.g. `http://proxy.corp:3128`. Unset, the
+    /// usual `HTTPS_PROXY`/`HTTP_PROXY` environment variables apply.
+    pub proxy: Option<String>,
+    /// Comma-separated hosts, domains and CIDRs that bypass `proxy`; falls
+    /// back to `NO_PROXY`.
+    pub no_proxy: Option<String>,
+    /// PEM bundle of extra root certificates to trust, e.g. a corporate CA.
+    pub ca_bundle: Option<PathBuf>,
+    /// PEM client certificate (chain) for mutual TLS; needs `client_key`.
+    pub client_cert: Option<PathBuf>,
+    /// PKCS#8 PEM private key for `client_cert`.
+    pub client_key: Option<PathBuf>,
+    /// Seconds allowed for establishing a connection.
+    pub connect_timeout: u64,
+    /// Seconds a connection may go without receiving data.
+    pub read_timeout: u64,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            no_proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            connect_timeout: 10,
+            read_timeout: 60,
+        }
+    }
+}
+
+/// What a model costs, in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Price {
+    pub input: f64,
+    /// Prompt tokens served from the provider's context cache; defaults to
+    /// `input`.
+    pub cached_input: Option<f64>,
+    pub output: f64,
+}
+
+/// DeepSeek's published list prices at the time of writing. Configuring a
+/// `[pricing]` table replaces these entirely.
+fn default_pricing() -> HashMap<String, Price> {
+    let price = Price {
+        input: 0.28,
+        cached_input: Some(0.028),
+        output: 0.42,
+    };
+    HashMap::from([
+        ("deepseek-chat".to_string(), price),
+        ("deepseek-reasoner".to_string(), price),
+    ])
 }
 
 fn ensure_config_exists(path: &Path) -> std::io::Result<()> {
@@ -28,7 +253,9 @@ fn ensure_config_exists(path: &Path) -> std::io::Result<()> {
         writeln!(
             file,
             r#"[deepseek]
-api_key = ""
+# Keep the API key out of this file: `git-github auth login` stores it in a
+# keyring or git credential helper, or read it from a password manager with
+# api_key_command = "pass show deepseek"
 model = "deepseek-chat"
 temperature = 0.7
 prompt = ""
@@ -45,32 +272,504 @@ fn home_dir() -> Option<PathBuf> {
         .map(PathBuf::from)
 }
 
+/// `$XDG_CONFIG_HOME/git-github`, falling back to `~/.config/git-github`:
+/// home of the user config file and prompt templates.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| Some(home_dir()?.join(".config")))?;
+    Some(base.join("git-github"))
+}
+
+/// The per-project file's name, at the repository root or in its git dir.
+/// Named specifically to avoid clashing with an unrelated `config.toml`.
+pub const PROJECT_FILE: &str = "git-github.toml";
+
+/// Prefix of the `git config` keys read as settings: `github-ai.<key>`.
+pub const GIT_CONFIG_SECTION: &str = "github-ai";
+
+/// Prefix of the environment variables read as settings, with `__`
+/// separating key parts: `GIT_GITHUB_DEEPSEEK__MODEL` is `deepseek.model`.
+pub const ENV_PREFIX: &str = "GIT_GITHUB";
+
+/// Where a setting comes from, lowest precedence first. Command-line flags
+/// override them all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Scope {
+    /// `/etc/git-github/config.toml`.
+    System,
+    /// The user config file (see [`config_dir`]).
+    User,
+    /// `git-github.toml` at the repository root (the current directory
+    /// outside a repository), usually committed.
+    Repo,
+    /// `git-github.toml` in the repository's git dir, never committed.
+    Local,
+    /// `git config` keys under `github-ai.`.
+    Git,
+    /// `GIT_GITHUB_*` environment variables.
+    Env,
+}
+
+impl Scope {
+    pub fn name(self) -> &'static str {
+        match self {
+            Scope::System => "system",
+            Scope::User => "user",
+            Scope::Repo => "repo",
+            Scope::Local => "local",
+            Scope::Git => "git config",
+            Scope::Env => "env",
+        }
+    }
+}
+
+/// One configuration layer.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub scope: Scope,
+    /// The layer's file, for the file-backed scopes.
+    pub path: Option<PathBuf>,
+    /// The `git config` settings, rendered as TOML.
+    text: Option<String>,
+}
+
+impl Layer {
+    pub(crate) fn file(scope: Scope, path: PathBuf) -> Layer {
+        Layer {
+            scope,
+            path: Some(path),
+            text: None,
+        }
+    }
+
+    /// `builder` with this layer added on top.
+    fn add_to(&self, builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+        match (self.scope, &self.path, &self.text) {
+            (Scope::Env, ..) => builder.add_source(
+                Environment::with_prefix(ENV_PREFIX)
+                    .prefix_separator("_")
+                    .separator("__")
+                    .try_parsing(true),
+            ),
+            (_, Some(path), _) => builder.add_source(File::from(path.as_path()).required(false)),
+            (_, None, Some(text)) => builder.add_source(File::from_str(text, FileFormat::Toml)),
+            (_, None, None) => builder,
+        }
+    }
+
+    /// The layer's own settings as `(dotted.key, value)` pairs, sorted.
+    pub fn entries(&self) -> Result<Vec<(String, String)>> {
+        let map: Map<String, Value> = self.add_to(Config::builder()).build()?.try_deserialize()?;
+        let mut entries = Vec::new();
+        flatten("", map, &mut entries);
+        entries.sort();
+        Ok(entries)
+    }
+
+    /// Check the layer on its own against the settings this tool reads. A
+    /// value of the wrong type is an error naming the layer and key; keys
+    /// nothing reads (typos, usually) are returned.
+    pub fn validate(&self) -> Result<Vec<String>> {
+        let invalid = |e| invalid_config(self.origin(), e);
+        let config = self.add_to(Config::builder()).build().map_err(invalid)?;
+        let mut unknown = Vec::new();
+        let _: AppConfig =
+            serde_ignored::deserialize(config, |path| unknown.push(path.to_string()))
+                .map_err(invalid)?;
+        unknown.sort();
+        Ok(unknown)
+    }
+
+    /// How `git-github config --show-origin` names the layer.
+    pub fn origin(&self) -> String {
+        match &self.path {
+            Some(path) => format!("{}:{}", self.scope.name(), path.display()),
+            None => self.scope.name().to_string(),
+        }
+    }
+}
+
+/// A `config` crate error as one pointing at `origin` and, when known, the
+/// offending key.
+fn invalid_config(origin: String, e: ConfigError) -> Error {
+    fn describe(e: ConfigError) -> (Option<String>, String) {
+        match e {
+            ConfigError::Type {
+                unexpected,
+                expected,
+                key,
+                ..
+            } => (key, format!("expected {expected}, found {unexpected}")),
+            ConfigError::At { error, key, .. } => {
+                let (inner, reason) = describe(*error);
+                (key.or(inner), reason)
+            }
+            ConfigError::FileParse { cause, .. } => (None, cause.to_string()),
+            ConfigError::NotFound(key) => (Some(key), "this required setting is missing".into()),
+            // `enum Display does not have variant constructor fancy`
+            ConfigError::Message(message) => match message.split_once(" variant constructor ") {
+                Some((_, value)) => (None, format!("unknown value `{value}`")),
+                None => (None, message),
+            },
+            other => (None, other.to_string()),
+        }
+    }
+    let (key, reason) = describe(e);
+    Error::InvalidConfig {
+        origin,
+        key: key.filter(|key| !key.is_empty()),
+        reason: reason.trim().to_string(),
+    }
+}
+
+fn flatten(prefix: &str, map: Map<String, Value>, out: &mut Vec<(String, String)>) {
+    let mut leaves = Vec::new();
+    leaf_values(prefix, map, &mut leaves);
+    out.extend(
+        leaves
+            .into_iter()
+            .map(|(key, value)| (key, value.to_string())),
+    );
+}
+
+/// The non-table values in `map`, under dotted keys.
+fn leaf_values(prefix: &str, map: Map<String, Value>, out: &mut Vec<(String, Value)>) {
+    for (key, value) in map {
+        let key = if prefix.is_empty() {
+            key
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value.clone().into_table() {
+            Ok(table) => leaf_values(&key, table, out),
+            Err(_) => out.push((key, value)),
+        }
+    }
+}
+
+/// Every configuration layer for the current directory, lowest precedence
+/// first.
+pub fn layers() -> Result<Vec<Layer>> {
+    let mut layers = Vec::new();
+    if cfg!(unix) {
+        layers.push(Layer::file(
+            Scope::System,
+            PathBuf::from("/etc/git-github/config.toml"),
+        ));
+    }
+    let user = config_dir().ok_or(Error::NoHomeDir)?.join("config.toml");
+    layers.push(Layer::file(Scope::User, user));
+
+    let cwd = env::current_dir().map_err(|_| Error::NoCurrentDir)?;
+    let repo = Repo::new(&cwd).ok();
+    let root = repo
+        .as_ref()
+        .and_then(|repo| repo.workdir())
+        .unwrap_or(&cwd);
+    layers.push(Layer::file(Scope::Repo, root.join(PROJECT_FILE)));
+    if let Some(repo) = &repo {
+        layers.push(Layer::file(
+            Scope::Local,
+            repo.common_dir().join(PROJECT_FILE),
+        ));
+    }
+
+    layers.push(Layer {
+        scope: Scope::Git,
+        path: None,
+        text: Some(git_config_toml(&git_config_entries())),
+    });
+    layers.push(Layer {
+        scope: Scope::Env,
+        path: None,
+        text: None,
+    });
+    Ok(layers)
+}
+
+/// `github-ai.*` from `git config` (system, global and the repository's),
+/// as `(key, value)` with the prefix removed.
+fn git_config_entries() -> Vec<(String, String)> {
+    let pattern = format!(r"^{GIT_CONFIG_SECTION}\.");
+    let Ok(output) = Command::new("git")
+        .args(["config", "--null", "--get-regexp", &pattern])
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter_map(|record| {
+            let (key, value) = record.split_once('\n')?;
+            let key = key.strip_prefix(GIT_CONFIG_SECTION)?.strip_prefix('.')?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// `git config` entries as TOML with dotted keys. Git doesn't allow `_` in
+/// variable names, so the last part's dashes become underscores
+/// (`github-ai.deepseek.api-key` is `deepseek.api_key`); subsection names
+/// such as a model in `github-ai.pricing.deepseek-chat.input` stay as
+/// written. Values are strings, converted like any config value.
+fn git_config_toml(entries: &[(String, String)]) -> String {
+    let mut text = String::new();
+    for (key, value) in entries {
+        let mut parts: Vec<String> = key.split('.').map(str::to_string).collect();
+        if let Some(last) = parts.last_mut() {
+            *last = last.replace('-', "_");
+        }
+        let key: Vec<String> = parts
+            .iter()
+            .map(|part| toml::Value::String(part.clone()).to_string())
+            .collect();
+        text.push_str(&format!(
+            "{} = {}\n",
+            key.join("."),
+            toml::Value::String(value.clone())
+        ));
+    }
+    text
+}
+
+/// Check that the config files alone make a valid configuration, so a bad
+/// value in one isn't masked by a `git config` or environment override.
+pub fn check_files() -> Result<()> {
+    let mut builder = Config::builder();
+    for layer in layers()?.iter().filter(|layer| layer.path.is_some()) {
+        builder = layer.add_to(builder);
+    }
+    builder.build()?.try_deserialize::<AppConfig>()?;
+    Ok(())
+}
+
+/// Set once unknown keys have been warned about.
+static UNKNOWN_KEYS_REPORTED: AtomicBool = AtomicBool::new(false);
+
+/// Leave reporting unknown keys to the caller, e.g. `doctor`.
+pub fn unknown_keys_reported() {
+    UNKNOWN_KEYS_REPORTED.store(true, Ordering::Relaxed);
+}
+
+/// The layers again with the `profiles` (taken from `merged`, lowest
+/// precedence first) between the files and the `git config`/environment
+/// layers: a profile overrides what the files say, but an explicit
+/// `github-ai.*` or `GIT_GITHUB_*` setting still overrides the profile.
+fn with_profiles(layers: &[Layer], merged: &Config, profiles: &[&str]) -> Result<Config> {
+    let (files, overrides): (Vec<&Layer>, Vec<&Layer>) = layers
+        .iter()
+        .partition(|layer| !matches!(layer.scope, Scope::Git | Scope::Env));
+    let mut builder = Config::builder();
+    for layer in files {
+        builder = layer.add_to(builder);
+    }
+    for name in profiles {
+        let mut table = merged.get_table(&format!("profiles.{name}"))?;
+        table.remove("match");
+        let mut leaves = Vec::new();
+        leaf_values("", table, &mut leaves);
+        let mut profile = Config::builder();
+        for (key, value) in leaves {
+            profile = profile.set_override(key, value)?;
+        }
+        builder = builder.add_source(profile.build()?);
+    }
+    for layer in overrides {
+        builder = layer.add_to(builder);
+    }
+    Ok(builder.build()?)
+}
+
+/// What [`load_github_config`] reads.
+#[derive(Deserialize)]
+struct GitHubSettings {
+    #[serde(default)]
+    http: HttpConfig,
+    #[serde(default)]
+    github: GitHubConfig,
+    #[serde(default)]
+    profiles: HashMap<String, Profile>,
+    profile: Option<String>,
+    #[serde(default)]
+    command_profiles: HashMap<String, String>,
+}
+
+/// Only the settings talking to GitHub needs — `[http]`, `[github]` and the
+/// profiles that may set them — with everything else at its default. Unlike
+/// [`load_config`] it never creates the user config, doesn't warn about
+/// unknown keys, and isn't held up by a bad value in an unrelated section.
+pub fn load_github_config() -> Result<AppConfig> {
+    let layers = layers()?;
+    let mut builder = Config::builder();
+    for layer in &layers {
+        builder = layer.add_to(builder);
+    }
+    let merged = builder.build()?;
+    let mut settings: GitHubSettings = merged.clone().try_deserialize()?;
+    let active = profile::applicable(
+        &settings.profiles,
+        settings.profile.as_deref(),
+        &settings.command_profiles,
+    )?;
+    if !active.is_empty() {
+        let names: Vec<&str> = active.iter().map(|(name, _)| name.as_str()).collect();
+        settings = with_profiles(&layers, &merged, &names)?.try_deserialize()?;
+    }
+
+    let mut app: AppConfig = Config::builder().build()?.try_deserialize()?;
+    app.http = settings.http;
+    app.github = settings.github;
+    app.active_profiles = active;
+    Ok(app)
+}
+
 pub fn load_config() -> Result<AppConfig> {
-    // Project-local override; named specifically to avoid clashing with an
-    // unrelated `config.toml` in the working directory.
-    let local_config = Path::new("git-github.toml").to_path_buf();
-    let home = home_dir().ok_or(Error::NoHomeDir)?;
-    let home_config = home
-        .join(".config")
-        .join("git-github")
-        .join("config.toml");
+    let layers = layers()?;
 
-    if !local_config.exists() && !home_config.exists() {
-        ensure_config_exists(&home_config)?;
+    // First run: start a user config to fill in, unless some file exists.
+    let files: Vec<&PathBuf> = layers.iter().filter_map(|l| l.path.as_ref()).collect();
+    if !files.iter().any(|path| path.exists()) {
+        if let Some(user) = layers.iter().find(|l| l.scope == Scope::User) {
+            ensure_config_exists(user.path.as_ref().expect("the user layer is a file"))?;
+        }
     }
 
-    let cfg = Config::builder()
-        .add_source(File::from(local_config).required(false))
-        .add_source(File::from(home_config).required(false))
-        .build()?;
+    // Each layer is checked by itself, so errors name the file at fault.
+    // Unknown keys are only warned about, once per run.
+    let warn = !UNKNOWN_KEYS_REPORTED.swap(true, Ordering::Relaxed);
+    let mut builder = Config::builder();
+    for layer in &layers {
+        for key in layer.validate()? {
+            if warn {
+                eprintln!("warning: unknown setting `{key}` in {}", layer.origin());
+            }
+        }
+        builder = layer.add_to(builder);
+    }
+    let merged = builder.build()?;
+    let mut app: AppConfig = merged.clone().try_deserialize()?;
 
-    let mut app: AppConfig = cfg.try_deserialize()?;
+    let active = profile::applicable(&app.profiles, app.profile.as_deref(), &app.command_profiles)?;
+    if !active.is_empty() {
+        let names: Vec<&str> = active.iter().map(|(name, _)| name.as_str()).collect();
+        app = with_profiles(&layers, &merged, &names)?.try_deserialize()?;
+        app.active_profiles = active;
+    }
 
     // An explicit env var wins over the config file, so a key never has to be
-    // written to disk (handy for CI).
+    // written to disk (handy for CI). Other secret stores are only consulted
+    // when a key is needed; see `auth::require_api_key`.
     if let Some(key) = env::var("DEEPSEEK_API_KEY").ok().filter(|k| !k.is_empty()) {
         app.deepseek.api_key = key;
     }
 
     Ok(app)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn maps_git_config_keys_to_settings() {
+        let entries = vec![
+            ("deepseek.api-key".to_string(), "sk-1".to_string()),
+            ("pricing.deepseek-chat.input".to_string(), "0.5".to_string()),
+            ("language".to_string(), "zh \"CN\"".to_string()),
+        ];
+        let text = git_config_toml(&entries);
+        let cfg = Config::builder()
+            .add_source(File::from_str(&text, FileFormat::Toml))
+            .build()
+            .unwrap();
+        assert_eq!(cfg.get_string("deepseek.api_key").unwrap(), "sk-1");
+        assert_eq!(cfg.get_float("pricing.deepseek-chat.input").unwrap(), 0.5);
+        assert_eq!(cfg.get_string("language").unwrap(), "zh \"CN\"");
+    }
+
+    #[test]
+    fn later_layers_win_and_flatten_to_dotted_keys() {
+        let base = "[deepseek]\napi_key = \"a\"\nmodel = \"m\"\n[http]\nread_timeout = 5\n";
+        let cfg = Config::builder()
+            .add_source(File::from_str(base, FileFormat::Toml))
+            .add_source(File::from_str("deepseek.model = \"n\"", FileFormat::Toml))
+            .build()
+            .unwrap();
+        let mut entries = Vec::new();
+        flatten("", cfg.try_deserialize().unwrap(), &mut entries);
+        entries.sort();
+        assert_eq!(
+            entries,
+            [
+                ("deepseek.api_key".to_string(), "a".to_string()),
+                ("deepseek.model".to_string(), "n".to_string()),
+                ("http.read_timeout".to_string(), "5".to_string()),
+            ]
+        );
+    }
+
+    fn git_layer(text: &str) -> Layer {
+        Layer {
+            scope: Scope::Git,
+            path: None,
+            text: Some(text.to_string()),
+        }
+    }
+
+    #[test]
+    fn profiles_sit_between_files_and_git_config() {
+        let file = Layer {
+            scope: Scope::User,
+            path: None,
+            text: Some(
+                "language = \"en\"\n[deepseek]\nmodel = \"file\"\n\
+                 [profiles.cheap]\ndeepseek.model = \"cheap\"\nlanguage = \"fr\"\n\
+                 [profiles.strong]\ndeepseek.model = \"strong\"\n"
+                    .to_string(),
+            ),
+        };
+        let layers = [file, git_layer("deepseek.model = \"git\"")];
+        let mut builder = Config::builder();
+        for layer in &layers {
+            builder = layer.add_to(builder);
+        }
+        let merged = builder.build().unwrap();
+
+        let cfg = with_profiles(&layers[..1], &merged, &["cheap", "strong"]).unwrap();
+        assert_eq!(cfg.get_string("deepseek.model").unwrap(), "strong");
+        assert_eq!(cfg.get_string("language").unwrap(), "fr");
+        let cfg = with_profiles(&layers, &merged, &["cheap", "strong"]).unwrap();
+        assert_eq!(cfg.get_string("deepseek.model").unwrap(), "git");
+        assert_eq!(cfg.get_string("language").unwrap(), "fr");
+    }
+
+    #[test]
+    fn validation_reports_unknown_keys_and_names_bad_ones() {
+        let layer = git_layer("language = \"ja\"\n[deepseek]\nmodle = \"x\"\n[cache]\nttl = 1\n");
+        assert_eq!(layer.validate().unwrap(), ["cache.ttl", "deepseek.modle"]);
+        // Free-form tables have no unknown keys.
+        assert!(git_layer("[team]\nbob = \"B <b@x>\"")
+            .validate()
+            .unwrap()
+            .is_empty());
+
+        let err = git_layer("[http]\nread_timeout = \"soon\"")
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid setting `http.read_timeout` in git config: \
+             expected an integer, found string \"soon\""
+        );
+        let err = git_layer("[ui]\ndisplay = \"fancy\"")
+            .validate()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .ends_with("`ui.display` in git config: unknown value `fancy`"));
+        let err = git_layer("[pricing.m]\ninput = 1").validate().unwrap_err();
+        assert!(err.to_string().contains("`pricing.m.output`"), "{err}");
+    }
+}
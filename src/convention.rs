@@ -0,0 +1,359 @@
+//! Learn a repository's commit message conventions from its own history, so
+//! generated messages match what is already there (prefixes, mood, ticket
+//! references, body usage) without a hand-written `prompt`.
+
+/// Share of samples a trait must reach before we call it a convention.
+const DOMINANT: f32 = 0.6;
+/// How many sampled messages are quoted verbatim as few-shot examples.
+const MAX_EXAMPLES: usize = 5;
+/// Longer sampled messages are cut to this many bytes when quoted.
+const MAX_EXAMPLE_BYTES: usize = 600;
+
+/// How a subject line starts, before the summary proper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prefix {
+    /// `feat: …`, `fix(parser): …`, `refactor!: …`
+    Conventional,
+    /// `[core] …`
+    Bracket,
+    /// `PROJ-123: …` / `PROJ-123 …`
+    Ticket,
+    None,
+}
+
+/// Grammatical form of the subject's first word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tense {
+    /// "Add", "Fix"
+    Imperative,
+    /// "Added", "Fixed"
+    Past,
+    /// "Adds", "Fixes"
+    ThirdPerson,
+}
+
+/// Conventions observed in a sample of commit messages, ready to be turned
+/// into prompt guidance with [`CommitStyle::prompt`].
+#[derive(Debug, Default)]
+pub struct CommitStyle {
+    samples: Vec<String>,
+    prefix: Option<Prefix>,
+    tense: Option<Tense>,
+    lowercase: Option<bool>,
+    /// An example ticket reference, when most messages carry one.
+    ticket: Option<String>,
+    /// Share of messages with a body beyond the subject line.
+    body_ratio: f32,
+    /// Whether bodies are mostly bullet lists.
+    bullet_bodies: bool,
+}
+
+/// Derive the style features of `samples` (full messages, newest first).
+pub fn analyze(samples: &[String]) -> CommitStyle {
+    if samples.is_empty() {
+        return CommitStyle::default();
+    }
+
+    let subjects: Vec<&str> = samples
+        .iter()
+        .map(|m| m.lines().next().unwrap_or_default().trim())
+        .collect();
+    let bodies: Vec<&str> = samples.iter().map(|m| body_of(m)).collect();
+    let with_body: Vec<&str> = bodies.iter().copied().filter(|b| !b.is_empty()).collect();
+
+    let prefixes: Vec<Prefix> = subjects.iter().map(|s| subject_prefix(s)).collect();
+    let summaries: Vec<&str> = subjects.iter().map(|s| strip_prefix(s)).collect();
+    let tenses: Vec<Tense> = summaries
+        .iter()
+        .filter_map(|s| s.split_whitespace().next())
+        .map(tense)
+        .collect();
+    let cases: Vec<bool> = summaries
+        .iter()
+        .filter_map(|s| s.chars().next())
+        .filter(|c| c.is_alphabetic())
+        .map(|c| c.is_lowercase())
+        .collect();
+    let tickets: Vec<String> = samples.iter().filter_map(|m| ticket_reference(m)).collect();
+
+    CommitStyle {
+        samples: samples.to_vec(),
+        prefix: dominant(&prefixes),
+        tense: dominant(&tenses),
+        lowercase: dominant(&cases),
+        ticket: (ratio(tickets.len(), samples.len()) >= DOMINANT)
+            .then(|| tickets.into_iter().next())
+            .flatten(),
+        body_ratio: ratio(with_body.len(), samples.len()),
+        bullet_bodies: !with_body.is_empty()
+            && ratio(
+                with_body.iter().filter(|b| is_bullet_list(b)).count(),
+                with_body.len(),
+            ) >= DOMINANT,
+    }
+}
+
+impl CommitStyle {
+    /// Guidance plus few-shot examples for the system prompt, or `None` when
+    /// there was no history to learn from.
+    pub fn prompt(&self) -> Option<String> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let mut rules = Vec::new();
+        match self.prefix {
+            Some(Prefix::Conventional) => rules.push(
+                "Start the subject with a Conventional Commit type, e.g. `feat:`, `fix(scope):`."
+                    .to_string(),
+            ),
+            Some(Prefix::Bracket) => rules
+                .push("Start the subject with a bracketed area tag, e.g. `[core]`.".to_string()),
+            Some(Prefix::Ticket) => {
+                rules.push("Start the subject with the ticket key, e.g. `PROJ-123:`.".to_string())
+            }
+            Some(Prefix::None) => {
+                rules.push("Do not prefix the subject with a type or tag.".to_string())
+            }
+            None => {}
+        }
+        match self.tense {
+            Some(Tense::Imperative) => rules.push(
+                "Write the subject in the imperative mood (\"Add\", not \"Added\").".to_string(),
+            ),
+            Some(Tense::Past) => rules
+                .push("Write the subject in the past tense (\"Added\", \"Fixed\").".to_string()),
+            Some(Tense::ThirdPerson) => rules
+                .push("Write the subject in the third person (\"Adds\", \"Fixes\").".to_string()),
+            None => {}
+        }
+        match self.lowercase {
+            Some(true) => rules.push("Begin the summary with a lowercase letter.".to_string()),
+            Some(false) => rules.push("Begin the summary with a capital letter.".to_string()),
+            None => {}
+        }
+        if let Some(ticket) = &self.ticket {
+            rules.push(format!(
+                "Reference the related ticket or issue like previous commits do (e.g. `{ticket}`)."
+            ));
+        }
+        if self.body_ratio < 0.25 {
+            rules.push(
+                "Most commits are a subject line only; add a body only when the change \
+                 needs explaining."
+                    .to_string(),
+            );
+        } else if self.body_ratio >= 0.75 {
+            rules.push(if self.bullet_bodies {
+                "Follow the subject with a blank line and a bullet-list body.".to_string()
+            } else {
+                "Follow the subject with a blank line and a prose body explaining the change."
+                    .to_string()
+            });
+        }
+
+        let mut prompt = String::from(
+            "Match the conventions of this repository's commit history. Where they \
+             conflict with the format above, follow the repository.\n",
+        );
+        for rule in rules {
+            prompt.push_str(&format!("- {rule}\n"));
+        }
+        prompt.push_str("\nRecent commit messages from this repository:\n");
+        for sample in self.samples.iter().take(MAX_EXAMPLES) {
+            prompt.push_str("\n---\n");
+            prompt.push_str(clip(sample, MAX_EXAMPLE_BYTES));
+            prompt.push('\n');
+        }
+        prompt.push_str("---\n");
+        Some(prompt)
+    }
+}
+
+/// Everything after the subject line, trimmed.
+fn body_of(message: &str) -> &str {
+    match message.trim().split_once('\n') {
+        Some((_, rest)) => rest.trim(),
+        None => "",
+    }
+}
+
+fn is_bullet_list(body: &str) -> bool {
+    body.lines()
+        .map(str::trim_start)
+        .filter(|l| !l.is_empty())
+        .all(|l| l.starts_with("- ") || l.starts_with("* "))
+}
+
+fn subject_prefix(subject: &str) -> Prefix {
+    if conventional_prefix_len(subject).is_some() {
+        Prefix::Conventional
+    } else if bracket_prefix_len(subject).is_some() {
+        Prefix::Bracket
+    } else if ticket_prefix_len(subject).is_some() {
+        Prefix::Ticket
+    } else {
+        Prefix::None
+    }
+}
+
+/// The subject with any recognised prefix removed.
+fn strip_prefix(subject: &str) -> &str {
+    let len = conventional_prefix_len(subject)
+        .or_else(|| bracket_prefix_len(subject))
+        .or_else(|| ticket_prefix_len(subject))
+        .unwrap_or(0);
+    subject[len..].trim_start()
+}
+
+/// Length of a `type(scope)!: ` prefix.
+fn conventional_prefix_len(subject: &str) -> Option<usize> {
+    let head = subject.split_once(':')?.0;
+    let kind = head
+        .split_once('(')
+        .map_or(head, |(k, _)| k)
+        .trim_end_matches('!');
+    let valid_kind = !kind.is_empty() && kind.chars().all(|c| c.is_ascii_lowercase());
+    let valid_scope = match head.split_once('(') {
+        Some((_, scope)) => scope.trim_end_matches('!').ends_with(')'),
+        None => true,
+    };
+    (valid_kind && valid_scope && !head.contains(' ')).then_some(head.len() + 1)
+}
+
+/// Length of a `[tag] ` prefix.
+fn bracket_prefix_len(subject: &str) -> Option<usize> {
+    let rest = subject.strip_prefix('[')?;
+    let close = rest.find(']')?;
+    (close > 0).then_some(close + 2)
+}
+
+/// Length of a `PROJ-123:` / `PROJ-123 ` prefix.
+fn ticket_prefix_len(subject: &str) -> Option<usize> {
+    let word = subject.split_whitespace().next()?;
+    let key = word.trim_end_matches(':');
+    is_ticket_key(key).then_some(word.len())
+}
+
+/// `PROJ-123`: an uppercase project key, a dash and a number.
+fn is_ticket_key(word: &str) -> bool {
+    let Some((project, number)) = word.split_once('-') else {
+        return false;
+    };
+    project
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_uppercase())
+        && project
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The first `#123` or `PROJ-123` reference in `message`.
+fn ticket_reference(message: &str) -> Option<String> {
+    message
+        .split(|c: char| c.is_whitespace() || "()[],;:.".contains(c))
+        .find(|word| {
+            let issue = word
+                .strip_prefix('#')
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+            issue || is_ticket_key(word)
+        })
+        .map(str::to_string)
+}
+
+fn tense(word: &str) -> Tense {
+    let word = word.to_lowercase();
+    if word.ends_with("ed") {
+        Tense::Past
+    } else if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") {
+        Tense::ThirdPerson
+    } else {
+        Tense::Imperative
+    }
+}
+
+/// The value shared by at least [`DOMINANT`] of `values`, if any.
+fn dominant<T: Copy + PartialEq>(values: &[T]) -> Option<T> {
+    values.iter().copied().find(|candidate| {
+        ratio(
+            values.iter().filter(|v| *v == candidate).count(),
+            values.len(),
+        ) >= DOMINANT
+    })
+}
+
+fn ratio(part: usize, whole: usize) -> f32 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f32 / whole as f32
+    }
+}
+
+/// `text` cut to at most `max` bytes on a char boundary.
+fn clip(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn samples(messages: &[&str]) -> Vec<String> {
+        messages.iter().map(|m| m.to_string()).collect()
+    }
+
+    #[test]
+    fn recognises_subject_prefixes() {
+        assert_eq!(subject_prefix("feat: add x"), Prefix::Conventional);
+        assert_eq!(
+            subject_prefix("fix(parser)!: handle y"),
+            Prefix::Conventional
+        );
+        assert_eq!(subject_prefix("[core] tidy up"), Prefix::Bracket);
+        assert_eq!(subject_prefix("PROJ-12: do z"), Prefix::Ticket);
+        assert_eq!(subject_prefix("Fix the thing: again"), Prefix::None);
+        assert_eq!(strip_prefix("fix(parser): handle y"), "handle y");
+        assert_eq!(strip_prefix("[core] tidy up"), "tidy up");
+    }
+
+    #[test]
+    fn finds_ticket_references() {
+        assert_eq!(ticket_reference("Fix crash (#42)"), Some("#42".into()));
+        assert_eq!(ticket_reference("Refs ABC-7 in body"), Some("ABC-7".into()));
+        assert_eq!(ticket_reference("Bump to v1-2"), None);
+    }
+
+    #[test]
+    fn derives_dominant_conventions() {
+        let style = analyze(&samples(&[
+            "feat: add login (#1)",
+            "fix(api): handle timeouts (#2)",
+            "docs: describe config (#3)",
+        ]));
+        assert_eq!(style.prefix, Some(Prefix::Conventional));
+        assert_eq!(style.tense, Some(Tense::Imperative));
+        assert_eq!(style.lowercase, Some(true));
+        assert_eq!(style.ticket.as_deref(), Some("#1"));
+        assert_eq!(style.body_ratio, 0.0);
+
+        let prompt = style.prompt().unwrap();
+        assert!(prompt.contains("Conventional Commit"));
+        assert!(prompt.contains("fix(api): handle timeouts (#2)"));
+    }
+
+    #[test]
+    fn no_history_means_no_guidance() {
+        assert!(analyze(&[]).prompt().is_none());
+    }
+}
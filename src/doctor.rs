@@ -0,0 +1,362 @@
+//! `git-github doctor`: check the setup up front — configuration layers,
+//! the DeepSeek key and connection, the GitHub token, the remotes and the
+//! commit hook — instead of finding a problem halfway through a command.
+
+use crate::auth;
+use crate::config::{self, load_config, AppConfig, Layer};
+use crate::error::{Error, Result};
+use crate::github;
+use crate::hook;
+use crate::llm::{self, Api};
+use crate::remote::Remote;
+use crate::repo::Repo;
+use crate::style;
+use std::env;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pass,
+    /// Works, but something is missing or likely unintended.
+    Warn,
+    Fail,
+    /// Neither good nor bad, e.g. an optional feature not in use.
+    Info,
+}
+
+/// The report, printed as it is made since some checks go over the network.
+#[derive(Default)]
+struct Report {
+    failed: usize,
+    /// What was reported, for the tests.
+    #[cfg(test)]
+    checks: Vec<(Status, String)>,
+}
+
+impl Report {
+    fn section(&self, title: &str) {
+        println!();
+        println!("{}", style::bold(title));
+    }
+
+    fn check(&mut self, status: Status, text: &str) {
+        let mark = match (status, style::enabled()) {
+            (Status::Pass, true) => style::green("✓"),
+            (Status::Warn, true) => style::yellow("!"),
+            (Status::Fail, true) => style::red("✗"),
+            (Status::Info, true) => style::dim("·"),
+            (Status::Pass, false) => "ok  ".to_string(),
+            (Status::Warn, false) => "warn".to_string(),
+            (Status::Fail, false) => "FAIL".to_string(),
+            (Status::Info, false) => "    ".to_string(),
+        };
+        if status == Status::Fail {
+            self.failed += 1;
+        }
+        #[cfg(test)]
+        self.checks.push((status, text.to_string()));
+        println!("  {mark} {text}");
+    }
+}
+
+/// Run every check and print the report. Fails when any check does;
+/// warnings alone don't.
+pub fn run() -> Result<()> {
+    let mut report = Report::default();
+    if let Some(mut config) = check_config(&mut report) {
+        check_deepseek(&mut report, &mut config);
+        check_github(&mut report, &config);
+    }
+    let repo = env::current_dir().ok().and_then(|dir| Repo::new(&dir).ok());
+    match &repo {
+        Some(repo) => {
+            check_remotes(&mut report, repo);
+            check_hook(&mut report);
+        }
+        None => {
+            report.section("Repository");
+            report.check(
+                Status::Info,
+                "not in a git repository; remote and hook checks skipped",
+            );
+        }
+    }
+
+    println!();
+    match report.failed {
+        0 => Ok(()),
+        n => Err(Error::ChecksFailed(n)),
+    }
+}
+
+/// Each layer on its own, then the merged result.
+fn check_config(report: &mut Report) -> Option<AppConfig> {
+    report.section("Configuration");
+    config::unknown_keys_reported();
+    let layers = match config::layers() {
+        Ok(layers) => layers,
+        Err(e) => {
+            report.check(Status::Fail, &e.to_string());
+            return None;
+        }
+    };
+    if !check_layers(report, &layers) {
+        return None;
+    }
+    match load_config() {
+        Ok(config) => {
+            for (name, reason) in &config.active_profiles {
+                report.check(
+                    Status::Info,
+                    &format!("profile {name}, from {}", reason.describe()),
+                );
+            }
+            Some(config)
+        }
+        Err(e) => {
+            report.check(Status::Fail, &e.to_string());
+            None
+        }
+    }
+}
+
+/// Check each layer by itself, so a problem names its file; whether all are
+/// valid. Missing files and empty `git config`/environment layers are left
+/// out.
+fn check_layers(report: &mut Report, layers: &[Layer]) -> bool {
+    let mut valid = true;
+    for layer in layers {
+        if layer.path.as_ref().is_some_and(|path| !path.exists()) {
+            continue;
+        }
+        let entries = layer
+            .entries()
+            .map(|entries| entries.len())
+            .unwrap_or_default();
+        if layer.path.is_none() && entries == 0 {
+            continue;
+        }
+        match layer.validate() {
+            Ok(unknown) => {
+                let settings = if entries == 1 { "setting" } else { "settings" };
+                report.check(
+                    Status::Pass,
+                    &format!("{} ({entries} {settings})", layer.origin()),
+                );
+                for key in unknown {
+                    report.check(Status::Warn, &format!("unknown setting `{key}`"));
+                }
+            }
+            Err(e) => {
+                report.check(Status::Fail, &e.to_string());
+                valid = false;
+            }
+        }
+    }
+    valid
+}
+
+/// The key, then a model listing to prove the key and the connection work.
+fn check_deepseek(report: &mut Report, config: &mut AppConfig) {
+    report.section("DeepSeek");
+    let secret = match auth::deepseek_key(config) {
+        Ok(Some(secret)) => secret,
+        Ok(None) => return report.check(Status::Fail, &Error::NoApiKey.to_string()),
+        Err(e) => return report.check(Status::Fail, &e.to_string()),
+    };
+    report.check(
+        Status::Pass,
+        &format!("API key from {}", secret.source.describe()),
+    );
+
+    config.deepseek.api_key = secret.value;
+    let api = Api::new(config);
+    match llm::list_models(&api) {
+        Ok(models) => {
+            report.check(Status::Pass, &format!("{} is reachable", api.base_url));
+            let model = config.deepseek.model.as_deref().unwrap_or("deepseek-chat");
+            if !models.is_empty() && !models.iter().any(|m| m == model) {
+                report.check(
+                    Status::Warn,
+                    &format!(
+                        "model `{model}` is not offered (available: {})",
+                        models.join(", ")
+                    ),
+                );
+            }
+        }
+        Err(e) => report.check(Status::Fail, &e.to_string()),
+    }
+}
+
+/// The token and what it may do. Without one, everything but `git pr`
+/// works on public repositories, so that is a warning.
+fn check_github(report: &mut Report, config: &AppConfig) {
+    report.section("GitHub");
+    let secret =
+        match github::find_token(config) {
+            Ok(Some(secret)) => secret,
+            Ok(None) => return report.check(
+                Status::Warn,
+                "no token: private repositories and `git pr` are unavailable, and requests are \
+                 limited to 60 an hour",
+            ),
+            Err(e) => return report.check(Status::Fail, &e.to_string()),
+        };
+    report.check(
+        Status::Pass,
+        &format!("token from {}", secret.source.describe()),
+    );
+
+    let info = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(Error::from)
+        .and_then(|rt| {
+            // The client is built inside the runtime, as octocrab requires.
+            rt.block_on(async { github::token_info(&github::client()?).await })
+        });
+    match info {
+        Ok(info) => match info.scopes {
+            Some(scopes) if github::allows_pull_requests(&scopes) => {
+                report.check(
+                    Status::Pass,
+                    &format!(
+                        "signed in as {} (scopes: {})",
+                        info.login,
+                        scopes.join(", ")
+                    ),
+                );
+                if !scopes.iter().any(|s| s == "repo") {
+                    report.check(
+                        Status::Warn,
+                        "the token only has `public_repo`; private repositories are unavailable",
+                    );
+                }
+            }
+            Some(scopes) => report.check(
+                Status::Warn,
+                &format!(
+                    "signed in as {}, but {}",
+                    info.login,
+                    Error::TokenScope {
+                        action: "open pull requests",
+                        scopes
+                    }
+                ),
+            ),
+            None => report.check(
+                Status::Pass,
+                &format!(
+                    "signed in as {} (fine-grained token; GitHub doesn't report its permissions)",
+                    info.login
+                ),
+            ),
+        },
+        // Installation tokens, Actions' `GITHUB_TOKEN` among them, are refused
+        // `/user` but work for repositories.
+        Err(Error::GitHubAuth(403 | 404)) => report.check(
+            Status::Pass,
+            "an app or Actions token; GitHub doesn't report its user or permissions",
+        ),
+        Err(e) => report.check(Status::Fail, &e.to_string()),
+    }
+}
+
+/// Every remote's URL must parse for `git open`/`git pr`/`git issues`.
+fn check_remotes(report: &mut Report, repo: &Repo) {
+    report.section("Remotes");
+    let remotes = match repo.remote_urls() {
+        Ok(remotes) => remotes,
+        Err(e) => return report.check(Status::Fail, &e.to_string()),
+    };
+    if remotes.is_empty() {
+        report.check(
+            Status::Warn,
+            "no remotes; add one with `git remote add origin <url>`",
+        );
+    }
+    for (name, url) in remotes {
+        match Remote::parse(&url) {
+            Some(remote) => report.check(
+                Status::Pass,
+                &format!("{name}: {}/{} on {}", remote.user, remote.repo, remote.host),
+            ),
+            None => report.check(
+                Status::Fail,
+                &format!("{name}: {}", Error::RemoteUrlParse(url)),
+            ),
+        }
+    }
+}
+
+fn check_hook(report: &mut Report) {
+    report.section("Commit hook");
+    match hook::state() {
+        Ok(hook::State::Installed(path)) => {
+            report.check(Status::Pass, &format!("installed at {}", path.display()))
+        }
+        Ok(hook::State::Foreign(path)) => report.check(
+            Status::Info,
+            &format!(
+                "{} is another hook; `git ac hook install` would chain it",
+                path.display()
+            ),
+        ),
+        Ok(hook::State::Absent) => report.check(
+            Status::Info,
+            "not installed (optional: `git ac hook install` drafts messages for plain \
+             `git commit`)",
+        ),
+        Err(e) => report.check(Status::Fail, &e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::Scope;
+    use std::fs;
+
+    #[test]
+    fn checks_each_existing_layer_by_itself() {
+        let dir = env::temp_dir().join(format!("git-github-doctor-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let good = dir.join("good.toml");
+        fs::write(&good, "language = \"fr\"\ncolour = \"yes\"\n").unwrap();
+        let bad = dir.join("bad.toml");
+        fs::write(&bad, "[cache]\nttl_hours = \"soon\"\n").unwrap();
+        let layers = [
+            Layer::file(Scope::System, dir.join("missing.toml")),
+            Layer::file(Scope::User, good.clone()),
+            Layer::file(Scope::Repo, bad),
+        ];
+
+        let mut report = Report::default();
+        assert!(!check_layers(&mut report, &layers));
+        let statuses: Vec<Status> = report.checks.iter().map(|(status, _)| *status).collect();
+        assert_eq!(statuses, [Status::Pass, Status::Warn, Status::Fail]);
+        assert_eq!(
+            report.checks[0].1,
+            format!("user:{} (2 settings)", good.display())
+        );
+        assert_eq!(report.checks[1].1, "unknown setting `colour`");
+        assert_eq!(report.failed, 1);
+
+        let mut report = Report::default();
+        assert!(check_layers(&mut report, &layers[..2]));
+        assert_eq!(report.failed, 0);
+    }
+
+    #[test]
+    fn counts_only_failures() {
+        let mut report = Report::default();
+        report.check(Status::Pass, "fine");
+        report.check(Status::Warn, "odd");
+        report.check(Status::Info, "by the way");
+        assert_eq!(report.failed, 0);
+        report.check(Status::Fail, "broken");
+        report.check(Status::Fail, "also broken");
+        assert_eq!(report.failed, 2);
+    }
+}
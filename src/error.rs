@@ -12,19 +12,95 @@ pub enum Error {
     RemoteUrlNotUtf8,
     RemoteUrlParse(String),
     NoCurrentBranch,
-    BranchNotFound { branch: String, remote: String },
+    BranchNotFound {
+        branch: String,
+        remote: String,
+    },
     PathOutsideRepo(PathBuf),
     NoStagedChanges,
     EmptyMessage,
     NoApiKey,
-    NoGitHubToken,
+    NoGitHubToken(&'static str),
+    GitHubAuth(u16),
+    GitHubRateLimited,
+    GitHubApi {
+        status: u16,
+        message: String,
+    },
+    TokenScope {
+        action: &'static str,
+        scopes: Vec<String>,
+    },
+    NoOAuthApp,
+    DeviceFlow(String),
     NoDefaultBranch,
     NoCommitsForPr(String),
+    NoPullForBranch(String),
+    PullNotOpen(u64, &'static str),
     GitCommand(String),
-    ApiError(String),
+    ApiError {
+        host: String,
+        message: String,
+    },
+    ApiAuth(String),
+    ApiQuota(String),
+    RateLimited(String),
+    ApiOverloaded {
+        host: String,
+        status: u16,
+    },
+    ContextTooLong,
+    ApiUnreachable {
+        host: String,
+        source: reqwest::Error,
+    },
+    StreamInterrupted(String),
+    HttpSetup(String),
     CommitCancelled,
     NoCurrentDir,
     NoHomeDir,
+    InvalidPattern(String, String),
+    UnknownCoAuthor(String),
+    NothingToAmend,
+    NoFixupTarget(usize),
+    InvalidRange(String),
+    RangeHasMerges,
+    AlreadyPushed(usize),
+    RebaseFailed,
+    NeedsConfirmation(&'static str),
+    Timeout(u64),
+    HookConflict(PathBuf),
+    NoHistory,
+    NoHistoryEntry(usize),
+    PromptTemplate {
+        path: PathBuf,
+        reason: String,
+    },
+    UnknownPrompt(String),
+    SettingNotFound(String),
+    InvalidSetting(String, String),
+    UnknownProfile {
+        name: String,
+        known: Vec<String>,
+    },
+    ChecksFailed(usize),
+    TagExists(String),
+    NothingToRelease(String),
+    AssetNotFound(PathBuf),
+    NotAVersion(String),
+    NoManifest(PathBuf),
+    InvalidConfig {
+        origin: String,
+        key: Option<String>,
+        reason: String,
+    },
+    SecretCommand {
+        setting: &'static str,
+        command: String,
+        reason: String,
+    },
+    NoSecretStore,
+    SecretStore(&'static str, String),
 
     // --- Wrapped library errors ---
     Git(git2::Error),
@@ -59,23 +135,213 @@ impl fmt::Display for Error {
             Error::EmptyMessage => write!(f, "the AI returned an empty commit message"),
             Error::NoApiKey => write!(
                 f,
-                "no DeepSeek API key found; set `api_key` in ~/.config/git-github/config.toml"
+                "no DeepSeek API key found; run `git-github auth login`, set `api_key_command`, or export DEEPSEEK_API_KEY"
             ),
-            Error::NoGitHubToken => write!(
+            Error::NoGitHubToken(action) => write!(
                 f,
-                "creating a pull request requires a GitHub token; set GITHUB_TOKEN (or GH_TOKEN)"
+                "{} requires a GitHub token; run `git-github auth login --github` or `gh auth login`, or set GITHUB_TOKEN",
+                action
             ),
+            Error::GitHubAuth(status) => write!(
+                f,
+                "GitHub rejected the token ({}); `git-github auth status` shows where it comes from",
+                status
+            ),
+            Error::GitHubRateLimited => write!(
+                f,
+                "GitHub's API rate limit is used up; sign in (`git-github auth login --github`) for a higher limit, or wait for it to reset"
+            ),
+            Error::GitHubApi { status: 403, message } => write!(
+                f,
+                "GitHub refused the request ({}); the token may lack access to this repository, see `git-github auth status`",
+                message
+            ),
+            Error::GitHubApi { status: 404, .. } => write!(
+                f,
+                "GitHub found no such repository or resource; if it is private, the token may lack access to it, see `git-github auth status`"
+            ),
+            Error::GitHubApi { status: 422, message } => {
+                write!(f, "GitHub rejected the request: {}", message)
+            }
+            Error::GitHubApi { status, message } => {
+                write!(f, "GitHub API error ({}): {}", status, message)
+            }
+            Error::TokenScope { action, scopes } => write!(
+                f,
+                "the GitHub token lacks the `repo` scope needed to {} (it has: {}); run `git-github auth login --github` or `gh auth refresh -s repo`",
+                action,
+                if scopes.is_empty() { "none".to_string() } else { scopes.join(", ") }
+            ),
+            Error::NoOAuthApp => write!(
+                f,
+                "logging in needs the client ID of a GitHub OAuth app with device flow enabled; set `github.oauth_client_id`, or sign in with `gh auth login`"
+            ),
+            Error::DeviceFlow(reason) => write!(f, "GitHub login failed: {}", reason),
             Error::NoDefaultBranch => {
                 write!(f, "could not determine the repository's default branch")
             }
             Error::NoCommitsForPr(base) => {
                 write!(f, "no commits between '{}' and the current branch", base)
             }
+            Error::NoPullForBranch(branch) => write!(
+                f,
+                "no open pull request for '{}'; give its number, or open one with `git pr`",
+                branch
+            ),
+            Error::PullNotOpen(number, state) => {
+                write!(f, "pull request #{} is {}", number, state)
+            }
             Error::GitCommand(msg) => write!(f, "git command failed: {}", msg),
-            Error::ApiError(msg) => write!(f, "DeepSeek API error: {}", msg),
+            Error::ApiError { host, message } => write!(f, "API error from {}: {}", host, message),
+            Error::ApiAuth(host) => write!(
+                f,
+                "{} rejected the API key; `git-github auth status` shows where it comes from",
+                host
+            ),
+            Error::ApiQuota(host) if host == "api.deepseek.com" => write!(
+                f,
+                "the DeepSeek account has insufficient balance; top it up at https://platform.deepseek.com"
+            ),
+            Error::ApiQuota(host) => write!(f, "the account at {} has insufficient balance", host),
+            Error::RateLimited(host) => write!(
+                f,
+                "{} kept rate-limiting the requests; wait a minute and try again",
+                host
+            ),
+            Error::ApiOverloaded { host, status } => write!(
+                f,
+                "the API at {} is unavailable or overloaded ({}) even after retrying; try again later",
+                host, status
+            ),
+            Error::ContextTooLong => write!(
+                f,
+                "the changes are too large for the model's context; stage fewer files and commit in parts"
+            ),
+            Error::ApiUnreachable { host, source } => write!(
+                f,
+                "could not reach the API at {} ({}); check your network connection",
+                host, source
+            ),
+            Error::StreamInterrupted(host) => write!(
+                f,
+                "the connection to the API at {} kept dropping mid-response; try again",
+                host
+            ),
+            Error::HttpSetup(msg) => write!(f, "invalid [http] settings: {}", msg),
             Error::CommitCancelled => write!(f, "git commit was cancelled or failed"),
             Error::NoCurrentDir => write!(f, "failed to get the current directory"),
             Error::NoHomeDir => write!(f, "could not determine the home directory"),
+            Error::InvalidPattern(pattern, reason) => {
+                write!(f, "invalid ticket pattern '{}': {}", pattern, reason)
+            }
+            Error::UnknownCoAuthor(alias) => write!(
+                f,
+                "unknown co-author '{}'; add it under [team] in the config or to .mailmap",
+                alias
+            ),
+            Error::NothingToAmend => write!(f, "there is no commit to amend yet"),
+            Error::NoFixupTarget(depth) => write!(
+                f,
+                "none of the last {} commits touched the staged lines; commit them normally",
+                depth
+            ),
+            Error::InvalidRange(range) => write!(
+                f,
+                "invalid range '{}'; expected <base>..HEAD on the current branch",
+                range
+            ),
+            Error::RangeHasMerges => {
+                write!(f, "the range contains merge commits, which cannot be reworded")
+            }
+            Error::AlreadyPushed(count) => write!(
+                f,
+                "{} of the commits are already on a remote; pass --force to rewrite them anyway",
+                count
+            ),
+            Error::RebaseFailed => write!(
+                f,
+                "rebase failed and was aborted; the branch is unchanged"
+            ),
+            Error::NeedsConfirmation(action) => write!(
+                f,
+                "{} needs confirmation, but stdin is not a terminal; pass --yes to go ahead",
+                action
+            ),
+            Error::Timeout(secs) => write!(f, "the model did not respond within {}s", secs),
+            Error::HookConflict(path) => write!(
+                f,
+                "cannot chain the existing hook: {} already exists",
+                path.display()
+            ),
+            Error::NoHistory => write!(
+                f,
+                "no generated message for the staged changes; run `git ac` without --last"
+            ),
+            Error::NoHistoryEntry(index) => write!(
+                f,
+                "no history entry {}; see `git ac history` for the list",
+                index
+            ),
+            Error::PromptTemplate { path, reason } => {
+                write!(f, "invalid prompt template {}: {}", path.display(), reason)
+            }
+            Error::UnknownPrompt(name) => write!(
+                f,
+                "unknown prompt '{}'; expected commit, pr, review or release-notes",
+                name
+            ),
+            Error::SettingNotFound(key) => write!(
+                f,
+                "'{}' is not set; see `git-github config list --show-origin`",
+                key
+            ),
+            Error::InvalidSetting(key, reason) => write!(f, "cannot set '{}': {}", key, reason),
+            Error::InvalidConfig {
+                origin,
+                key: Some(key),
+                reason,
+            } => write!(f, "invalid setting `{}` in {}: {}", key, origin, reason),
+            Error::InvalidConfig {
+                origin,
+                key: None,
+                reason,
+            } => write!(f, "invalid configuration in {}: {}", origin, reason),
+            Error::UnknownProfile { name, known } if known.is_empty() => write!(
+                f,
+                "no profile named '{}'; define one in a [profiles.{}] section",
+                name, name
+            ),
+            Error::UnknownProfile { name, known } => {
+                write!(f, "no profile named '{}' (defined: {})", name, known.join(", "))
+            }
+            Error::ChecksFailed(1) => write!(f, "1 check failed"),
+            Error::ChecksFailed(n) => write!(f, "{} checks failed", n),
+            Error::TagExists(tag) => write!(f, "tag '{}' already exists", tag),
+            Error::NothingToRelease(since) if since.is_empty() => {
+                write!(f, "there are no commits to release")
+            }
+            Error::NothingToRelease(since) => write!(f, "no commits since {}", since),
+            Error::AssetNotFound(path) => write!(f, "asset not found: {}", path.display()),
+            Error::NotAVersion(tag) => {
+                write!(f, "'{}' is not a release version like v1.2.3", tag)
+            }
+            Error::NoManifest(dir) => write!(
+                f,
+                "no Cargo.toml or package.json with a version to bump in {}",
+                dir.display()
+            ),
+            Error::SecretCommand {
+                setting,
+                command,
+                reason,
+            } => write!(f, "{} `{}` failed: {}", setting, command, reason),
+            Error::NoSecretStore => write!(
+                f,
+                "no keyring or git credential helper to store the key in; configure `credential.helper`, or set `api_key_command`"
+            ),
+            Error::SecretStore(store, reason) => {
+                write!(f, "could not save to {}: {}", store, reason)
+            }
             Error::Git(e) => write!(f, "{}", e),
             Error::Io(e) => write!(f, "{}", e),
             Error::Http(e) => write!(f, "{}", e),
@@ -90,7 +356,7 @@ impl std::error::Error for Error {
         match self {
             Error::Git(e) => Some(e),
             Error::Io(e) => Some(e),
-            Error::Http(e) => Some(e),
+            Error::Http(e) | Error::ApiUnreachable { source: e, .. } => Some(e),
             Error::Config(e) => Some(e),
             Error::GitHub(e) => Some(e),
             _ => None,
@@ -122,8 +388,55 @@ impl From<config::ConfigError> for Error {
     }
 }
 
+/// GitHub's error responses become specific errors; transport failures
+/// and the like stay wrapped.
 impl From<octocrab::Error> for Error {
     fn from(e: octocrab::Error) -> Self {
-        Error::GitHub(e)
+        // GraphQL answers a bad query with a 200 and a list of errors.
+        if let octocrab::Error::Graphql { source, .. } = &e {
+            let messages: Vec<&str> = source
+                .0
+                .iter()
+                .map(|error| error.message.as_str())
+                .collect();
+            return Error::GitHubApi {
+                status: 422,
+                message: messages.join("; "),
+            };
+        }
+        let octocrab::Error::GitHub { source, .. } = e else {
+            return Error::GitHub(e);
+        };
+        let status = source.status_code.as_u16();
+        // A 422's `errors` say what was wrong, e.g. "A pull request already
+        // exists for owner:branch."
+        let details: Vec<String> = source
+            .errors
+            .iter()
+            .flatten()
+            .filter_map(|error| match error {
+                serde_json::Value::String(message) => Some(message.clone()),
+                error => error["message"].as_str().map(str::to_string).or_else(|| {
+                    let field = error["field"].as_str()?;
+                    Some(format!(
+                        "{} {}",
+                        field,
+                        error["code"].as_str().unwrap_or("invalid")
+                    ))
+                }),
+            })
+            .collect();
+        let message = if details.is_empty() {
+            source.message.clone()
+        } else {
+            details.join("; ")
+        };
+        match status {
+            401 => Error::GitHubAuth(status),
+            403 | 429 if source.message.to_lowercase().contains("rate limit") => {
+                Error::GitHubRateLimited
+            }
+            _ => Error::GitHubApi { status, message },
+        }
     }
 }
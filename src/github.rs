@@ -1,23 +1,179 @@
-use crate::error::Result;
-use octocrab::Octocrab;
+use crate::auth::{self, Secret, Source};
+use crate::config::{load_github_config, AppConfig};
+use crate::error::{Error, Result};
+use crate::http::{self, Transport};
+use crate::oauth;
+use crate::remote::Remote;
+use octocrab::service::middleware::auth_header::AuthHeaderLayer;
+use octocrab::service::middleware::base_uri::BaseUriLayer;
+use octocrab::{AuthState, Octocrab, OctocrabBuilder};
+use serde::de::DeserializeOwned;
 use std::env;
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
 use std::process::Command;
 
-/// A GitHub token, from `GITHUB_TOKEN`/`GH_TOKEN`, or the `gh` CLI as a
-/// fallback. Enables private repos and lifts the unauthenticated rate limit
-/// (60/hr).
-pub fn token() -> Option<String> {
-    env::var("GITHUB_TOKEN")
-        .or_else(|_| env::var("GH_TOKEN"))
-        .ok()
-        .filter(|t| !t.is_empty())
-        .or_else(gh_cli_token)
-}
-
-/// Ask the `gh` CLI for its stored token, so a machine authenticated with
-/// `gh auth login` works without exporting an env var.
-fn gh_cli_token() -> Option<String> {
-    let output = Command::new("gh").args(["auth", "token"]).output().ok()?;
+/// A GitHub token. Enables private repos and lifts the unauthenticated rate
+/// limit (60/hr).
+pub fn token(config: &AppConfig) -> Result<Option<String>> {
+    Ok(find_token(config)?.map(|secret| secret.value))
+}
+
+/// The GitHub token and its source: `GITHUB_TOKEN`/`GH_TOKEN`, else `[github]
+/// token_command`, else the token `git-github auth login --github` stored,
+/// else the `gh` CLI's (its `hosts.yml`, then `gh auth token` for tokens gh
+/// keeps in a keyring), else git's credential helper for the GitHub host
+/// (the token `git push` over HTTPS uses). All but the first two pick
+/// `[github] user`'s token when set.
+pub fn find_token(config: &AppConfig) -> Result<Option<Secret>> {
+    let found = |value: String, source: Source| Ok(Some(Secret { value, source }));
+    for var in ["GITHUB_TOKEN", "GH_TOKEN"] {
+        if let Some(value) = env::var(var).ok().filter(|t| !t.is_empty()) {
+            return found(value, Source::Env(var));
+        }
+    }
+    let command = config.github.token_command.as_deref();
+    if let Some(command) = command.filter(|c| !c.trim().is_empty()) {
+        let source = Source::Command {
+            setting: "token_command",
+            command: command.to_string(),
+        };
+        return found(auth::run_command("token_command", command)?, source);
+    }
+    let host = web_host(config);
+    let user = config.github.user.as_deref();
+    if let Some(secret) = oauth::stored_token(&host, user) {
+        return Ok(Some(secret));
+    }
+    if let Some(path) = gh_hosts_path() {
+        let token = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| gh_hosts_token(&text, &host, user));
+        if let Some(token) = token {
+            return found(token, Source::GhHosts(path));
+        }
+    }
+    if let Some(token) = gh_cli_token(&host, user) {
+        return found(token, Source::GhCli);
+    }
+    if let Some(token) = auth::credential_fill(&host, user) {
+        return found(token, Source::GitCredential);
+    }
+    Ok(None)
+}
+
+/// The host git talks to: `github.com`, or the GitHub Enterprise host of
+/// `[github] api_url`.
+pub(crate) fn web_host(config: &AppConfig) -> String {
+    match config.github.api_url.as_deref() {
+        None => "github.com".to_string(),
+        Some(url) => {
+            let host = auth::host_of(url);
+            host.strip_prefix("api.")
+                .map(str::to_string)
+                .unwrap_or(host)
+        }
+    }
+}
+
+/// gh's `hosts.yml`: in `$GH_CONFIG_DIR`, else `$XDG_CONFIG_HOME/gh`, else
+/// `~/.config/gh`.
+fn gh_hosts_path() -> Option<PathBuf> {
+    let dir = match env::var_os("GH_CONFIG_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir).join("gh"),
+            None => PathBuf::from(env::var_os("HOME")?).join(".config/gh"),
+        },
+    };
+    Some(dir.join("hosts.yml"))
+}
+
+/// The token in gh's `hosts.yml` for `host`: that of `user` (gh keeps one
+/// per account under `users:`) or of the active account. gh leaves the
+/// token out when it keeps it in the keyring.
+///
+/// ```yaml
+/// github.com:
+///     users:
+///         octocat:
+///             oauth_token: gho_…
+///     oauth_token: gho_…
+///     user: octocat
+/// ```
+fn gh_hosts_token(text: &str, host: &str, user: Option<&str>) -> Option<String> {
+    let leaves = yaml_leaves(text);
+    let get = |path: &[&str]| {
+        leaves
+            .iter()
+            .find(|(key, _)| key.iter().map(String::as_str).eq(path.iter().copied()))
+            .map(|(_, value)| value.clone())
+            .filter(|value| !value.is_empty())
+    };
+    match user {
+        Some(user) => get(&[host, "users", user, "oauth_token"]).or_else(|| {
+            let active = get(&[host, "user"])?;
+            active
+                .eq_ignore_ascii_case(user)
+                .then(|| get(&[host, "oauth_token"]))?
+        }),
+        None => get(&[host, "oauth_token"]),
+    }
+}
+
+/// The `key: value` lines of a block-style YAML mapping, each with the
+/// keys of its parents, as far as gh's `hosts.yml` needs: no lists, flow
+/// collections or multi-line strings.
+fn yaml_leaves(text: &str) -> Vec<(Vec<String>, String)> {
+    let unquote = |s: &str| {
+        let s = s.trim();
+        s.strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .or_else(|| s.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')))
+            .unwrap_or(s)
+            .to_string()
+    };
+    let mut parents: Vec<(usize, String)> = Vec::new();
+    let mut leaves = Vec::new();
+    for line in text.lines() {
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - content.len();
+        // Keys may hold colons themselves (`ghe.corp.com:8443:`).
+        let content = content.split(" #").next().unwrap_or_default().trim_end();
+        let Some((key, value)) = content
+            .strip_suffix(':')
+            .map(|key| (key, ""))
+            .or_else(|| content.split_once(": "))
+        else {
+            continue;
+        };
+        parents.retain(|(depth, _)| *depth < indent);
+        let value = value.trim();
+        if value.is_empty() {
+            parents.push((indent, unquote(key)));
+        } else {
+            let mut path: Vec<String> = parents.iter().map(|(_, key)| key.clone()).collect();
+            path.push(unquote(key));
+            leaves.push((path, unquote(value)));
+        }
+    }
+    leaves
+}
+
+/// Ask the `gh` CLI for its stored token for `host` (and `user`, if gh is
+/// signed in to several accounts), so a machine authenticated with `gh auth
+/// login` works without exporting an env var.
+fn gh_cli_token(host: &str, user: Option<&str>) -> Option<String> {
+    let mut command = Command::new("gh");
+    command.args(["auth", "token", "--hostname", host]);
+    if let Some(user) = user {
+        command.args(["--user", user]);
+    }
+    let output = command.output().ok()?;
     if !output.status.success() {
         return None;
     }
@@ -25,11 +181,177 @@ fn gh_cli_token() -> Option<String> {
     (!token.is_empty()).then_some(token)
 }
 
-/// An authenticated client when a token is present, otherwise the shared
-/// anonymous instance (sufficient for reading public data).
+/// Who a token belongs to and what it may do.
+pub struct TokenInfo {
+    pub login: String,
+    /// The OAuth scopes granted (`X-OAuth-Scopes`); `None` for fine-grained
+    /// and app tokens, whose permissions GitHub doesn't report.
+    pub scopes: Option<Vec<String>>,
+}
+
+/// Ask GitHub about the client's token, with a `GET /user`.
+pub async fn token_info(client: &Octocrab) -> Result<TokenInfo> {
+    let response = client._get("/user").await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::GitHubAuth(status.as_u16()));
+    }
+    let scopes = parse_scopes(response.headers().get("x-oauth-scopes"));
+    let body = client.body_to_string(response).await?;
+    let user: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+    Ok(TokenInfo {
+        login: user["login"].as_str().unwrap_or("?").to_string(),
+        scopes,
+    })
+}
+
+/// Run a GraphQL query with `variables`, deserializing its `data`.
+pub(crate) async fn graphql<T: DeserializeOwned>(
+    client: &Octocrab,
+    query: &str,
+    variables: serde_json::Value,
+) -> Result<T> {
+    let request = serde_json::json!({ "query": query, "variables": variables });
+    Ok(client.graphql(&request).await?)
+}
+
+/// The scopes listed in an `X-OAuth-Scopes` header, if there is one.
+pub(crate) fn parse_scopes(header: Option<&::http::HeaderValue>) -> Option<Vec<String>> {
+    let value = header?.to_str().ok()?;
+    Some(
+        value
+            .split(',')
+            .map(|scope| scope.trim().to_string())
+            .filter(|scope| !scope.is_empty())
+            .collect(),
+    )
+}
+
+/// Whether OAuth `scopes` allow opening pull requests and other writes to
+/// repositories: `repo`, or `public_repo` for public ones only.
+pub fn allows_pull_requests(scopes: &[String]) -> bool {
+    scopes.iter().any(|s| s == "repo" || s == "public_repo")
+}
+
+/// Fetch the repository a write is about to go to, checking on the way that
+/// the token has the scope for it, so a missing scope is reported as such
+/// rather than as GitHub's bare 404. Only classic OAuth tokens list their
+/// scopes; fine-grained, app and Actions tokens pass, and their permissions
+/// show in the write's own error. The header is read off the repository,
+/// which the write needs anyway, rather than `/user`, which installation
+/// tokens are refused.
+pub async fn writable_repo<T: DeserializeOwned>(
+    client: &Octocrab,
+    remote: &Remote,
+    action: &'static str,
+) -> Result<T> {
+    let route = format!("/repos/{}/{}", remote.user, remote.repo);
+    let response = octocrab::map_github_error(client._get(route).await?).await?;
+    let status = response.status().as_u16();
+    match parse_scopes(response.headers().get("x-oauth-scopes")) {
+        Some(scopes) if !allows_pull_requests(&scopes) => {
+            return Err(Error::TokenScope { action, scopes })
+        }
+        _ => {}
+    }
+    let body = client.body_to_string(response).await?;
+    serde_json::from_str(&body).map_err(|e| Error::GitHubApi {
+        status,
+        message: format!("unexpected repository response: {e}"),
+    })
+}
+
+pub(crate) const GITHUB_API: &str = "https://api.github.com";
+
+/// Run a GitHub operation, building the client inside the runtime so its
+/// hyper/tower stack has a reactor (octocrab's buffered client panics
+/// otherwise).
+pub(crate) fn run<F, Fut, T, E>(op: F) -> Result<T>
+where
+    F: FnOnce(Octocrab) -> Fut,
+    Fut: Future<Output = std::result::Result<T, E>>,
+    Error: From<E>,
+{
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    rt.block_on(async move {
+        let client = client()?;
+        op(client).await.map_err(Error::from)
+    })
+}
+
+/// An authenticated client when a token is present, otherwise an anonymous
+/// one (sufficient for reading public data). Requests go through the shared
+/// [`http`] client, so `[http]` proxy and TLS settings apply, to `[github]
+/// api_url` when set.
 pub fn client() -> Result<Octocrab> {
-    match token() {
-        Some(token) => Ok(Octocrab::builder().personal_token(token).build()?),
-        None => Ok((*octocrab::instance()).clone()),
+    let config = load_github_config()?;
+    let api_url = config.github.api_url.as_deref().unwrap_or(GITHUB_API);
+    let base: ::http::Uri = api_url
+        .trim_end_matches('/')
+        .parse()
+        .map_err(|_| Error::HttpSetup(format!("invalid GitHub api_url '{api_url}'")))?;
+    let upload = if config.github.api_url.is_some() {
+        base.clone()
+    } else {
+        "https://uploads.github.com".parse().expect("valid URI")
+    };
+    let auth = match token(&config)? {
+        Some(token) => Some(
+            format!("Bearer {token}")
+                .parse()
+                .map_err(|_| Error::HttpSetup("the GitHub token is not a valid header".into()))?,
+        ),
+        None => None,
+    };
+
+    Ok(OctocrabBuilder::new_empty()
+        .with_service(Transport(http::client(&config.http)?))
+        .with_layer(&BaseUriLayer::new(base.clone()))
+        .with_layer(&AuthHeaderLayer::new(auth, base, upload))
+        .with_auth(AuthState::None)
+        .build()
+        .unwrap_or_else(|never| match never {}))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HOSTS: &str = "github.com:\n    users:\n        octocat:\n            oauth_token: gho_octo\n        hubot:\n            oauth_token: \"gho_hubot\"\n    git_protocol: https\n    oauth_token: gho_octo # active\n    user: octocat\nghe.corp.com:\n    user: jdoe\n";
+
+    #[test]
+    fn reads_tokens_from_gh_hosts_files() {
+        assert_eq!(
+            gh_hosts_token(HOSTS, "github.com", None).as_deref(),
+            Some("gho_octo")
+        );
+        assert_eq!(
+            gh_hosts_token(HOSTS, "github.com", Some("hubot")).as_deref(),
+            Some("gho_hubot")
+        );
+        assert_eq!(gh_hosts_token(HOSTS, "github.com", Some("nobody")), None);
+        // gh keeps the token in the keyring; `gh auth token` has to fetch it.
+        assert_eq!(gh_hosts_token(HOSTS, "ghe.corp.com", None), None);
+        let port = "ghe.corp.com:8443:\n  oauth_token: gho_ghe\n";
+        assert_eq!(
+            gh_hosts_token(port, "ghe.corp.com:8443", None).as_deref(),
+            Some("gho_ghe")
+        );
+        let old = "github.com:\n  oauth_token: gho_old\n  user: octocat\n";
+        assert_eq!(
+            gh_hosts_token(old, "github.com", Some("octocat")).as_deref(),
+            Some("gho_old")
+        );
+    }
+
+    #[test]
+    fn write_scopes() {
+        let scopes = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(allows_pull_requests(&scopes(&["read:org", "repo"])));
+        assert!(allows_pull_requests(&scopes(&["public_repo"])));
+        assert!(!allows_pull_requests(&scopes(&["read:org", "gist"])));
+        assert!(!allows_pull_requests(&[]));
     }
 }
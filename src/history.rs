@@ -0,0 +1,198 @@
+//! A per-repository log of generated commit messages, kept in
+//! `<git dir>/git-github/history.jsonl`. Every message is recorded as soon as
+//! it is generated, so one that was regenerated away, aborted, or lost to a
+//! failing `commit-msg` hook can still be recovered (`git ac --last`,
+//! `git ac history`).
+
+use crate::error::{Error, Result};
+use crate::repo::Repo;
+use crate::style;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const FILE: &str = "history.jsonl";
+/// Older entries are dropped once the log grows past this.
+const MAX_ENTRIES: usize = 200;
+
+/// One generated message and what produced it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    /// The staged tree the message describes (see [`Repo::staged_tree_id`]).
+    pub tree: String,
+    pub model: String,
+    /// Regenerate guidance in effect, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub guidance: Vec<String>,
+    pub message: String,
+}
+
+impl Entry {
+    /// An entry for `message`, stamped with the current time.
+    pub fn new(tree: &str, model: &str, guidance: &[String], message: &str) -> Entry {
+        Entry {
+            time: now(),
+            tree: tree.to_string(),
+            model: model.to_string(),
+            guidance: guidance.to_vec(),
+            message: message.trim().to_string(),
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn path(repo: &Repo) -> PathBuf {
+    repo.data_dir().join(FILE)
+}
+
+/// All recorded entries, oldest first. A missing log is empty; lines that
+/// don't parse (say, from a newer version) are skipped.
+pub fn load(repo: &Repo) -> Result<Vec<Entry>> {
+    let text = match fs::read_to_string(path(repo)) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(text
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Append `entry` to the log, dropping the oldest beyond [`MAX_ENTRIES`].
+pub fn record(repo: &Repo, entry: Entry) -> Result<()> {
+    let mut entries = load(repo)?;
+    entries.push(entry);
+    let overflow = entries.len().saturating_sub(MAX_ENTRIES);
+
+    let mut text = String::new();
+    for entry in &entries[overflow..] {
+        text.push_str(&serde_json::to_string(entry).map_err(io::Error::from)?);
+        text.push('\n');
+    }
+    fs::create_dir_all(repo.data_dir())?;
+    fs::write(path(repo), text)?;
+    Ok(())
+}
+
+/// The most recent message generated for `tree`.
+pub fn latest_for<'a>(entries: &'a [Entry], tree: &str) -> Option<&'a Entry> {
+    entries.iter().rev().find(|e| e.tree == tree)
+}
+
+/// `git ac history`: list the newest `limit` entries, numbered from 1 (the
+/// newest), or print entry `index` in full — bare, so it can be piped into
+/// `git commit -F -`.
+pub fn show(index: Option<usize>, limit: usize) -> Result<()> {
+    let path = env::current_dir().map_err(|_| Error::NoCurrentDir)?;
+    let repo = Repo::new(&path)?;
+    let entries = load(&repo)?;
+
+    if let Some(index) = index {
+        let entry = index
+            .checked_sub(1)
+            .and_then(|i| entries.iter().rev().nth(i))
+            .ok_or(Error::NoHistoryEntry(index))?;
+        println!("{}", entry.message);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No generated messages yet.");
+        return Ok(());
+    }
+    // Mark messages for what is staged right now: `git ac --last` material.
+    let staged = repo.staged_tree_id().ok();
+    for (idx, entry) in entries.iter().rev().take(limit).enumerate() {
+        let subject = entry.message.lines().next().unwrap_or_default();
+        let marker = if staged.as_deref() == Some(entry.tree.as_str()) {
+            style::green("*")
+        } else {
+            " ".to_string()
+        };
+        println!(
+            "{:>3} {marker} {}  {}",
+            style::cyan(&(idx + 1).to_string()),
+            style::dim(&format!("{:<14}", age(entry.time))),
+            subject
+        );
+        for hint in &entry.guidance {
+            println!("        {}", style::dim(&format!("guidance: {hint}")));
+        }
+    }
+    if entries.len() > limit {
+        println!(
+            "{}",
+            style::dim(&format!("… {} older (use -n)", entries.len() - limit))
+        );
+    }
+    Ok(())
+}
+
+/// How long ago `time` (seconds since the epoch) was, e.g. `5 minutes ago`.
+pub fn age(time: u64) -> String {
+    ago(now().saturating_sub(time))
+}
+
+/// A coarse human duration: `just now`, `5 minutes ago`, `2 days ago`.
+fn ago(secs: u64) -> String {
+    let (count, unit) = match secs {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (secs / 60, "minute"),
+        3600..=86_399 => (secs / 3600, "hour"),
+        _ => (secs / 86_400, "day"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{count} {unit}{plural} ago")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(tree: &str, message: &str) -> Entry {
+        Entry::new(tree, "deepseek-chat", &[], message)
+    }
+
+    #[test]
+    fn finds_the_latest_message_for_a_tree() {
+        let entries = vec![
+            entry("aaa", "First try"),
+            entry("bbb", "Other change"),
+            entry("aaa", "Second try\n"),
+        ];
+        assert_eq!(latest_for(&entries, "aaa").unwrap().message, "Second try");
+        assert!(latest_for(&entries, "ccc").is_none());
+    }
+
+    #[test]
+    fn entries_round_trip_as_json_lines() {
+        let mut with_guidance = entry("aaa", "Fix it");
+        with_guidance.guidance = vec!["shorter".into()];
+        let line = serde_json::to_string(&with_guidance).unwrap();
+        assert!(!line.contains('\n'));
+        assert_eq!(serde_json::from_str::<Entry>(&line).unwrap(), with_guidance);
+
+        let line = serde_json::to_string(&entry("aaa", "Fix it")).unwrap();
+        assert!(!line.contains("guidance"));
+    }
+
+    #[test]
+    fn formats_coarse_durations() {
+        assert_eq!(ago(5), "just now");
+        assert_eq!(ago(60), "1 minute ago");
+        assert_eq!(ago(7200), "2 hours ago");
+        assert_eq!(ago(86_400 * 3), "3 days ago");
+    }
+}
@@ -0,0 +1,259 @@
+//! `git ac hook`: a `prepare-commit-msg` hook so a plain `git commit` (say,
+//! from an IDE) gets an AI-drafted message too. The hook never blocks a
+//! commit: a slow model, missing key or network failure just leaves the
+//! message empty, as if the hook weren't there.
+
+use crate::ai;
+use crate::auth;
+use crate::cache::Cache;
+use crate::config::{load_config, DEFAULT_HOOK_TIMEOUT};
+use crate::error::{Error, Result};
+use crate::history;
+use crate::language::Language;
+use crate::llm::{self, Api};
+use crate::repo::Repo;
+use crate::style;
+use crate::ticket;
+use crate::trailer;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const HOOK: &str = "prepare-commit-msg";
+/// Identifies a hook file as ours.
+const MARKER: &str = "# Installed by git-github (`git ac hook install`).";
+/// A pre-existing hook is moved aside under this suffix and run first.
+const CHAINED_SUFFIX: &str = ".pre-git-github";
+
+fn hook_paths() -> Result<(PathBuf, PathBuf)> {
+    let path = env::current_dir().map_err(|_| Error::NoCurrentDir)?;
+    let dir = Repo::new(&path)?.hooks_dir()?;
+    Ok((dir.join(HOOK), dir.join(format!("{HOOK}{CHAINED_SUFFIX}"))))
+}
+
+/// Whether the hook is set up in the current repository.
+pub(crate) enum State {
+    Installed(PathBuf),
+    /// Another `prepare-commit-msg` hook, not ours.
+    Foreign(PathBuf),
+    Absent,
+}
+
+pub(crate) fn state() -> Result<State> {
+    let (hook, _) = hook_paths()?;
+    Ok(match fs::read_to_string(&hook) {
+        Ok(text) if text.contains(MARKER) => State::Installed(hook),
+        Ok(_) => State::Foreign(hook),
+        Err(_) => State::Absent,
+    })
+}
+
+/// Install the hook into the repository's hooks directory (respecting
+/// `core.hooksPath`). An existing `prepare-commit-msg` hook is kept and
+/// chained: it runs first, and a failure from it still aborts the commit.
+pub fn install() -> Result<()> {
+    let (hook, chained) = hook_paths()?;
+    if let Some(parent) = hook.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if hook.exists() {
+        if fs::read_to_string(&hook)
+            .unwrap_or_default()
+            .contains(MARKER)
+        {
+            println!("The hook is already installed at {}", hook.display());
+            return Ok(());
+        }
+        if chained.exists() {
+            return Err(Error::HookConflict(chained));
+        }
+        fs::rename(&hook, &chained)?;
+    }
+
+    // Prefer the binary doing the install, so IDEs with a minimal PATH still
+    // find it; fall back to whatever `git-ac` is on PATH.
+    let exe = env::current_exe()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| "git-ac".to_string());
+    fs::write(&hook, script(&exe))?;
+    make_executable(&hook)?;
+
+    style::success(&format!("Installed {}", hook.display()));
+    if chained.exists() {
+        println!("The existing hook was kept and runs first.");
+    }
+    Ok(())
+}
+
+/// Remove our hook, restoring the hook it chained (if any).
+pub fn uninstall() -> Result<()> {
+    let (hook, chained) = hook_paths()?;
+    if !fs::read_to_string(&hook)
+        .unwrap_or_default()
+        .contains(MARKER)
+    {
+        println!("The hook is not installed.");
+        return Ok(());
+    }
+
+    fs::remove_file(&hook)?;
+    if chained.exists() {
+        fs::rename(&chained, &hook)?;
+        style::success("Removed the hook and restored the previous one");
+    } else {
+        style::success("Removed the hook");
+    }
+    Ok(())
+}
+
+/// The hook body. `exec` is avoided so the hook's own exit status is always
+/// 0 once the chained hook has passed.
+fn script(exe: &str) -> String {
+    format!(
+        r#"#!/bin/sh
+{MARKER}
+# Pre-fills an empty commit message from the staged diff; never blocks a commit.
+chained="$(dirname "$0")/{HOOK}{CHAINED_SUFFIX}"
+if [ -x "$chained" ]; then
+    "$chained" "$@" || exit $?
+fi
+exe={exe}
+[ -x "$exe" ] || exe=$(command -v git-ac) || exit 0
+"$exe" hook run "$@" </dev/null || true
+exit 0
+"#,
+        exe = quote(exe)
+    )
+}
+
+/// Single-quote `text` for `sh`.
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(path, permissions)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Entry point for the installed hook. Fills in `message_file` only when git
+/// has no message of its own: an empty `source` (not `-m`/`-F`, a template,
+/// merge, squash or amend) and a file holding nothing but comments and
+/// trailers such as `git commit -s`'s sign-off. Failures are reported on
+/// stderr and otherwise ignored.
+pub fn run(message_file: &Path, source: Option<&str>) -> Result<()> {
+    if source.is_some_and(|s| !s.is_empty()) {
+        return Ok(());
+    }
+    let existing = fs::read_to_string(message_file)?;
+    if has_message(&existing) {
+        return Ok(());
+    }
+
+    let drafted = draft(&existing);
+    crate::usage::settle("hook", false);
+    match drafted {
+        Ok(message) => fs::write(message_file, format!("{message}\n{existing}"))?,
+        Err(e) => eprintln!("git-github: no message drafted ({e})"),
+    }
+    Ok(())
+}
+
+/// Whether `text` already carries a message: any line that is neither blank,
+/// a comment, nor a `Key: value` trailer. The diff `git commit -v` appends
+/// below its scissors line doesn't count.
+fn has_message(text: &str) -> bool {
+    text.lines()
+        .take_while(|l| !(l.starts_with('#') && l.contains(">8")))
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .any(|l| !trailer::is_trailer(l))
+}
+
+/// Generate a message for the staged changes within the configured timeout,
+/// with the same trailers `git ac` would add (minus any already present in
+/// `existing`).
+fn draft(existing: &str) -> Result<String> {
+    let path = env::current_dir().map_err(|_| Error::NoCurrentDir)?;
+    let repo = Repo::new(&path)?;
+    let changes = repo.get_staged_git_changes()?;
+    let mut config = load_config()?;
+    auth::require_api_key(&mut config)?;
+
+    let language = Language::choose(None, config.language.as_deref());
+    let prompt = ai::load_prompt(&repo, &config)?;
+    let values = ai::prompt_values(&prompt, &repo, &config, language.as_ref());
+    let conventions = ai::conventions(&repo, &config);
+    let messages = ai::build_prompt_messages(
+        &prompt,
+        &values,
+        &changes,
+        conventions.as_deref(),
+        language.as_ref(),
+    );
+    let timeout = config.commit.hook_timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT);
+    let model = config.deepseek.model.as_deref().unwrap_or("deepseek-chat");
+    let message = llm::collect(
+        &Api::new(&config),
+        model,
+        messages,
+        config.deepseek.temperature,
+        Duration::from_secs(timeout),
+        Cache::open(&config.cache).as_ref(),
+    )?;
+    if message.trim().is_empty() {
+        return Err(Error::EmptyMessage);
+    }
+
+    let ticket = match repo.current_branch() {
+        Ok(branch) => ticket::from_branch(&branch, &config.tickets)?,
+        Err(_) => None,
+    };
+    let mut trailers = config.commit.trailers.clone();
+    if config.commit.signoff {
+        trailers.push(format!("Signed-off-by: {}", repo.identity()?));
+    }
+    trailers.retain(|t| !existing.lines().any(|l| l.trim() == t));
+
+    let stripped = trailer::strip_managed(&message, &config.commit.trailers);
+    let message = trailer::apply(&stripped, ticket.as_ref(), &trailers).0;
+    if let Ok(tree) = repo.staged_tree_id() {
+        let _ = history::record(&repo, history::Entry::new(&tree, model, &[], &message));
+    }
+    Ok(message)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn comments_and_trailers_are_not_a_message() {
+        assert!(!has_message("\n# Please enter the commit message\n#\n"));
+        assert!(!has_message("\nSigned-off-by: A <a@x>\n# comment\n"));
+        assert!(has_message("Fix the build\n# comment\n"));
+        assert!(!has_message(
+            "\n# ------------------------ >8 ------------------------\ndiff --git a/x b/x\n"
+        ));
+    }
+
+    #[test]
+    fn script_chains_and_never_fails() {
+        let script = script("/opt/bin/git-ac");
+        assert!(script.contains(MARKER));
+        assert!(script.contains("prepare-commit-msg.pre-git-github"));
+        assert!(script.contains("exe='/opt/bin/git-ac'"));
+        assert!(script.trim_end().ends_with("exit 0"));
+    }
+}
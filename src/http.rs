@@ -0,0 +1,175 @@
+//! The HTTP client behind every API call. The model requests and octocrab's
+//! GitHub requests both go through a `reqwest::Client` built from `[http]`,
+//! so a proxy, an extra CA, a client certificate or the timeouts configured
+//! once apply everywhere.
+
+use crate::config::HttpConfig;
+use crate::error::{Error, Result};
+use octocrab::OctoBody;
+use reqwest::{Certificate, Client, Identity, NoProxy, Proxy};
+use std::fs;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// A client configured by `config`. Without an explicit `proxy`, reqwest
+/// honours `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY` from the environment.
+pub fn client(config: &HttpConfig) -> Result<Client> {
+    // `connect_timeout` bounds reaching the server; `read_timeout` is an idle
+    // timeout between reads, so a long-but-active stream is not cut off while
+    // a stalled connection still fails fast.
+    let mut builder = Client::builder()
+        .user_agent(concat!("git-github/", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(Duration::from_secs(config.connect_timeout))
+        .read_timeout(Duration::from_secs(config.read_timeout));
+
+    if let Some(url) = &config.proxy {
+        let proxy =
+            Proxy::all(url).map_err(|e| Error::HttpSetup(format!("invalid proxy '{url}': {e}")))?;
+        let no_proxy = match &config.no_proxy {
+            Some(list) => NoProxy::from_string(list),
+            None => NoProxy::from_env(),
+        };
+        builder = builder.proxy(proxy.no_proxy(no_proxy));
+    }
+
+    if let Some(path) = &config.ca_bundle {
+        let certs = Certificate::from_pem_bundle(&read(path)?)
+            .map_err(|e| Error::HttpSetup(format!("{}: {e}", path.display())))?;
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    match (&config.client_cert, &config.client_key) {
+        (Some(cert), Some(key)) => {
+            let identity = Identity::from_pkcs8_pem(&read(cert)?, &read(key)?)
+                .map_err(|e| Error::HttpSetup(format!("{}: {e}", cert.display())))?;
+            builder = builder.identity(identity);
+        }
+        (None, None) => {}
+        _ => {
+            return Err(Error::HttpSetup(
+                "client_cert and client_key must be set together".to_string(),
+            ))
+        }
+    }
+
+    builder.build().map_err(|e| Error::HttpSetup(e.to_string()))
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|e| Error::HttpSetup(format!("{}: {e}", path.display())))
+}
+
+/// A [`Client`] as the transport under octocrab's middleware, in place of
+/// its own hyper connector (which knows nothing about proxies or extra CAs).
+#[derive(Clone)]
+pub struct Transport(pub Client);
+
+type Response = http::Response<reqwest::Body>;
+
+impl tower_service::Service<http::Request<OctoBody>> for Transport {
+    type Response = Response;
+    type Error = reqwest::Error;
+    type Future =
+        Pin<Box<dyn Future<Output = std::result::Result<Response, reqwest::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<OctoBody>) -> Self::Future {
+        let client = self.0.clone();
+        Box::pin(async move {
+            let request = reqwest::Request::try_from(request.map(reqwest::Body::wrap))?;
+            Ok(client.execute(request).await?.into())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// A one-shot HTTP server on localhost that answers `body` and hands
+    /// back the request line it received.
+    fn serve_once(body: &'static str) -> (u16, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 4096];
+            let len = stream.read(&mut request).unwrap();
+            let request = String::from_utf8_lossy(&request[..len]).to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            request.lines().next().unwrap_or_default().to_string()
+        });
+        (port, handle)
+    }
+
+    fn get(config: &HttpConfig, url: &str) -> String {
+        let client = client(config).unwrap();
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async { client.get(url).send().await?.text().await })
+            .unwrap()
+    }
+
+    #[test]
+    fn sends_requests_through_the_proxy() {
+        let (port, proxy) = serve_once("via proxy");
+        let config = HttpConfig {
+            proxy: Some(format!("http://127.0.0.1:{port}")),
+            no_proxy: Some(String::new()),
+            ..HttpConfig::default()
+        };
+        assert_eq!(
+            get(&config, "http://api.example.invalid/models"),
+            "via proxy"
+        );
+        assert_eq!(
+            proxy.join().unwrap(),
+            "GET http://api.example.invalid/models HTTP/1.1"
+        );
+    }
+
+    #[test]
+    fn bypasses_the_proxy_for_no_proxy_hosts() {
+        let (port, server) = serve_once("direct");
+        let config = HttpConfig {
+            // Nothing listens here; using it would fail the request.
+            proxy: Some("http://127.0.0.1:9".to_string()),
+            no_proxy: Some("example.com, 127.0.0.1".to_string()),
+            ..HttpConfig::default()
+        };
+        assert_eq!(get(&config, &format!("http://127.0.0.1:{port}/")), "direct");
+        assert_eq!(server.join().unwrap(), "GET / HTTP/1.1");
+    }
+
+    #[test]
+    fn rejects_unusable_settings() {
+        let half = HttpConfig {
+            client_cert: Some("cert.pem".into()),
+            ..HttpConfig::default()
+        };
+        assert!(matches!(client(&half), Err(Error::HttpSetup(_))));
+        let missing = HttpConfig {
+            ca_bundle: Some("/nonexistent/ca.pem".into()),
+            ..HttpConfig::default()
+        };
+        assert!(matches!(client(&missing), Err(Error::HttpSetup(_))));
+    }
+}
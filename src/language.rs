@@ -0,0 +1,239 @@
+//! The language generated messages are written in (`language` in the config,
+//! `--lang` on the command line). The model is told to write prose in it
+//! while keeping Conventional Commit types, scopes and identifiers as they
+//! are; trailers are ours and never translated. For languages with a
+//! distinctive script, the output is checked to actually be written in it.
+
+use crate::style;
+use crate::trailer;
+
+/// Writing systems we can recognise in generated text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Han,
+    /// Kana, usually mixed with Han.
+    Japanese,
+    Hangul,
+    Cyrillic,
+    Greek,
+    Arabic,
+    Hebrew,
+    Devanagari,
+    Thai,
+}
+
+impl Script {
+    fn contains(self, ch: char) -> bool {
+        let c = ch as u32;
+        match self {
+            Script::Latin => ch.is_ascii_alphabetic() || (0xc0..=0x24f).contains(&c),
+            Script::Han => is_han(c),
+            Script::Japanese => is_han(c) || (0x3040..=0x30ff).contains(&c),
+            Script::Hangul => (0xac00..=0xd7af).contains(&c) || (0x1100..=0x11ff).contains(&c),
+            Script::Cyrillic => (0x400..=0x4ff).contains(&c),
+            Script::Greek => (0x370..=0x3ff).contains(&c),
+            Script::Arabic => (0x600..=0x6ff).contains(&c),
+            Script::Hebrew => (0x590..=0x5ff).contains(&c),
+            Script::Devanagari => (0x900..=0x97f).contains(&c),
+            Script::Thai => (0xe00..=0xe7f).contains(&c),
+        }
+    }
+}
+
+fn is_han(c: u32) -> bool {
+    (0x4e00..=0x9fff).contains(&c) || (0x3400..=0x4dbf).contains(&c)
+}
+
+/// A requested output language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Language {
+    /// How the model is told, e.g. `Simplified Chinese`.
+    pub name: String,
+    script: Option<Script>,
+}
+
+/// Known languages: codes and names (lowercase) → display name and script.
+const KNOWN: &[(&[&str], &str, Script)] = &[
+    (&["en", "english"], "English", Script::Latin),
+    (
+        &["zh", "zh-cn", "zh-hans", "chinese", "中文", "简体中文"],
+        "Simplified Chinese",
+        Script::Han,
+    ),
+    (
+        &["zh-tw", "zh-hk", "zh-hant", "繁體中文"],
+        "Traditional Chinese",
+        Script::Han,
+    ),
+    (&["ja", "japanese", "日本語"], "Japanese", Script::Japanese),
+    (&["ko", "korean", "한국어"], "Korean", Script::Hangul),
+    (&["ru", "russian", "русский"], "Russian", Script::Cyrillic),
+    (&["uk", "ukrainian"], "Ukrainian", Script::Cyrillic),
+    (&["el", "greek"], "Greek", Script::Greek),
+    (&["ar", "arabic"], "Arabic", Script::Arabic),
+    (&["he", "hebrew"], "Hebrew", Script::Hebrew),
+    (&["hi", "hindi"], "Hindi", Script::Devanagari),
+    (&["th", "thai"], "Thai", Script::Thai),
+    (&["de", "german", "deutsch"], "German", Script::Latin),
+    (&["fr", "french", "français"], "French", Script::Latin),
+    (&["es", "spanish", "español"], "Spanish", Script::Latin),
+    (&["pt", "pt-br", "portuguese"], "Portuguese", Script::Latin),
+    (&["it", "italian"], "Italian", Script::Latin),
+    (&["nl", "dutch"], "Dutch", Script::Latin),
+    (&["pl", "polish"], "Polish", Script::Latin),
+    (&["tr", "turkish"], "Turkish", Script::Latin),
+    (&["vi", "vietnamese"], "Vietnamese", Script::Latin),
+];
+
+impl Language {
+    /// `spec` as a language: a code (`zh-CN`, `ja_JP`) or name (`Chinese`,
+    /// `中文`). Anything unrecognised is passed to the model as written,
+    /// without a script check.
+    pub fn parse(spec: &str) -> Language {
+        let key = spec.trim().to_lowercase().replace('_', "-");
+        // A locale we don't list by itself falls back to its language code.
+        let primary = key.split('-').next().unwrap_or_default();
+        let lookup = |key: &str| KNOWN.iter().find(|(aliases, ..)| aliases.contains(&key));
+        lookup(&key)
+            .or_else(|| lookup(primary))
+            .map(|&(_, name, script)| Language {
+                name: name.to_string(),
+                script: Some(script),
+            })
+            .unwrap_or_else(|| Language {
+                name: spec.trim().to_string(),
+                script: None,
+            })
+    }
+
+    /// The `--lang` flag, else the configured `language`.
+    pub fn choose(flag: Option<&str>, configured: Option<&str>) -> Option<Language> {
+        flag.or(configured)
+            .filter(|spec| !spec.trim().is_empty())
+            .map(Language::parse)
+    }
+
+    /// The system instruction asking for this language.
+    pub fn instruction(&self) -> String {
+        format!(
+            "Write the text in {}. Keep Conventional Commit types and scopes (feat, fix(api), …), \
+             code identifiers, file paths and commands exactly as they are, in English.",
+            self.name
+        )
+    }
+
+    /// Whether `text` is plausibly written in this language's script. Code
+    /// spans, a Conventional Commit prefix and trailers are ignored, and a
+    /// share of other letters is tolerated for identifiers and loanwords.
+    pub fn matches(&self, text: &str) -> bool {
+        let Some(script) = self.script else {
+            return true;
+        };
+        let prose = prose(text);
+        let letters: Vec<char> = prose.chars().filter(|c| c.is_alphabetic()).collect();
+        if letters.is_empty() {
+            return true;
+        }
+        let hits = letters.iter().filter(|&&c| script.contains(c)).count();
+        // Ideographs pack a word into a character or two, against whole
+        // English identifiers; a modest share already means the prose is
+        // in the language. Latin targets need most letters to be Latin.
+        let needed = if script == Script::Latin { 0.8 } else { 0.25 };
+        hits as f64 / letters.len() as f64 >= needed
+    }
+}
+
+/// Point out on stderr when `text` isn't in the requested `language`, so
+/// the user can regenerate or edit before it lands.
+pub fn warn_if_mismatched(language: Option<&Language>, text: &str) {
+    if let Some(language) = language.filter(|l| !l.matches(text)) {
+        eprintln!(
+            "{}",
+            style::dim(&format!("⚠ This doesn't look like {}.", language.name))
+        );
+    }
+}
+
+/// The parts of a message written in natural language.
+fn prose(text: &str) -> String {
+    let mut out = String::new();
+    let mut fenced = false;
+    for (idx, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            fenced = !fenced;
+            continue;
+        }
+        // A `feat: …` subject looks like a trailer; only later lines can be.
+        if fenced || (idx > 0 && trailer::is_trailer(trimmed)) {
+            continue;
+        }
+        let line = if idx == 0 {
+            strip_type(trimmed)
+        } else {
+            trimmed
+        };
+        // Drop `inline code`: every other backtick-delimited segment.
+        for (n, part) in line.split('`').enumerate() {
+            if n % 2 == 0 {
+                out.push_str(part);
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// `feat(api)!: 添加` → `添加`.
+fn strip_type(subject: &str) -> &str {
+    match subject.split_once(": ") {
+        Some((prefix, rest))
+            if !prefix.is_empty()
+                && prefix
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "()!-_/.".contains(c)) =>
+        {
+            rest
+        }
+        _ => subject,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_codes_and_names() {
+        assert_eq!(Language::parse("zh-CN").name, "Simplified Chinese");
+        assert_eq!(Language::parse("中文").name, "Simplified Chinese");
+        assert_eq!(Language::parse("zh_TW").name, "Traditional Chinese");
+        assert_eq!(Language::parse("ja_JP").name, "Japanese");
+        assert_eq!(Language::parse("Klingon").script, None);
+        assert_eq!(
+            Language::choose(Some("en"), Some("zh")).map(|l| l.name),
+            Some("English".to_string())
+        );
+        assert_eq!(Language::choose(None, Some(" ")), None);
+    }
+
+    #[test]
+    fn checks_the_script_of_the_prose() {
+        let chinese = Language::parse("zh");
+        let message = "feat(api): 添加用户认证的超时处理\n\n\
+                       - 在 `AuthClient::login` 中处理超时\n\n\
+                       Refs PROJ-12\nSigned-off-by: A <a@example.com>";
+        assert!(chinese.matches(message));
+        assert!(!chinese.matches("feat(api): add timeout handling to user authentication"));
+        assert!(Language::parse("en").matches("fix: handle `超时` in login"));
+        assert!(!Language::parse("en").matches("修复登录超时问题"));
+        assert!(Language::parse("Klingon").matches("anything"));
+    }
+
+    #[test]
+    fn strips_conventional_prefixes_only() {
+        assert_eq!(strip_type("feat(api)!: 添加"), "添加");
+        assert_eq!(strip_type("注意: 这个"), "注意: 这个");
+    }
+}
@@ -1,19 +1,48 @@
 use std::process::ExitCode;
 
 pub mod ai;
+pub mod auth;
+mod cache;
+pub mod ci;
 mod config;
+mod convention;
+pub mod doctor;
 pub mod error;
 mod github;
+pub mod history;
+pub mod hook;
+mod http;
 pub mod issue;
+mod language;
 mod llm;
+pub mod oauth;
 pub mod open;
 pub mod pr;
+pub mod profile;
+pub mod prompt;
+pub mod release;
 mod remote;
 mod repo;
+pub mod reword;
+pub mod settings;
 mod style;
+mod ticket;
+mod trailer;
+pub mod usage;
 
 pub use error::{Error, Result};
 
+/// What a command prints on stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    /// Progress, styled results and prompts, for a person at a terminal.
+    Normal,
+    /// Only the final result (the message, the PR URL), for scripts.
+    Quiet,
+    /// Only the final result, as one JSON object.
+    Json,
+}
+
 /// Print an error as `error: …` (cargo/ripgrep style) and map it to a failure
 /// exit code. Every binary funnels its top-level result through this.
 pub fn report(result: Result<()>) -> ExitCode {
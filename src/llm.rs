@@ -1,11 +1,54 @@
+use crate::cache::{self, Cache};
+use crate::config::{AppConfig, Display, HttpConfig, DEFAULT_DEEPSEEK_URL};
 use crate::error::{Error, Result};
+use crate::http;
 use futures::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
 use std::time::Duration;
 
+/// Where and how to reach the model provider.
+pub struct Api<'a> {
+    pub key: &'a str,
+    /// API root, without the `/chat/completions` path.
+    pub base_url: &'a str,
+    pub http: &'a HttpConfig,
+}
+
+impl<'a> Api<'a> {
+    pub fn new(config: &'a AppConfig) -> Self {
+        Api {
+            key: &config.deepseek.api_key,
+            base_url: config
+                .deepseek
+                .base_url
+                .as_deref()
+                .unwrap_or(DEFAULT_DEEPSEEK_URL),
+            http: &config.http,
+        }
+    }
+
+    fn completions_url(&self) -> String {
+        format!("{}/chat/completions", self.base_url.trim_end_matches('/'))
+    }
+
+    /// The provider's host, which errors name so a non-DeepSeek
+    /// `base_url` isn't blamed on DeepSeek.
+    fn host(&self) -> String {
+        crate::auth::host_of(self.base_url)
+    }
+
+    fn unreachable(&self, source: reqwest::Error) -> Error {
+        Error::ApiUnreachable {
+            host: self.host(),
+            source,
+        }
+    }
+}
+
 /// One message in a chat completion request.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ChatMessage {
     pub role: String,
     pub content: String,
@@ -33,11 +76,44 @@ struct ChatRequest {
     messages: Vec<ChatMessage>,
     stream: bool,
     temperature: Option<f32>,
+    stream_options: StreamOptions,
+}
+
+/// Asks for a final chunk carrying the request's token usage.
+#[derive(Debug, Serialize)]
+struct StreamOptions {
+    include_usage: bool,
 }
 
 #[derive(Debug, Deserialize)]
 struct StreamResponseChunk {
+    #[serde(default)]
     choices: Vec<StreamChoice>,
+    usage: Option<Usage>,
+}
+
+/// Tokens billed for one request.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Prompt tokens served from the provider's context cache, billed at a
+    /// lower rate. Included in `prompt_tokens`.
+    #[serde(default, alias = "prompt_cache_hit_tokens")]
+    pub cached_tokens: u64,
+}
+
+/// Usage of every request this process has completed, with its model, until
+/// collected by [`take_usage`]. A command may make several requests
+/// (regenerating, `--candidates`, one per reworded commit).
+static SESSION_USAGE: Mutex<Vec<(String, Usage)>> = Mutex::new(Vec::new());
+
+/// Take the usage recorded so far, leaving the tally empty.
+pub fn take_usage() -> Vec<(String, Usage)> {
+    SESSION_USAGE
+        .lock()
+        .map(|mut tally| std::mem::take(&mut *tally))
+        .unwrap_or_default()
 }
 
 #[derive(Debug, Deserialize)]
@@ -50,81 +126,298 @@ struct DeltaMessage {
     content: Option<String>,
 }
 
+/// Progress reported while a completion streams in.
+pub enum Delta<'a> {
+    Text(&'a str),
+    /// The stream broke off and is being requested again; text received so
+    /// far is void.
+    Restart,
+}
+
 /// Stream a chat completion live and return the full collected message.
 ///
 /// Collect a chat completion and return the full message.
 ///
-/// A spinner animates `title` on the header line while the model generates;
-/// once the response is complete, that same line transforms in place — the
-/// label retracts and the generated message is typed out over it (see
-/// [`crate::style::reveal`]). Runs the async request on a local runtime so
-/// callers stay synchronous.
+/// With [`Display::Reveal`], a spinner animates `title` on the header line
+/// while the model generates; once the response is complete, that same line
+/// transforms in place — the label retracts and the generated message is
+/// typed out over it (see [`crate::style::reveal`]). [`Display::Stream`]
+/// prints the text as it arrives instead ([`crate::style::Live`]), and
+/// [`Display::Quiet`] prints nothing. Runs the async request on a local
+/// runtime so callers stay synchronous.
+///
+/// With a `cache`, an identical earlier request is answered from it (and
+/// revealed straight away), and a new non-empty completion is stored.
 pub fn stream_and_collect(
     title: &str,
-    api_key: &str,
+    api: &Api<'_>,
     model: &str,
     messages: Vec<ChatMessage>,
     temperature: Option<f32>,
+    cache: Option<&Cache>,
+    display: Display,
 ) -> Result<String> {
+    let key = cache::key(api.base_url, model, temperature, &messages);
+    if let Some(hit) = cache.and_then(|c| c.get(&key)) {
+        if display != Display::Quiet {
+            crate::style::reveal(&format!("{title} (cached)"), &hit);
+        }
+        return Ok(hit);
+    }
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    let full_message = match display {
+        Display::Quiet => rt.block_on(stream_chat(api, model, messages, temperature, |_| {}))?,
+        // Live output needs a terminal to rewrite; piped output gets the
+        // finished message like `reveal` gives it.
+        Display::Stream if crate::style::enabled() => {
+            let mut live = crate::style::Live::start(title);
+            let result = rt.block_on(stream_chat(
+                api,
+                model,
+                messages,
+                temperature,
+                |d| match d {
+                    Delta::Text(text) => live.push(text),
+                    Delta::Restart => live.restart(),
+                },
+            ));
+            live.finish();
+            result?
+        }
+        _ => {
+            let spinner = crate::style::Spinner::start(title);
+            let result = rt.block_on(stream_chat(api, model, messages, temperature, |_| {}));
+            // Stop the spinner before anything else so the terminal is never
+            // left mid-animation, whether the stream succeeded or errored.
+            spinner.stop();
+            let full_message = result?;
+            if full_message.trim().is_empty() {
+                // Nothing to reveal; clear the spinner's line so the error
+                // reads clean.
+                crate::style::clear_line();
+            } else {
+                crate::style::reveal(title, &full_message);
+            }
+            full_message
+        }
+    };
+
+    if let Some(cache) = cache.filter(|_| !full_message.trim().is_empty()) {
+        cache.put(&key, &full_message);
+    }
+    Ok(full_message)
+}
+
+/// Request `n` independent completions of the same conversation in parallel,
+/// behind a single spinner, and return those that succeeded (in request
+/// order). Only when every request fails is the first error returned. Nothing
+/// is revealed; callers present the alternatives themselves.
+pub fn collect_many(
+    title: &str,
+    api: &Api<'_>,
+    model: &str,
+    messages: Vec<ChatMessage>,
+    temperature: Option<f32>,
+    n: usize,
+) -> Result<Vec<String>> {
     let rt = tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()?;
-    let mut full_message = String::new();
 
     let spinner = crate::style::Spinner::start(title);
-    let stream_result = rt.block_on(async {
-        stream_chat(api_key, model, messages, temperature, |content| {
-            full_message.push_str(&content);
-        })
-        .await
-    });
-    // Stop the spinner before anything else so the terminal is never left
-    // mid-animation, whether the stream succeeded or errored.
+    let results =
+        rt.block_on(futures::future::join_all((0..n).map(|_| {
+            stream_chat(api, model, messages.clone(), temperature, |_| {})
+        })));
     spinner.stop();
-    stream_result?;
+    crate::style::clear_line();
 
-    if full_message.trim().is_empty() {
-        // Nothing to reveal; clear the spinner's line so the error reads clean.
-        crate::style::clear_line();
-    } else {
-        crate::style::reveal(title, &full_message);
+    let mut candidates = Vec::new();
+    let mut first_error = None;
+    for result in results {
+        match result {
+            Ok(message) => candidates.push(message),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match first_error {
+        Some(e) if candidates.is_empty() => Err(e),
+        _ => Ok(candidates),
+    }
+}
+
+/// Collect a chat completion silently — no spinner, no reveal — for contexts
+/// where the terminal isn't ours, such as a git hook. Gives up with
+/// [`Error::Timeout`] once `timeout` elapses. Uses `cache` like
+/// [`stream_and_collect`].
+pub fn collect(
+    api: &Api<'_>,
+    model: &str,
+    messages: Vec<ChatMessage>,
+    temperature: Option<f32>,
+    timeout: Duration,
+    cache: Option<&Cache>,
+) -> Result<String> {
+    let key = cache::key(api.base_url, model, temperature, &messages);
+    if let Some(hit) = cache.and_then(|c| c.get(&key)) {
+        return Ok(hit);
     }
 
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    // The timer must be created inside the runtime, so in an async block.
+    let result = rt.block_on(async {
+        tokio::time::timeout(
+            timeout,
+            stream_chat(api, model, messages, temperature, |_| {}),
+        )
+        .await
+    });
+    let full_message = match result {
+        Ok(streamed) => streamed?,
+        Err(_) => return Err(Error::Timeout(timeout.as_secs())),
+    };
+    if let Some(cache) = cache.filter(|_| !full_message.trim().is_empty()) {
+        cache.put(&key, &full_message);
+    }
     Ok(full_message)
 }
 
+/// Request a completion, retrying transient failures — rate limits, an
+/// overloaded or erroring server, network trouble, a stream cut off before
+/// `[DONE]` — up to [`MAX_ATTEMPTS`] times with exponential backoff (or the
+/// server's `Retry-After`). A stream that breaks mid-way is restarted from
+/// scratch; completions can't be resumed. Everything else fails at once with
+/// a classified error. `on_delta` sees the text as it arrives.
 async fn stream_chat(
-    api_key: &str,
+    api: &Api<'_>,
     model: &str,
     messages: Vec<ChatMessage>,
     temperature: Option<f32>,
-    mut callback: impl FnMut(String),
-) -> Result<()> {
-    // `connect_timeout` bounds reaching the API; `read_timeout` is an idle
-    // timeout between stream reads, so a long-but-active stream is not cut off
-    // while a stalled connection still fails fast.
-    let client = Client::builder()
-        .connect_timeout(Duration::from_secs(10))
-        .read_timeout(Duration::from_secs(60))
-        .build()?;
+    mut on_delta: impl FnMut(Delta),
+) -> Result<String> {
+    let client = http::client(api.http)?;
     let request_body = ChatRequest {
         model: model.to_string(),
         messages,
         stream: true,
         temperature,
+        stream_options: StreamOptions {
+            include_usage: true,
+        },
     };
 
+    let mut attempt = 1;
+    loop {
+        match attempt_chat(&client, api, &request_body, &mut on_delta).await {
+            Ok((message, usage)) => {
+                if let (Some(usage), Ok(mut tally)) = (usage, SESSION_USAGE.lock()) {
+                    tally.push((model.to_string(), usage));
+                }
+                return Ok(message);
+            }
+            Err(failure) if is_transient(&failure.error) && attempt < MAX_ATTEMPTS => {
+                on_delta(Delta::Restart);
+                let delay = failure.retry_after.unwrap_or_else(|| backoff(attempt));
+                announce_retry(&failure.error, delay);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(failure) => return Err(failure.error),
+        }
+    }
+}
+
+/// Attempts per request, the first included.
+const MAX_ATTEMPTS: u32 = 4;
+/// The first retry's delay; each later one doubles it, up to [`MAX_DELAY`].
+const BASE_DELAY: Duration = Duration::from_secs(1);
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// A failed attempt, with the wait the server asked for, if any.
+struct Failure {
+    error: Error,
+    retry_after: Option<Duration>,
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        Failure {
+            error,
+            retry_after: None,
+        }
+    }
+}
+
+/// The ids of the models the API offers. A cheap authenticated request
+/// (no tokens are spent), used to check the key and connection.
+pub fn list_models(api: &Api<'_>) -> Result<Vec<String>> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    rt.block_on(async {
+        let url = format!("{}/models", api.base_url.trim_end_matches('/'));
+        let response = http::client(api.http)?
+            .get(url)
+            .header("Authorization", format!("Bearer {}", api.key))
+            .send()
+            .await
+            .map_err(|e| api.unreachable(e))?;
+        let status = response.status();
+        let body = response.text().await.map_err(|e| api.unreachable(e))?;
+        if !status.is_success() {
+            return Err(classify(&api.host(), status.as_u16(), &body));
+        }
+        let models: serde_json::Value =
+            serde_json::from_str(&body).map_err(|_| Error::ApiError {
+                host: api.host(),
+                message: format!("unexpected /models response: {body}"),
+            })?;
+        Ok(models["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|model| model["id"].as_str().map(str::to_string))
+            .collect())
+    })
+}
+
+/// One request: send it and collect the streamed content until `[DONE]`,
+/// along with the usage reported at the end (if the provider sent any).
+async fn attempt_chat(
+    client: &Client,
+    api: &Api<'_>,
+    request_body: &ChatRequest,
+    on_delta: &mut impl FnMut(Delta),
+) -> std::result::Result<(String, Option<Usage>), Failure> {
     let response = client
-        .post("https://api.deepseek.com/chat/completions")
+        .post(api.completions_url())
         .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .json(&request_body)
+        .header("Authorization", format!("Bearer {}", api.key))
+        .json(request_body)
         .send()
-        .await?;
+        .await
+        .map_err(|e| api.unreachable(e))?;
 
-    if !response.status().is_success() {
-        let err_msg = response.text().await?;
-        return Err(Error::ApiError(err_msg));
+    let status = response.status();
+    if !status.is_success() {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_retry_after);
+        let body = response.text().await.unwrap_or_default();
+        return Err(Failure {
+            error: classify(&api.host(), status.as_u16(), &body),
+            retry_after,
+        });
     }
 
     // SSE events are newline-delimited, but `bytes_stream` yields arbitrary
@@ -132,18 +425,126 @@ async fn stream_chat(
     // two chunks. Buffer bytes and only parse whole lines so nothing is lost.
     let mut stream = response.bytes_stream();
     let mut buffer: Vec<u8> = Vec::new();
+    let mut full_message = String::new();
+    let mut done = false;
+    let mut usage = None;
     while let Some(chunk) = stream.next().await {
-        buffer.extend_from_slice(&chunk?);
-        drain_sse_lines(&mut buffer, &mut callback);
+        buffer.extend_from_slice(&chunk.map_err(|e| api.unreachable(e))?);
+        let mut push = |content: String| {
+            on_delta(Delta::Text(&content));
+            full_message.push_str(&content);
+        };
+        done |= drain_sse_lines(&mut buffer, &mut push, &mut usage);
+    }
+    if !done {
+        return Err(Error::StreamInterrupted(api.host()).into());
+    }
+    Ok((full_message, usage))
+}
+
+/// Whether an error may go away by itself, making a retry worthwhile.
+fn is_transient(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::RateLimited(_)
+            | Error::ApiOverloaded { .. }
+            | Error::ApiUnreachable { .. }
+            | Error::StreamInterrupted(_)
+    )
+}
+
+/// Map an error response to the error class it represents. DeepSeek reports
+/// an over-long prompt as a plain 400, recognisable only by its message.
+fn classify(host: &str, status: u16, body: &str) -> Error {
+    let message = api_message(body);
+    let host = host.to_string();
+    match status {
+        401 | 403 => Error::ApiAuth(host),
+        402 => Error::ApiQuota(host),
+        429 => Error::RateLimited(host),
+        500 | 502 | 503 | 504 => Error::ApiOverloaded { host, status },
+        400 | 413 | 422 if mentions_context_length(&message) => Error::ContextTooLong,
+        _ => Error::ApiError {
+            host,
+            message: format!("{status}: {message}"),
+        },
     }
+}
+
+/// The human-readable part of an error body (`{"error": {"message": …}}`),
+/// else the body itself.
+fn api_message(body: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v["error"]["message"].as_str().map(str::to_string))
+        .unwrap_or_else(|| body.trim().to_string())
+}
+
+fn mentions_context_length(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "context length",
+        "context_length",
+        "maximum context",
+        "too many tokens",
+    ]
+    .iter()
+    .any(|phrase| message.contains(phrase))
+}
 
-    Ok(())
+/// `Retry-After` in seconds (the HTTP-date form is not worth a date parser),
+/// capped so a misbehaving server can't stall us for long.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let secs: u64 = value.trim().parse().ok()?;
+    Some(Duration::from_secs(secs).min(MAX_DELAY * 2))
+}
+
+/// The wait before retry number `attempt` (1-based): doubling from
+/// [`BASE_DELAY`], capped at [`MAX_DELAY`], plus up to a quarter second of
+/// jitter so parallel requests (`--candidates`) don't retry in lockstep.
+fn backoff(attempt: u32) -> Duration {
+    let delay = BASE_DELAY
+        .saturating_mul(1 << (attempt - 1).min(16))
+        .min(MAX_DELAY);
+    let jitter = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_millis() % 250)
+        .unwrap_or_default();
+    delay + Duration::from_millis(jitter.into())
+}
+
+/// Tell the user why there is a pause, on stderr so captured output stays
+/// clean. Clears the spinner's line first; the spinner resumes below.
+fn announce_retry(error: &Error, delay: Duration) {
+    let clear = if crate::style::enabled() {
+        "\r\x1b[2K"
+    } else {
+        ""
+    };
+    let reason = match error {
+        Error::RateLimited(_) => "rate limited".to_string(),
+        Error::ApiOverloaded { status, .. } => format!("the API is unavailable ({status})"),
+        _ => "the connection failed".to_string(),
+    };
+    eprintln!(
+        "{clear}{}",
+        crate::style::dim(&format!(
+            "↻ {reason}; retrying in {}s",
+            delay.as_secs().max(1)
+        ))
+    );
 }
 
 /// Parse every complete (newline-terminated) SSE line in `buffer`, invoking
 /// `callback` for each content delta. Any trailing partial line is left in
-/// `buffer` for the next chunk.
-fn drain_sse_lines(buffer: &mut Vec<u8>, callback: &mut impl FnMut(String)) {
+/// `buffer` for the next chunk. A usage report is stored in `usage`. Returns
+/// whether the `[DONE]` marker was seen.
+fn drain_sse_lines(
+    buffer: &mut Vec<u8>,
+    callback: &mut impl FnMut(String),
+    usage: &mut Option<Usage>,
+) -> bool {
+    let mut done = false;
     while let Some(newline) = buffer.iter().position(|&b| b == b'\n') {
         let line: Vec<u8> = buffer.drain(..=newline).collect();
         let line = String::from_utf8_lossy(&line);
@@ -152,9 +553,13 @@ fn drain_sse_lines(buffer: &mut Vec<u8>, callback: &mut impl FnMut(String)) {
         if let Some(data) = line.strip_prefix("data:") {
             let data = data.trim();
             if data == "[DONE]" {
+                done = true;
                 continue;
             }
             if let Ok(parsed) = serde_json::from_str::<StreamResponseChunk>(data) {
+                if parsed.usage.is_some() {
+                    *usage = parsed.usage;
+                }
                 for choice in parsed.choices {
                     if let Some(content) = choice.delta.content {
                         callback(content);
@@ -163,11 +568,12 @@ fn drain_sse_lines(buffer: &mut Vec<u8>, callback: &mut impl FnMut(String)) {
             }
         }
     }
+    done
 }
 
 #[cfg(test)]
 mod test {
-    use super::drain_sse_lines;
+    use super::*;
 
     /// Feed an SSE stream one byte at a time and confirm every content delta is
     /// recovered — i.e. lines and multibyte chars split across chunks are not
@@ -178,7 +584,7 @@ mod test {
         let mut push = |c: String| out.push_str(&c);
         for &byte in stream {
             buffer.push(byte);
-            drain_sse_lines(&mut buffer, &mut push);
+            drain_sse_lines(&mut buffer, &mut push, &mut None);
         }
         (out, buffer)
     }
@@ -197,6 +603,83 @@ mod test {
         assert!(buffer.is_empty());
     }
 
+    #[test]
+    fn picks_up_the_usage_chunk() {
+        let mut buffer = concat!(
+            "data: {\"choices\":[{\"delta\":{\"content\":\"ok\"}}]}\n",
+            "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":120,\"completion_tokens\":8,",
+            "\"total_tokens\":128,\"prompt_cache_hit_tokens\":100}}\n",
+            "data: [DONE]\n",
+        )
+        .as_bytes()
+        .to_vec();
+        let mut usage = None;
+        assert!(drain_sse_lines(&mut buffer, &mut |_| {}, &mut usage));
+        assert_eq!(
+            usage,
+            Some(Usage {
+                prompt_tokens: 120,
+                completion_tokens: 8,
+                cached_tokens: 100,
+            })
+        );
+    }
+
+    #[test]
+    fn classifies_error_responses() {
+        let host = "api.deepseek.com";
+        assert!(matches!(classify(host, 401, ""), Error::ApiAuth(_)));
+        assert!(matches!(
+            classify(host, 402, "Insufficient Balance"),
+            Error::ApiQuota(_)
+        ));
+        assert!(matches!(classify(host, 429, ""), Error::RateLimited(_)));
+        assert!(matches!(
+            classify(host, 503, ""),
+            Error::ApiOverloaded { status: 503, .. }
+        ));
+        let too_long =
+            r#"{"error":{"message":"This model's maximum context length is 65536 tokens."}}"#;
+        assert!(matches!(
+            classify(host, 400, too_long),
+            Error::ContextTooLong
+        ));
+        let invalid = r#"{"error":{"message":"Model Not Exist"}}"#;
+        assert!(matches!(
+            classify(host, 400, invalid),
+            Error::ApiError { message, .. } if message == "400: Model Not Exist"
+        ));
+
+        assert!(is_transient(&Error::RateLimited(host.to_string())));
+        assert!(is_transient(&Error::StreamInterrupted(host.to_string())));
+        assert!(!is_transient(&Error::ApiAuth(host.to_string())));
+        assert!(!is_transient(&Error::ContextTooLong));
+    }
+
+    #[test]
+    fn errors_name_the_configured_host() {
+        let http = HttpConfig::default();
+        let api = Api {
+            key: "k",
+            base_url: "https://api.openai.com/v1",
+            http: &http,
+        };
+        let message = classify(&api.host(), 401, "").to_string();
+        assert!(message.starts_with("api.openai.com rejected"), "{message}");
+        let message = classify(&api.host(), 402, "").to_string();
+        assert!(!message.contains("DeepSeek"), "{message}");
+    }
+
+    #[test]
+    fn backs_off_exponentially_with_a_cap() {
+        let secs = |attempt| backoff(attempt).as_secs();
+        assert_eq!((secs(1), secs(2), secs(3)), (1, 2, 4));
+        assert_eq!(secs(10), 30);
+        assert_eq!(parse_retry_after(" 7 "), Some(Duration::from_secs(7)));
+        assert_eq!(parse_retry_after("3600"), Some(Duration::from_secs(60)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+
     #[test]
     fn keeps_trailing_partial_line_buffered() {
         let mut buffer = Vec::new();
@@ -207,7 +690,8 @@ mod test {
         buffer.extend_from_slice(
             b"data: {\"choices\":[{\"delta\":{\"content\":\"hi\"}}]}\ndata: {\"choi",
         );
-        drain_sse_lines(&mut buffer, &mut push);
+        // No `[DONE]` yet: the stream is still open.
+        assert!(!drain_sse_lines(&mut buffer, &mut push, &mut None));
 
         assert_eq!(out, "hi");
         assert_eq!(buffer, b"data: {\"choi");
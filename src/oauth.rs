@@ -0,0 +1,420 @@
+//! `git-github auth login --github`: sign in to GitHub through the OAuth
+//! device flow, so no `gh` install or hand-made token is needed, and keep
+//! the token per host. The token goes in the keyring when one is running;
+//! `hosts.toml` next to the user config records the account (and holds the
+//! token itself, readable only by the user, when there is no keyring).
+
+use crate::auth::{self, Secret, Source};
+use crate::config::{config_dir, load_config};
+use crate::error::{Error, Result};
+use crate::github::{self, GITHUB_API};
+use crate::http;
+use crate::style;
+use serde::Deserialize;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// What the token may do: `repo` covers opening pull requests on private
+/// repositories too.
+const SCOPES: &str = "repo";
+const GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+/// The client ID of git-github's own OAuth app on github.com, baked in at
+/// build time from `GIT_GITHUB_OAUTH_CLIENT_ID`; `github.oauth_client_id`
+/// overrides it, and is needed for GitHub Enterprise Server.
+const CLIENT_ID: Option<&str> = option_env!("GIT_GITHUB_OAUTH_CLIENT_ID");
+
+/// `$XDG_CONFIG_HOME/git-github/hosts.toml`: `["<host>"]` tables with the
+/// signed-in `user`, and `oauth_token` when it is not in the keyring.
+fn hosts_path() -> Result<PathBuf> {
+    Ok(config_dir().ok_or(Error::NoHomeDir)?.join("hosts.toml"))
+}
+
+fn read_hosts(path: &Path) -> Result<toml::Table> {
+    match fs::read_to_string(path) {
+        Ok(text) => text
+            .parse()
+            .map_err(|e: toml::de::Error| Error::InvalidConfig {
+                origin: path.display().to_string(),
+                key: None,
+                reason: e.message().to_string(),
+            }),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(toml::Table::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write the hosts file, readable only by the user since it may hold
+/// tokens.
+fn write_hosts(path: &Path, hosts: &toml::Table) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let text = toml::to_string(hosts).expect("a table serializes");
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        file.write_all(text.as_bytes())?;
+    }
+    #[cfg(not(unix))]
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// The token `auth login --github` stored for `host`, unless `user` names a
+/// different account than the one signed in.
+pub(crate) fn stored_token(host: &str, user: Option<&str>) -> Option<Secret> {
+    let path = hosts_path().ok()?;
+    let hosts = read_hosts(&path).ok()?;
+    let entry = hosts.get(host)?.as_table()?;
+    let login = entry
+        .get("user")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    if user.is_some_and(|user| !user.eq_ignore_ascii_case(login)) {
+        return None;
+    }
+    if let Some(token) = entry.get("oauth_token").and_then(|v| v.as_str()) {
+        return Some(Secret {
+            value: token.to_string(),
+            source: Source::HostsFile(path),
+        });
+    }
+    let value = auth::secret_tool_lookup(&[("host", host), ("user", login)])?;
+    Some(Secret {
+        value,
+        source: Source::SecretService,
+    })
+}
+
+/// The web and API roots for logging in to `hostname`, or to the host of
+/// `[github] api_url` (`https://github.com` by default). GitHub Enterprise
+/// serves its API under `/api/v3` or on an `api.` subdomain.
+fn endpoints(api_url: Option<&str>, hostname: Option<&str>) -> (String, String) {
+    let configured = match api_url.map(|api| api.trim_end_matches('/')) {
+        None => ("https://github.com".to_string(), GITHUB_API.to_string()),
+        Some(api) => {
+            let (scheme, rest) = api.split_once("://").unwrap_or(("https", api));
+            let host = auth::host_of(rest);
+            let host = host.strip_prefix("api.").unwrap_or(&host);
+            (format!("{scheme}://{host}"), api.to_string())
+        }
+    };
+    match hostname {
+        Some(host) if !configured.0.ends_with(&format!("://{host}")) => match host {
+            "github.com" => ("https://github.com".to_string(), GITHUB_API.to_string()),
+            host => (format!("https://{host}"), format!("https://{host}/api/v3")),
+        },
+        _ => configured,
+    }
+}
+
+/// The OAuth app to sign in with at `web`: the configured one, else on
+/// github.com the `default` one this build ships.
+fn client_id(configured: Option<&str>, web: &str, default: Option<&str>) -> Result<String> {
+    match configured.map(str::trim).filter(|id| !id.is_empty()) {
+        Some(id) => Ok(id.to_string()),
+        None if web == "https://github.com" => default
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .ok_or(Error::NoOAuthApp),
+        None => Err(Error::NoOAuthApp),
+    }
+}
+
+#[derive(Deserialize)]
+struct DeviceCode {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    expires_in: u64,
+    #[serde(default = "default_interval")]
+    interval: u64,
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+/// GitHub's answers while polling for the token.
+#[derive(Deserialize)]
+struct TokenReply {
+    access_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+    interval: Option<u64>,
+}
+
+/// `auth login --github`: show a one-time code, wait for the user to enter
+/// it at GitHub, then store the token for the host.
+pub fn login(hostname: Option<String>) -> Result<()> {
+    let config = load_config()?;
+    let host = hostname
+        .clone()
+        .unwrap_or_else(|| github::web_host(&config));
+    let (web, api) = endpoints(config.github.api_url.as_deref(), hostname.as_deref());
+    let client_id = client_id(config.github.oauth_client_id.as_deref(), &web, CLIENT_ID)?;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let (token, login, scopes) = rt.block_on(async {
+        let client = http::client(&config.http)?;
+        let token = device_flow(&client, &web, &client_id).await?;
+        let info = github_user(&client, &api, &token).await?;
+        Ok::<_, Error>((token, info.login, info.scopes))
+    })?;
+
+    let path = hosts_path()?;
+    let mut hosts = read_hosts(&path)?;
+    let mut entry = toml::Table::new();
+    entry.insert("user".into(), login.clone().into());
+    let stored_in = if auth::secret_service_available() {
+        let label = format!("git-github: GitHub token for {host}");
+        auth::secret_tool_store(&label, &[("host", &host), ("user", &login)], &token)?;
+        "the Secret Service keyring".to_string()
+    } else {
+        entry.insert("oauth_token".into(), token.into());
+        path.display().to_string()
+    };
+    hosts.insert(host.clone(), entry.into());
+    write_hosts(&path, &hosts)?;
+
+    style::success(&format!(
+        "Logged in to {host} as {login}; the token is in {stored_in}"
+    ));
+    if let Some(scopes) = scopes.filter(|scopes| !github::allows_pull_requests(scopes)) {
+        eprintln!(
+            "warning: {}",
+            Error::TokenScope {
+                action: "open pull requests",
+                scopes
+            }
+        );
+    }
+    if let Ok(Some(secret)) = github::find_token(&config) {
+        if !matches!(secret.source, Source::HostsFile(_) | Source::SecretService) {
+            println!(
+                "Note: {} still takes precedence over it.",
+                secret.source.describe()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Ask for a device code, have the user authorize it, and poll until
+/// GitHub hands out the token.
+async fn device_flow(client: &reqwest::Client, web: &str, client_id: &str) -> Result<String> {
+    let code: DeviceCode = post(
+        client,
+        &format!("{web}/login/device/code"),
+        serde_json::json!({ "client_id": client_id, "scope": SCOPES }),
+    )
+    .await?;
+
+    eprintln!(
+        "First copy your one-time code: {}",
+        style::bold(&code.user_code)
+    );
+    eprintln!("Then open {} and enter it.", code.verification_uri);
+    if io::stdout().is_terminal() {
+        let _ = open::that(&code.verification_uri);
+    }
+    eprintln!("{}", style::dim("Waiting for authorization…"));
+
+    let deadline = Instant::now() + Duration::from_secs(code.expires_in);
+    let mut interval = code.interval.max(1);
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        if Instant::now() >= deadline {
+            return Err(Error::DeviceFlow(
+                "the code expired; run the login again".into(),
+            ));
+        }
+        let reply: TokenReply = post(
+            client,
+            &format!("{web}/login/oauth/access_token"),
+            serde_json::json!({
+                "client_id": client_id,
+                "device_code": code.device_code,
+                "grant_type": GRANT_TYPE,
+            }),
+        )
+        .await?;
+        if let Some(token) = reply.access_token.filter(|token| !token.is_empty()) {
+            return Ok(token);
+        }
+        match reply.error.as_deref() {
+            Some("authorization_pending") => {}
+            // GitHub asks for a longer interval, and says which.
+            Some("slow_down") => interval = reply.interval.unwrap_or(interval + 5),
+            Some("expired_token") => {
+                return Err(Error::DeviceFlow(
+                    "the code expired; run the login again".into(),
+                ))
+            }
+            Some("access_denied") => {
+                return Err(Error::DeviceFlow("the authorization was declined".into()))
+            }
+            other => {
+                let reason = reply.error_description.or(other.map(str::to_string));
+                return Err(Error::DeviceFlow(
+                    reason.unwrap_or_else(|| "no token".into()),
+                ));
+            }
+        }
+    }
+}
+
+/// POST `body` as JSON and read the JSON reply. GitHub answers these
+/// endpoints with 200 even for errors, which come in the body.
+async fn post<T: for<'de> Deserialize<'de>>(
+    client: &reqwest::Client,
+    url: &str,
+    body: serde_json::Value,
+) -> Result<T> {
+    let response = client
+        .post(url)
+        .header("Accept", "application/json")
+        .json(&body)
+        .send()
+        .await?;
+    let status = response.status();
+    let text = response.text().await?;
+    if !status.is_success() {
+        return Err(Error::DeviceFlow(format!("{url} returned {status}")));
+    }
+    serde_json::from_str(&text).map_err(|_| {
+        let reason = serde_json::from_str::<serde_json::Value>(&text)
+            .ok()
+            .and_then(|v| v["error_description"].as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("unexpected reply from {url}"));
+        Error::DeviceFlow(reason)
+    })
+}
+
+/// `GET /user` with the new token, for the account name and scopes.
+async fn github_user(
+    client: &reqwest::Client,
+    api: &str,
+    token: &str,
+) -> Result<github::TokenInfo> {
+    let response = client
+        .get(format!("{}/user", api.trim_end_matches('/')))
+        .header("Accept", "application/vnd.github+json")
+        .bearer_auth(token)
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::GitHubAuth(status.as_u16()));
+    }
+    let scopes = github::parse_scopes(response.headers().get("x-oauth-scopes"));
+    let user: serde_json::Value = response.json().await?;
+    Ok(github::TokenInfo {
+        login: user["login"].as_str().unwrap_or("?").to_string(),
+        scopes,
+    })
+}
+
+/// `auth logout --github`: forget the token stored for the host.
+pub fn logout(hostname: Option<String>) -> Result<()> {
+    let config = load_config()?;
+    let host = hostname.unwrap_or_else(|| github::web_host(&config));
+    let path = hosts_path()?;
+    let mut hosts = read_hosts(&path)?;
+    let Some(entry) = hosts.remove(&host) else {
+        println!("Not logged in to {host} with `git-github auth login --github`.");
+        return Ok(());
+    };
+    let login = entry
+        .get("user")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    if entry.get("oauth_token").is_none() {
+        let _ = auth::secret_tool(&["clear"], &[("host", &host), ("user", login)]).status();
+    }
+    write_hosts(&path, &hosts)?;
+    style::success(&format!("Logged out of {host} ({login})"));
+    if let Some(secret) = github::find_token(&config)? {
+        println!("A token is still provided by {}.", secret.source.describe());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn client_id_defaults_to_the_shipped_app_on_github_com() {
+        let github = "https://github.com";
+        assert_eq!(
+            client_id(None, github, Some("Ov23-shipped")).unwrap(),
+            "Ov23-shipped"
+        );
+        assert_eq!(
+            client_id(Some(" own "), github, Some("Ov23-shipped")).unwrap(),
+            "own"
+        );
+        assert!(matches!(
+            client_id(None, "https://ghe.corp.com", Some("Ov23-shipped")),
+            Err(Error::NoOAuthApp)
+        ));
+        assert_eq!(
+            client_id(Some("own"), "https://ghe.corp.com", None).unwrap(),
+            "own"
+        );
+        assert!(matches!(
+            client_id(Some(""), github, None),
+            Err(Error::NoOAuthApp)
+        ));
+    }
+
+    #[test]
+    fn endpoints_follow_the_api_url_or_hostname() {
+        let github = ("https://github.com".to_string(), GITHUB_API.to_string());
+        assert_eq!(endpoints(None, None), github);
+        assert_eq!(
+            endpoints(Some("https://ghe.corp.com/api/v3"), Some("github.com")),
+            github
+        );
+        assert_eq!(
+            endpoints(Some("https://ghe.corp.com/api/v3/"), None),
+            (
+                "https://ghe.corp.com".into(),
+                "https://ghe.corp.com/api/v3".into()
+            )
+        );
+        assert_eq!(
+            endpoints(Some("https://api.ghe.corp.com"), None),
+            (
+                "https://ghe.corp.com".into(),
+                "https://api.ghe.corp.com".into()
+            )
+        );
+        assert_eq!(
+            endpoints(Some("https://ghe.corp.com/api/v3"), Some("ghe.corp.com")),
+            (
+                "https://ghe.corp.com".into(),
+                "https://ghe.corp.com/api/v3".into()
+            )
+        );
+        assert_eq!(
+            endpoints(None, Some("ghe.corp.com")),
+            (
+                "https://ghe.corp.com".into(),
+                "https://ghe.corp.com/api/v3".into()
+            )
+        );
+    }
+}
@@ -1,15 +1,28 @@
-use crate::config::load_config;
+use crate::auth;
+use crate::cache::Cache;
+use crate::ci;
+use crate::config::{load_config, AppConfig, Display};
 use crate::error::{Error, Result};
 use crate::github;
-use crate::llm::{self, ChatMessage};
+use crate::language::{self, Language};
+use crate::llm::{self, Api, ChatMessage};
+use crate::prompt::{Kind, Prompt};
 use crate::repo::Repo;
+use crate::style;
+use crate::ticket::{self, Reference};
+use crate::Output;
+use octocrab::models::IssueState;
+use serde::Deserialize;
 use std::env;
 use std::fs;
-use std::future::Future;
+use std::io::{self, IsTerminal};
 use std::process::{Command, Stdio};
+use std::time::Duration;
 
 /// Cap the diff sent to the model so large branches don't blow the context.
 const MAX_DIFF_BYTES: usize = 12 * 1024;
+/// Cap the linked issue's body included as context.
+const MAX_ISSUE_BYTES: usize = 2 * 1024;
 
 pub struct Options {
     pub remote: String,
@@ -17,34 +30,43 @@ pub struct Options {
     pub draft: bool,
     pub edit: bool,
     pub no_push: bool,
+    pub no_cache: bool,
+    pub output: Output,
+    /// Language to write the title and body in, overriding the config's.
+    pub lang: Option<String>,
 }
 
 /// Create a GitHub pull request for the current branch, with an AI-drafted
 /// title and body generated from the commits and diff against the base branch.
 pub fn create(opts: Options) -> Result<()> {
+    let show_usage = opts.output == Output::Normal;
+    let result = draft_and_create(opts);
+    crate::usage::settle("pr", show_usage);
+    result
+}
+
+fn draft_and_create(opts: Options) -> Result<()> {
     let path = env::current_dir().map_err(|_| Error::NoCurrentDir)?;
     let repo = Repo::new(&path)?;
     let remote = repo.remote(&opts.remote)?;
     let head = repo.current_branch()?;
 
-    let config = load_config()?;
-    if config.deepseek.api_key.is_empty() {
-        return Err(Error::NoApiKey);
-    }
+    let mut config = load_config()?;
+    auth::require_api_key(&mut config)?;
     // Creating a PR is a write; an anonymous client cannot do it.
-    if github::token().is_none() {
-        return Err(Error::NoGitHubToken);
+    if github::token(&config)?.is_none() {
+        return Err(Error::NoGitHubToken("creating a pull request"));
     }
+    // Check its scopes now rather than after drafting and pushing.
+    let target: octocrab::models::Repository = github::run(|client| {
+        let remote = &remote;
+        async move { github::writable_repo(&client, remote, "open pull requests").await }
+    })?;
 
     // Resolve the base branch: an explicit `--base`, else the repo's default.
     let base = match opts.base.clone() {
         Some(base) => base,
-        None => {
-            let (owner, name) = (remote.user.clone(), remote.repo.clone());
-            run_github(|client| async move { client.repos(owner, name).get().await })?
-                .default_branch
-                .ok_or(Error::NoDefaultBranch)?
-        }
+        None => target.default_branch.ok_or(Error::NoDefaultBranch)?,
     };
     if base == head {
         return Err(Error::NoCommitsForPr(base));
@@ -69,19 +91,89 @@ pub fn create(opts: Options) -> Result<()> {
     }
     let diff = truncate(&git_capture(&["diff", &format!("{}...HEAD", base_ref)])?);
 
+    // A GitHub issue named in the branch gives the model the intent behind the
+    // change. Fetching it is best-effort: a missing or private issue just
+    // means less context.
+    let mut ticket = ticket::from_branch(&head, &config.tickets)?;
+    let issue = match ticket.as_ref().map(|t| &t.reference) {
+        Some(Reference::Issue(number)) => {
+            let (owner, name, number) = (remote.user.clone(), remote.repo.clone(), *number);
+            github::run(move |client| async move { client.issues(owner, name).get(number).await })
+                .ok()
+        }
+        _ => None,
+    };
+    if let (Some(ticket), Some(issue)) = (ticket.as_mut(), issue.as_ref()) {
+        // Only an open issue (not a pull request) can be closed by this PR.
+        ticket.closes &= issue.state == IssueState::Open && issue.pull_request.is_none();
+    }
+    let issue_context = issue.map(|issue| {
+        format!(
+            "#{}: {}\n\n{}",
+            issue.number,
+            issue.title,
+            clip(
+                issue.body.as_deref().unwrap_or_default().trim(),
+                MAX_ISSUE_BYTES
+            )
+        )
+    });
+
+    let language = Language::choose(opts.lang.as_deref(), config.language.as_deref());
+    let prompt = Prompt::load(Kind::Pr, repo.workdir(), None)?;
+    let values = [
+        ("branch", head.clone()),
+        ("base", base.clone()),
+        (
+            "language",
+            language.as_ref().map_or("English", |l| &l.name).to_string(),
+        ),
+        (
+            "ticket",
+            ticket.as_ref().map(|t| t.display()).unwrap_or_default(),
+        ),
+    ];
     let model = config.deepseek.model.as_deref().unwrap_or("deepseek-chat");
+    let cache = if opts.no_cache {
+        None
+    } else {
+        Cache::open(&config.cache)
+    };
     let drafted = llm::stream_and_collect(
         "Drafting pull request",
-        &config.deepseek.api_key,
+        &Api::new(&config),
         model,
-        build_prompt(&commits, &diff),
+        build_prompt(
+            &prompt,
+            &values,
+            &commits,
+            &diff,
+            issue_context.as_deref(),
+            language.as_ref(),
+        ),
         config.deepseek.temperature,
+        cache.as_ref(),
+        if opts.output == Output::Normal {
+            config.ui.display
+        } else {
+            Display::Quiet
+        },
     )?;
 
     let (mut title, mut body) = split_title_body(&drafted);
     if title.is_empty() {
         return Err(Error::EmptyMessage);
     }
+    if opts.output == Output::Normal {
+        language::warn_if_mismatched(language.as_ref(), &drafted);
+    }
+    // Link the ticket (with a closing keyword when the branch fixes it) before
+    // any editor review, so the author sees and can adjust it.
+    if let Some(ticket) = &ticket {
+        body = ticket::append_reference(&body, ticket)
+            .trim_start()
+            .to_string();
+    }
 
     if opts.edit {
         let edited = edit_in_editor(&format!("{}\n\n{}", title, body))?;
@@ -96,14 +188,18 @@ pub fn create(opts: Options) -> Result<()> {
     // Publish the branch (now that we're committed to creating the PR) so the
     // head exists and is up to date on the remote.
     if !opts.no_push {
-        println!("Pushing {} to {}...", head, opts.remote);
-        git_run(&["push", "-u", &opts.remote, &head])?;
+        if opts.output == Output::Normal {
+            println!("Pushing {} to {}...", head, opts.remote);
+            git_run(&["push", "-u", &opts.remote, &head])?;
+        } else {
+            git_run(&["push", "-q", "-u", &opts.remote, &head])?;
+        }
     }
 
     let (owner, name, head_ref, base_ref) =
         (remote.user.clone(), remote.repo.clone(), head.clone(), base.clone());
     let draft = opts.draft;
-    let pull = run_github(move |client| async move {
+    let pull = github::run(move |client| async move {
         client
             .pulls(owner, name)
             .create(title, head_ref, base_ref)
@@ -113,30 +209,76 @@ pub fn create(opts: Options) -> Result<()> {
             .await
     })?;
 
-    crate::style::success("Pull request created");
-    match pull.html_url {
-        Some(url) => println!("{}", url),
-        None => println!("Created pull request into {}", base),
+    let url = pull.html_url.map(|url| url.to_string());
+    match opts.output {
+        Output::Normal => {
+            crate::style::success("Pull request created");
+            match url {
+                Some(url) => println!("{}", url),
+                None => println!("Created pull request into {}", base),
+            }
+        }
+        Output::Quiet => println!("{}", url.unwrap_or_default()),
+        Output::Json => println!(
+            "{}",
+            serde_json::json!({
+                "number": pull.number,
+                "url": url,
+                "title": pull.title,
+                "base": base,
+                "head": head,
+                "draft": draft,
+            })
+        ),
     }
 
     Ok(())
 }
 
-/// Build the prompt: commit subjects plus the (truncated) diff.
-fn build_prompt(commits: &str, diff: &str) -> Vec<ChatMessage> {
-    let system = r###"You are an assistant that writes GitHub pull request descriptions.
-
-Given the commit list and diff, produce:
-1. A concise PR title on the FIRST line (max 72 characters, no type prefix and no markdown heading).
-2. A blank line.
-3. A markdown body: a short summary paragraph, then a "## Changes" section with a bullet list.
+/// Build the prompt: the rendered template, then the commit subjects, the
+/// (truncated) diff and the linked issue when the branch names one — each
+/// unless the template places it itself.
+fn build_prompt(
+    prompt: &Prompt,
+    values: &[(&str, String)],
+    commits: &str,
+    diff: &str,
+    issue: Option<&str>,
+    language: Option<&Language>,
+) -> Vec<ChatMessage> {
+    let mut values = values.to_vec();
+    values.push(("commits", commits.to_string()));
+    values.push(("diff", diff.to_string()));
+    values.push(("issue", issue.unwrap_or_default().to_string()));
+    let system = prompt.render(&values);
 
-Be professional and concise. Output only the title and body, nothing else."###;
+    let mut context = String::new();
+    if let Some(issue) = issue.filter(|_| !prompt.uses("issue")) {
+        context.push_str(&format!(
+            "This branch addresses the following issue; use it to explain the motivation \
+             (the issue link is added separately, do not add one):\n{}\n\n",
+            issue
+        ));
+    }
+    if !prompt.uses("commits") {
+        context.push_str(&format!("Commits:\n{}\n\n", commits));
+    }
+    if !prompt.uses("diff") {
+        context.push_str(&format!("Diff:\n{}", diff));
+    }
 
-    vec![
-        ChatMessage::system(system),
-        ChatMessage::user(format!("Commits:\n{}\n\nDiff:\n{}", commits, diff)),
-    ]
+    let instruction = language.map(|l| ChatMessage::system(l.instruction()));
+    // A template that places everything itself is the whole request.
+    if context.trim().is_empty() {
+        return instruction
+            .into_iter()
+            .chain([ChatMessage::user(system)])
+            .collect();
+    }
+    let mut messages = vec![ChatMessage::system(system)];
+    messages.extend(instruction);
+    messages.push(ChatMessage::user(context.trim_end()));
+    messages
 }
 
 /// Split generated text into a title (first non-empty line) and body (the rest).
@@ -158,15 +300,23 @@ fn truncate(diff: &str) -> String {
     if diff.len() <= MAX_DIFF_BYTES {
         return diff.to_string();
     }
-    let mut end = MAX_DIFF_BYTES;
-    while !diff.is_char_boundary(end) {
+    format!("{}\n…(diff truncated)…", clip(diff, MAX_DIFF_BYTES))
+}
+
+/// `text` cut to at most `max` bytes on a char boundary.
+fn clip(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
         end -= 1;
     }
-    format!("{}\n…(diff truncated)…", &diff[..end])
+    &text[..end]
 }
 
 /// Open `$VISUAL`/`$EDITOR` (falling back to `vi`) on the seeded text.
-fn edit_in_editor(initial: &str) -> Result<String> {
+pub(crate) fn edit_in_editor(initial: &str) -> Result<String> {
     let temp = env::temp_dir().join(format!("git-github-pr-{}.md", std::process::id()));
     fs::write(&temp, initial)?;
 
@@ -195,7 +345,7 @@ fn edit_in_editor(initial: &str) -> Result<String> {
 }
 
 /// Run a git command, capturing stdout; errors carry git's stderr.
-fn git_capture(args: &[&str]) -> Result<String> {
+pub(crate) fn git_capture(args: &[&str]) -> Result<String> {
     let output = Command::new("git").args(args).output()?;
     if !output.status.success() {
         return Err(Error::GitCommand(
@@ -206,7 +356,7 @@ fn git_capture(args: &[&str]) -> Result<String> {
 }
 
 /// Run a git command with inherited stdio (so progress is visible).
-fn git_run(args: &[&str]) -> Result<()> {
+pub(crate) fn git_run(args: &[&str]) -> Result<()> {
     let status = Command::new("git")
         .args(args)
         .stdin(Stdio::inherit())
@@ -219,20 +369,733 @@ fn git_run(args: &[&str]) -> Result<()> {
     Ok(())
 }
 
-/// Run a GitHub operation, building the client inside the runtime so its
-/// hyper/tower stack has a reactor (octocrab's buffered client panics
-/// otherwise).
-fn run_github<F, Fut, T>(op: F) -> Result<T>
-where
-    F: FnOnce(octocrab::Octocrab) -> Fut,
-    Fut: Future<Output = octocrab::Result<T>>,
-{
-    let rt = tokio::runtime::Builder::new_current_thread()
-        .enable_all()
-        .build()?;
-    rt.block_on(async move {
-        let client = github::client()?;
-        op(client).await.map_err(Error::from)
+/// How `git pr merge` lands a pull request on its base branch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Merge,
+    Squash,
+    Rebase,
+}
+
+impl Method {
+    /// The name GitHub's REST API takes.
+    fn name(self) -> &'static str {
+        match self {
+            Method::Merge => "merge",
+            Method::Squash => "squash",
+            Method::Rebase => "rebase",
+        }
+    }
+}
+
+pub struct MergeOptions {
+    pub remote: String,
+    /// The pull request; by default the open one for the current branch.
+    pub number: Option<u64>,
+    /// By default the first the repository allows: merge, squash, rebase.
+    pub method: Option<Method>,
+    /// Have GitHub merge it once its requirements are met, instead of
+    /// waiting here.
+    pub auto: bool,
+    /// Use GitHub's squash message rather than an AI-written one.
+    pub no_ai: bool,
+    pub edit: bool,
+    /// Don't ask before merging.
+    pub yes: bool,
+    /// Keep the remote and local branches.
+    pub keep_branch: bool,
+    /// Seconds between refreshes while waiting for checks.
+    pub interval: u64,
+    pub lang: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pull {
+    number: u64,
+    node_id: String,
+    title: String,
+    #[serde(default)]
+    body: Option<String>,
+    state: String,
+    #[serde(default)]
+    merged: bool,
+    #[serde(default)]
+    draft: bool,
+    mergeable_state: Option<String>,
+    head: Branch,
+    base: Branch,
+}
+
+#[derive(Debug, Deserialize)]
+struct Branch {
+    #[serde(rename = "ref")]
+    name: String,
+    sha: String,
+    repo: Option<BranchRepo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BranchRepo {
+    full_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullCommit {
+    commit: PullCommitMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullCommitMessage {
+    message: String,
+}
+
+/// The merge methods a repository allows; only visible with push access,
+/// so each defaults to allowed.
+#[derive(Debug, Deserialize)]
+struct MergeSettings {
+    #[serde(default = "allowed")]
+    allow_merge_commit: bool,
+    #[serde(default = "allowed")]
+    allow_squash_merge: bool,
+    #[serde(default = "allowed")]
+    allow_rebase_merge: bool,
+}
+
+fn allowed() -> bool {
+    true
+}
+
+impl MergeSettings {
+    fn default_method(&self) -> Method {
+        if self.allow_merge_commit {
+            Method::Merge
+        } else if self.allow_squash_merge || !self.allow_rebase_merge {
+            Method::Squash
+        } else {
+            Method::Rebase
+        }
+    }
+}
+
+const SQUASH: &str = "You write the commit message that squash-merges a pull request. From its \
+title, description and commits, write a subject line under 72 characters in the style of the \
+commits, a blank line, then a short body saying what changed and why. Leave out the pull request \
+number. Output only the message.";
+
+const AUTO_MERGE: &str = "mutation($id: ID!, $method: PullRequestMergeMethod!, \
+$headline: String, $body: String) { enablePullRequestAutoMerge(input: {pullRequestId: $id, \
+mergeMethod: $method, commitHeadline: $headline, commitBody: $body}) { clientMutationId } }";
+
+/// The squash commit's subject: the drafted one with the pull request
+/// number GitHub's own squash subjects carry.
+fn squash_subject(subject: &str, number: u64) -> String {
+    let suffix = format!("(#{number})");
+    if subject.ends_with(&suffix) {
+        subject.to_string()
+    } else {
+        format!("{subject} {suffix}")
+    }
+}
+
+/// Have the model write the squash commit message from the pull request
+/// and its commits.
+fn draft_squash(
+    config: &AppConfig,
+    pull: &Pull,
+    commits: &[PullCommit],
+    lang: Option<&str>,
+) -> Result<String> {
+    let language = Language::choose(lang, config.language.as_deref());
+    let commits = commits
+        .iter()
+        .map(|c| format!("- {}", c.commit.message.trim().replace('\n', "\n  ")))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let description = clip(
+        pull.body.as_deref().unwrap_or_default().trim(),
+        MAX_ISSUE_BYTES,
+    );
+    let mut messages = vec![ChatMessage::system(SQUASH)];
+    messages.extend(
+        language
+            .as_ref()
+            .map(|l| ChatMessage::system(l.instruction())),
+    );
+    messages.push(ChatMessage::user(format!(
+        "Title: {}\n\nDescription:\n{description}\n\nCommits:\n{commits}",
+        pull.title
+    )));
+    let model = config.deepseek.model.as_deref().unwrap_or("deepseek-chat");
+    let drafted = llm::stream_and_collect(
+        &format!("Squash message for #{}", pull.number),
+        &Api::new(config),
+        model,
+        messages,
+        config.deepseek.temperature,
+        Cache::open(&config.cache).as_ref(),
+        config.ui.display,
+    )?;
+    language::warn_if_mismatched(language.as_ref(), &drafted);
+    Ok(drafted)
+}
+
+/// Merge a pull request — after its checks finish, or by GitHub with
+/// `auto` — then delete its branch and bring the local checkout back to the
+/// updated base branch.
+pub fn merge(opts: MergeOptions) -> Result<()> {
+    let result = merge_pull(opts);
+    crate::usage::settle("pr", true);
+    result
+}
+
+fn merge_pull(opts: MergeOptions) -> Result<()> {
+    let path = env::current_dir().map_err(|_| Error::NoCurrentDir)?;
+    let repo = Repo::new(&path)?;
+    let remote = &repo.remote(&opts.remote)?;
+    let mut config = load_config()?;
+    if github::token(&config)?.is_none() {
+        return Err(Error::NoGitHubToken("merging a pull request"));
+    }
+    let current = repo.current_branch().ok();
+    let route = &format!("/repos/{}/{}", remote.user, remote.repo);
+
+    let (number, branch) = (opts.number, current.clone());
+    let (pull, settings) = github::run(|client| async move {
+        let settings: MergeSettings =
+            github::writable_repo(&client, remote, "merge pull requests").await?;
+        let number = match number {
+            Some(number) => number,
+            None => {
+                let branch = branch.ok_or(Error::NoCurrentBranch)?;
+                let head = format!("{}:{branch}", remote.user);
+                let query = [("head", head.as_str()), ("state", "open")];
+                let open: Vec<Pull> = client.get(format!("{route}/pulls"), Some(&query)).await?;
+                open.first()
+                    .map(|pull| pull.number)
+                    .ok_or(Error::NoPullForBranch(branch))?
+            }
+        };
+        let pull: Pull = client
+            .get(format!("{route}/pulls/{number}"), None::<&()>)
+            .await?;
+        Ok::<_, Error>((pull, settings))
+    })?;
+    if pull.merged {
+        return Err(Error::PullNotOpen(pull.number, "already merged"));
+    }
+    if pull.state != "open" {
+        return Err(Error::PullNotOpen(pull.number, "closed"));
+    }
+    if pull.draft {
+        return Err(Error::PullNotOpen(pull.number, "a draft"));
+    }
+    let method = opts.method.unwrap_or_else(|| settings.default_method());
+
+    // Only squash merges get a message of our own; a merge commit keeps
+    // GitHub's, which names the pull request.
+    let mut message = None;
+    if method == Method::Squash && !opts.no_ai {
+        auth::require_api_key(&mut config)?;
+        let number = pull.number;
+        let commits: Vec<PullCommit> = github::run(|client| async move {
+            client
+                .get(
+                    format!("{route}/pulls/{number}/commits"),
+                    Some(&[("per_page", "100")]),
+                )
+                .await
+        })?;
+        message = Some(draft_squash(
+            &config,
+            &pull,
+            &commits,
+            opts.lang.as_deref(),
+        )?);
+    }
+    if opts.edit && method != Method::Rebase {
+        let seed = message.clone().unwrap_or_else(|| pull.title.clone());
+        message = Some(edit_in_editor(&seed)?);
+    }
+    let (subject, body) = match message.as_deref().map(split_title_body) {
+        Some((subject, _)) if subject.is_empty() => return Err(Error::EmptyMessage),
+        Some((subject, body)) if method == Method::Squash => {
+            (Some(squash_subject(&subject, pull.number)), Some(body))
+        }
+        Some((subject, body)) => (Some(subject), Some(body)),
+        None => (None, None),
+    };
+
+    println!();
+    println!(
+        "{} #{} {} into {} ({})",
+        style::bold("Merge"),
+        pull.number,
+        pull.title,
+        pull.base.name,
+        method.name()
+    );
+    if !opts.yes && io::stdin().is_terminal() && !crate::ai::confirm("Merge it?")? {
+        return Err(Error::CommitCancelled);
+    }
+
+    if opts.auto {
+        let variables = serde_json::json!({
+            "id": pull.node_id,
+            "method": method.name().to_uppercase(),
+            "headline": subject,
+            "body": body,
+        });
+        github::run(|client| async move {
+            let _: serde_json::Value = github::graphql(&client, AUTO_MERGE, variables).await?;
+            Ok::<_, Error>(())
+        })?;
+        style::success(&format!(
+            "Auto-merge enabled: GitHub will {} #{} once its requirements are met",
+            method.name(),
+            pull.number
+        ));
+        return Ok(());
+    }
+
+    let interval = Duration::from_secs(opts.interval.max(1));
+    let (number, sha, base) = (pull.number, pull.head.sha.clone(), &pull.base.name);
+    let label = format!("#{number}");
+    github::run(|client| async move {
+        let checks = ci::wait_for_checks(&client, remote, &sha, base, &label, interval).await?;
+        let failed = match checks {
+            Some(failed) => failed,
+            None => {
+                println!(
+                    "{}",
+                    style::dim(&format!("#{number} has no checks; merging."))
+                );
+                0
+            }
+        };
+        if failed > 0 {
+            // GitHub calls a pull request whose failing checks aren't
+            // required "unstable"; it can still be merged.
+            let pull: Pull = client
+                .get(format!("{route}/pulls/{number}"), None::<&()>)
+                .await?;
+            if pull.mergeable_state.as_deref() != Some("unstable") {
+                return Err(Error::ChecksFailed(failed));
+            }
+            println!(
+                "{}",
+                style::yellow("Only checks that aren't required failed; merging.")
+            );
+        }
+        let mut merge = serde_json::json!({ "merge_method": method.name(), "sha": sha });
+        if let Some(subject) = subject {
+            merge["commit_title"] = subject.into();
+        }
+        if let Some(body) = body {
+            merge["commit_message"] = body.into();
+        }
+        let _: serde_json::Value = client
+            .put(format!("{route}/pulls/{number}/merge"), Some(&merge))
+            .await?;
+        Ok(())
+    })?;
+    style::success(&format!("Merged #{} into {}", pull.number, pull.base.name));
+
+    if !opts.keep_branch {
+        clean_up(&repo, &opts.remote, route, &pull, current.as_deref())?;
+    }
+    Ok(())
+}
+
+/// After a merge: delete the pull request's branch on GitHub (unless it
+/// lives in a fork) and locally, switching to the base branch and pulling
+/// it when that's where the checkout was.
+fn clean_up(
+    repo: &Repo,
+    remote: &str,
+    route: &str,
+    pull: &Pull,
+    current: Option<&str>,
+) -> Result<()> {
+    let (head, base) = (&pull.head.name, &pull.base.name);
+    let same_repo = match (&pull.head.repo, &pull.base.repo) {
+        (Some(head), Some(base)) => head.full_name == base.full_name,
+        _ => false,
+    };
+    if same_repo {
+        let route = format!("{route}/git/refs/heads/{head}");
+        // The repository may delete merged branches itself.
+        let deleted = github::run(|client| async move {
+            let response = client._delete(route, None::<&()>).await?;
+            Ok::<_, Error>(response.status().is_success() || response.status() == 422)
+        })?;
+        if deleted {
+            println!("Deleted {remote}/{head}");
+        }
+        let _ = git_capture(&["fetch", "-q", "--prune", remote]);
+    }
+
+    let on_head = current == Some(head.as_str());
+    if on_head {
+        git_run(&["switch", "-q", base])?;
+        println!("Switched to {base}");
+    }
+    if on_head || current == Some(base.as_str()) {
+        git_run(&["pull", "-q", "--ff-only", remote, base])?;
+    }
+    // Only a branch with nothing beyond what was merged.
+    if repo.local_branch_id(head).as_deref() == Some(pull.head.sha.as_str()) {
+        git_run(&["branch", "-q", "-D", head])?;
+        println!("Deleted branch {head}");
+    }
+    Ok(())
+}
+
+/// Which pull requests `git pr list` shows.
+pub struct ListOptions {
+    pub remote: String,
+    /// `open`, `closed`, `merged` or `all`.
+    pub state: String,
+    /// A login, or `@me`.
+    pub author: Option<String>,
+    /// A login, or `@me`.
+    pub review_requested: Option<String>,
+    /// Only drafts.
+    pub draft: bool,
+    pub labels: Vec<String>,
+    pub base: Option<String>,
+    pub limit: usize,
+}
+
+/// A pull request as the listings show it.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary {
+    number: u64,
+    title: String,
+    url: String,
+    is_draft: bool,
+    state: String,
+    head_ref_name: String,
+    /// `None` when the fork it came from is gone.
+    head_repository_owner: Option<Login>,
+    author: Option<Login>,
+    review_decision: Option<String>,
+    commits: Nodes<LastCommit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Login {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Nodes<T> {
+    nodes: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LastCommit {
+    commit: Rollup,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Rollup {
+    status_check_rollup: Option<RollupState>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RollupState {
+    state: String,
+}
+
+const SUMMARY: &str = "fragment summary on PullRequest { number title url isDraft state \
+headRefName headRepositoryOwner { login } author { login } reviewDecision \
+commits(last: 1) { nodes { commit { statusCheckRollup { state } } } } }";
+
+const LIST: &str = "query($q: String!, $n: Int!) { search(query: $q, type: ISSUE, first: $n) \
+{ nodes { ...summary } } }";
+
+const STATUS: &str = "query($mine: String!, $review: String!, $owner: String!, $name: String!, \
+$branch: String!) { \
+mine: search(query: $mine, type: ISSUE, first: 20) { nodes { ...summary } } \
+review: search(query: $review, type: ISSUE, first: 20) { nodes { ...summary } } \
+repository(owner: $owner, name: $name) { pullRequests(headRefName: $branch, first: 10, \
+orderBy: {field: CREATED_AT, direction: DESC}) { nodes { ...summary } } } }";
+
+#[derive(Debug, Deserialize)]
+struct Search {
+    search: Nodes<Summary>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Dashboard {
+    mine: Nodes<Summary>,
+    review: Nodes<Summary>,
+    repository: CurrentPulls,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurrentPulls {
+    pull_requests: Nodes<Summary>,
+}
+
+/// A word in a listing's status, and how to color it.
+type Badge = (&'static str, fn(&str) -> String);
+
+impl Summary {
+    /// What stands out about it, as words and the color each is shown in:
+    /// `draft` or `merged`, the combined state of the head commit's checks,
+    /// and the review decision.
+    fn status(&self) -> Vec<Badge> {
+        let mut parts: Vec<Badge> = Vec::new();
+        match self.state.as_str() {
+            "MERGED" => parts.push(("merged", style::dim)),
+            "CLOSED" => parts.push(("closed", style::dim)),
+            _ if self.is_draft => parts.push(("draft", style::dim)),
+            _ => {}
+        }
+        let rollup = self
+            .commits
+            .nodes
+            .last()
+            .and_then(|c| c.commit.status_check_rollup.as_ref());
+        match rollup.map(|rollup| rollup.state.as_str()) {
+            Some("SUCCESS") => parts.push(("checks pass", style::green)),
+            Some("FAILURE" | "ERROR") => parts.push(("checks fail", style::red)),
+            Some(_) => parts.push(("checks running", style::yellow)),
+            None => {}
+        }
+        match self.review_decision.as_deref() {
+            Some("APPROVED") => parts.push(("approved", style::green)),
+            Some("CHANGES_REQUESTED") => parts.push(("changes requested", style::red)),
+            Some("REVIEW_REQUIRED") => parts.push(("review required", style::yellow)),
+            _ => {}
+        }
+        parts
+    }
+
+    /// One line: number, title, branch (and author), then its status,
+    /// with the title cut to fit `width` columns.
+    fn line(&self, width: usize, show_author: bool) -> String {
+        let number = format!("#{}", self.number);
+        let mut who = format!("[{}]", self.head_ref_name);
+        if let Some(author) = self.author.as_ref().filter(|_| show_author) {
+            who = format!("{who} @{}", author.login);
+        }
+        let status = self.status();
+        let status_width: usize = status.iter().map(|(word, _)| word.len() + 3).sum();
+        let rest = number.len() + who.chars().count() + status_width + 2;
+        let title = style::truncate(&self.title, width.saturating_sub(rest).max(20));
+
+        let mut line = format!("{} {title} {}", style::bold(&number), style::dim(&who));
+        if !status.is_empty() {
+            let status: Vec<String> = status.iter().map(|(word, paint)| paint(word)).collect();
+            line = format!("{line}  {}", status.join(&style::dim(" · ")));
+        }
+        line
+    }
+}
+
+/// The search query for `opts`, in GitHub's search syntax.
+fn list_query(owner: &str, name: &str, opts: &ListOptions) -> String {
+    let mut query = vec![format!("repo:{owner}/{name}"), "is:pr".to_string()];
+    match opts.state.as_str() {
+        "all" => {}
+        "merged" => query.push("is:merged".to_string()),
+        "closed" => query.push("is:closed".to_string()),
+        _ => query.push("is:open".to_string()),
+    }
+    query.extend(opts.author.as_ref().map(|a| format!("author:{a}")));
+    query.extend(
+        opts.review_requested
+            .as_ref()
+            .map(|r| format!("review-requested:{r}")),
+    );
+    if opts.draft {
+        query.push("draft:true".to_string());
+    }
+    for label in &opts.labels {
+        // Quoted, so labels with spaces work.
+        query.push(format!("label:\"{}\"", label.replace('"', "")));
+    }
+    query.extend(opts.base.as_ref().map(|b| format!("base:{b}")));
+    query.push("sort:updated-desc".to_string());
+    query.join(" ")
+}
+
+/// `git pr list`: the repository's pull requests matching `opts`, most
+/// recently updated first.
+pub fn list(opts: ListOptions) -> Result<()> {
+    let path = env::current_dir().map_err(|_| Error::NoCurrentDir)?;
+    let repo = Repo::new(&path)?;
+    let remote = repo.remote(&opts.remote)?;
+    let config = load_config()?;
+    // GitHub's GraphQL API, unlike REST, has no anonymous access.
+    if github::token(&config)?.is_none() {
+        return Err(Error::NoGitHubToken("listing pull requests"));
+    }
+    let query = list_query(&remote.user, &remote.repo, &opts);
+    let variables = serde_json::json!({ "q": query, "n": opts.limit.clamp(1, 100) });
+    let found: Search = github::run(|client| async move {
+        github::graphql(&client, &format!("{LIST} {SUMMARY}"), variables).await
+    })?;
+
+    if found.search.nodes.is_empty() {
+        println!("{}", style::dim("No pull requests match."));
+        return Ok(());
+    }
+    let width = style::width();
+    for pull in &found.search.nodes {
+        println!("{}", pull.line(width, true));
+    }
+    Ok(())
+}
+
+/// The newest of `pulls` whose head branch lives in `owner`'s repository,
+/// not in a fork with a branch of the same name.
+fn from_owner(pulls: Vec<Summary>, owner: &str) -> Option<Summary> {
+    pulls.into_iter().find(|pull| {
+        pull.head_repository_owner
+            .as_ref()
+            .is_some_and(|head| head.login.eq_ignore_ascii_case(owner))
     })
 }
 
+/// `git pr status`: the current branch's pull request, those you opened and
+/// those waiting for your review, with their checks and reviews, in one
+/// GraphQL query.
+pub fn status(remote: &str) -> Result<()> {
+    let path = env::current_dir().map_err(|_| Error::NoCurrentDir)?;
+    let repo = Repo::new(&path)?;
+    let remote = repo.remote(remote)?;
+    let config = load_config()?;
+    if github::token(&config)?.is_none() {
+        return Err(Error::NoGitHubToken("listing pull requests"));
+    }
+    let branch = repo.current_branch().ok();
+    let scope = format!("repo:{}/{} is:pr is:open", remote.user, remote.repo);
+    let variables = serde_json::json!({
+        "mine": format!("{scope} author:@me sort:updated-desc"),
+        "review": format!("{scope} review-requested:@me sort:updated-desc"),
+        "owner": remote.user,
+        "name": remote.repo,
+        "branch": branch.clone().unwrap_or_default(),
+    });
+    let dashboard: Dashboard = github::run(|client| async move {
+        github::graphql(&client, &format!("{STATUS} {SUMMARY}"), variables).await
+    })?;
+
+    let width = style::width().saturating_sub(2);
+    // Without a branch the query matched any head; there's no current one.
+    // A fork's branch of the same name isn't this one either.
+    let current: Vec<Summary> = match branch {
+        Some(_) => from_owner(dashboard.repository.pull_requests.nodes, &remote.user)
+            .into_iter()
+            .collect(),
+        None => Vec::new(),
+    };
+    let sections = [
+        (
+            format!(
+                "Current branch ({})",
+                branch.as_deref().unwrap_or("detached")
+            ),
+            current,
+            false,
+        ),
+        ("Created by you".to_string(), dashboard.mine.nodes, false),
+        (
+            "Requesting your review".to_string(),
+            dashboard.review.nodes,
+            true,
+        ),
+    ];
+    for (i, (title, pulls, show_author)) in sections.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("{}", style::bold(title));
+        if pulls.is_empty() {
+            println!("  {}", style::dim("none"));
+        }
+        for pull in pulls {
+            println!("  {}", pull.line(width, *show_author));
+        }
+    }
+    if let Some(pull) = sections[0].1.first() {
+        println!();
+        println!("{}", style::dim(&pull.url));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn squash_messages_and_methods() {
+        assert_eq!(squash_subject("Add widgets", 12), "Add widgets (#12)");
+        assert_eq!(squash_subject("Add widgets (#12)", 12), "Add widgets (#12)");
+        let settings: MergeSettings =
+            serde_json::from_str(r#"{"allow_merge_commit": false}"#).unwrap();
+        assert_eq!(settings.default_method(), Method::Squash);
+        let settings: MergeSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.default_method(), Method::Merge);
+        let settings: MergeSettings =
+            serde_json::from_str(r#"{"allow_merge_commit": false, "allow_squash_merge": false}"#)
+                .unwrap();
+        assert_eq!(settings.default_method(), Method::Rebase);
+    }
+
+    #[test]
+    fn current_pull_skips_forks() {
+        let pulls: Vec<Summary> = serde_json::from_value(serde_json::json!([
+            { "number": 9, "title": "Fork's main", "url": "u9", "isDraft": false,
+              "state": "OPEN", "headRefName": "main", "headRepositoryOwner": { "login": "someone" },
+              "commits": { "nodes": [] } },
+            { "number": 7, "title": "Deleted fork", "url": "u7", "isDraft": false,
+              "state": "OPEN", "headRefName": "main", "headRepositoryOwner": null,
+              "commits": { "nodes": [] } },
+            { "number": 5, "title": "Ours", "url": "u5", "isDraft": false,
+              "state": "OPEN", "headRefName": "main", "headRepositoryOwner": { "login": "Octo" },
+              "commits": { "nodes": [] } },
+        ]))
+        .unwrap();
+        assert_eq!(from_owner(pulls, "octo").map(|pull| pull.number), Some(5));
+    }
+
+    #[test]
+    fn list_queries() {
+        let mut opts = ListOptions {
+            remote: "origin".to_string(),
+            state: "open".to_string(),
+            author: Some("@me".to_string()),
+            review_requested: None,
+            draft: true,
+            labels: vec!["good first issue".to_string()],
+            base: Some("main".to_string()),
+            limit: 30,
+        };
+        assert_eq!(
+            list_query("o", "r", &opts),
+            "repo:o/r is:pr is:open author:@me draft:true label:\"good first issue\" base:main \
+             sort:updated-desc"
+        );
+        opts = ListOptions {
+            state: "all".to_string(),
+            author: None,
+            draft: false,
+            labels: Vec::new(),
+            base: None,
+            review_requested: Some("@me".to_string()),
+            ..opts
+        };
+        assert_eq!(
+            list_query("o", "r", &opts),
+            "repo:o/r is:pr review-requested:@me sort:updated-desc"
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! Named profiles: `[profiles.<name>]` sections holding model and GitHub
+//! account settings, applied over the rest of the configuration. Several
+//! can apply at once, later ones winning: the `profile` setting, the
+//! command's entry in `[command_profiles]`, every profile whose `match`
+//! patterns fit the remote, then `--profile`.
+
+use crate::config::{DeepSeekConfig, GitHubConfig};
+use crate::error::{Error, Result};
+use crate::remote::Remote;
+use crate::repo::Repo;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::sync::OnceLock;
+
+/// The settings a profile may hold.
+#[derive(Debug, Default, Deserialize)]
+pub struct Profile {
+    /// Remotes that select the profile automatically: `host`, `host/owner`
+    /// or `host/owner/repo`, with `*` wildcards, e.g. `github.com/acme-*`.
+    #[serde(default, rename = "match")]
+    pub matches: Vec<String>,
+    #[serde(default)]
+    pub deepseek: DeepSeekConfig,
+    #[serde(default)]
+    pub github: GitHubConfig,
+    pub language: Option<String>,
+}
+
+/// Why a profile applies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// The `profile` setting.
+    Default,
+    /// `[command_profiles]` for this command.
+    Command(&'static str),
+    /// A `match` pattern fit the remote's URL.
+    Remote(String),
+    /// `--profile`.
+    Flag,
+}
+
+impl Reason {
+    pub fn describe(&self) -> String {
+        match self {
+            Reason::Default => "the `profile` setting".to_string(),
+            Reason::Command(command) => format!("[command_profiles] {command}"),
+            Reason::Remote(remote) => format!("remote {remote}"),
+            Reason::Flag => "--profile".to_string(),
+        }
+    }
+}
+
+/// What the running command tells the configuration loader.
+struct Context {
+    command: &'static str,
+    flag: Option<String>,
+    remote: String,
+}
+
+static CONTEXT: OnceLock<Context> = OnceLock::new();
+
+/// Called by each binary before doing anything else: its name as used in
+/// `[command_profiles]` (`ac`, `pr`, …), its `--profile` flag, and the
+/// remote whose URL selects profiles by `match`.
+pub fn select(command: &'static str, flag: Option<String>, remote: &str) {
+    let _ = CONTEXT.set(Context {
+        command,
+        flag,
+        remote: remote.to_string(),
+    });
+}
+
+/// The profiles that apply, lowest precedence first, each listed once.
+pub(crate) fn applicable(
+    profiles: &HashMap<String, Profile>,
+    default: Option<&str>,
+    command_profiles: &HashMap<String, String>,
+) -> Result<Vec<(String, Reason)>> {
+    let context = CONTEXT.get();
+    let mut chosen: Vec<(String, Reason)> = Vec::new();
+    let mut add = |name: &str, reason: Reason| -> Result<()> {
+        if !profiles.contains_key(name) {
+            return Err(Error::UnknownProfile {
+                name: name.to_string(),
+                known: sorted_names(profiles),
+            });
+        }
+        chosen.retain(|(other, _)| other != name);
+        chosen.push((name.to_string(), reason));
+        Ok(())
+    };
+
+    if let Some(name) = default.filter(|name| !name.is_empty()) {
+        add(name, Reason::Default)?;
+    }
+    if let Some(context) = context {
+        if let Some(name) = command_profiles.get(context.command) {
+            add(name, Reason::Command(context.command))?;
+        }
+        if let Some(remote) = remote_path(&context.remote) {
+            for name in sorted_names(profiles) {
+                if profiles[&name].matches.iter().any(|p| matches(p, &remote)) {
+                    add(&name, Reason::Remote(remote.clone()))?;
+                }
+            }
+        }
+        if let Some(name) = &context.flag {
+            add(name, Reason::Flag)?;
+        }
+    }
+    Ok(chosen)
+}
+
+fn sorted_names(profiles: &HashMap<String, Profile>) -> Vec<String> {
+    let mut names: Vec<String> = profiles.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// `host/owner/repo` of `remote` in the current repository.
+fn remote_path(remote: &str) -> Option<String> {
+    let repo = Repo::new(&env::current_dir().ok()?).ok()?;
+    let Remote {
+        host, user, repo, ..
+    } = repo.remote(remote).ok()?;
+    Some(format!("{host}/{user}/{repo}"))
+}
+
+/// Whether `pattern` fits `path` (`host/owner/repo`), segment by segment.
+/// A pattern with fewer segments matches everything beneath them.
+fn matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.trim_matches('/').split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    pattern.len() <= path.len()
+        && pattern
+            .iter()
+            .zip(&path)
+            .all(|(pattern, segment)| glob(&pattern.to_lowercase(), &segment.to_lowercase()))
+}
+
+/// `*` matches any run of characters; everything else itself.
+fn glob(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((head, tail)) => {
+            let Some(rest) = text.strip_prefix(head) else {
+                return false;
+            };
+            (0..=rest.len())
+                .filter(|&at| rest.is_char_boundary(at))
+                .any(|at| glob(tail, &rest[at..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn patterns_match_host_owner_and_repo() {
+        let path = "github.com/acme-corp/api";
+        assert!(matches("github.com", path));
+        assert!(matches("github.com/acme-corp", path));
+        assert!(matches("github.com/acme-*", path));
+        assert!(matches("*/ACME-corp/", path));
+        assert!(matches("github.com/acme-corp/api", path));
+        assert!(!matches("github.com/acme", path));
+        assert!(!matches("gitlab.com", path));
+        assert!(!matches("github.com/acme-corp/api/extra", path));
+        assert!(glob("*.corp.example.com", "git.corp.example.com"));
+        assert!(glob("a*b*c", "axxbyyc") && !glob("a*b*c", "axxbyy"));
+    }
+
+    #[test]
+    fn later_reasons_win_and_unknown_names_fail() {
+        let profiles = HashMap::from([
+            ("cheap".to_string(), Profile::default()),
+            ("work".to_string(), Profile::default()),
+        ]);
+        let chosen = applicable(&profiles, Some("work"), &HashMap::new()).unwrap();
+        assert_eq!(chosen, [("work".to_string(), Reason::Default)]);
+        let err = applicable(&profiles, Some("wrok"), &HashMap::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no profile named 'wrok' (defined: cheap, work)"
+        );
+    }
+}
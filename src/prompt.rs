@@ -0,0 +1,378 @@
+//! Named prompt templates. Each built-in system prompt can be replaced by a
+//! file — `.git-github/prompts/<name>.md` in the repository, else
+//! `prompts/<name>.md` in the user config directory — whose `{variable}`s are
+//! filled in per request (`{{` and `}}` stand for literal braces). Templates
+//! are checked when loaded, so a misspelt variable fails up front instead of
+//! reaching the model verbatim.
+
+use crate::config::{self, load_config};
+use crate::error::{Error, Result};
+use crate::repo::Repo;
+use crate::style;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The prompts that can be customised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Commit,
+    Pr,
+    Review,
+    ReleaseNotes,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 4] = [Kind::Commit, Kind::Pr, Kind::Review, Kind::ReleaseNotes];
+
+    /// The template's name, which is also its file stem.
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Commit => "commit",
+            Kind::Pr => "pr",
+            Kind::Review => "review",
+            Kind::ReleaseNotes => "release-notes",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Kind> {
+        Kind::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// The variables a template of this kind may use.
+    pub fn variables(self) -> &'static [&'static str] {
+        match self {
+            Kind::Commit => &[
+                "branch",
+                "language",
+                "ticket",
+                "recent_commits",
+                "conventions",
+                "diff",
+            ],
+            Kind::Pr => &[
+                "branch", "base", "language", "ticket", "commits", "issue", "diff",
+            ],
+            Kind::Review => &["branch", "base", "language", "diff"],
+            Kind::ReleaseNotes => &["tag", "previous_tag", "language", "commits"],
+        }
+    }
+
+    fn builtin(self) -> &'static str {
+        match self {
+            Kind::Commit => COMMIT,
+            Kind::Pr => PR,
+            Kind::Review => REVIEW,
+            Kind::ReleaseNotes => RELEASE_NOTES,
+        }
+    }
+}
+
+/// Where a template was loaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    BuiltIn,
+    /// The legacy `[deepseek] prompt` string, taken literally.
+    Config,
+    File(PathBuf),
+}
+
+/// A parsed template.
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub kind: Kind,
+    pub origin: Origin,
+    pieces: Vec<Piece>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Var(String),
+}
+
+impl Prompt {
+    /// The template for `kind`: the repository's file, else the user's, else
+    /// `fallback` (a literal prompt from the config), else the built-in one.
+    pub fn load(kind: Kind, workdir: Option<&Path>, fallback: Option<&str>) -> Result<Prompt> {
+        for path in candidates(kind, workdir) {
+            if let Ok(text) = fs::read_to_string(&path) {
+                let pieces = parse(&text, kind).map_err(|reason| Error::PromptTemplate {
+                    path: path.clone(),
+                    reason,
+                })?;
+                return Ok(Prompt {
+                    kind,
+                    origin: Origin::File(path),
+                    pieces,
+                });
+            }
+        }
+        match fallback.filter(|text| !text.trim().is_empty()) {
+            Some(text) => Ok(Prompt {
+                kind,
+                origin: Origin::Config,
+                pieces: vec![Piece::Text(text.to_string())],
+            }),
+            None => Ok(Prompt::builtin(kind)),
+        }
+    }
+
+    pub fn builtin(kind: Kind) -> Prompt {
+        Prompt {
+            kind,
+            origin: Origin::BuiltIn,
+            pieces: parse(kind.builtin(), kind).expect("built-in templates are valid"),
+        }
+    }
+
+    /// Whether the template places `variable` itself. Data it leaves out is
+    /// sent alongside it instead.
+    pub fn uses(&self, variable: &str) -> bool {
+        self.pieces
+            .iter()
+            .any(|piece| matches!(piece, Piece::Var(name) if name == variable))
+    }
+
+    /// The template with its variables replaced by `values`; a variable
+    /// without a value renders empty.
+    pub fn render(&self, values: &[(&str, String)]) -> String {
+        let mut out = String::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Text(text) => out.push_str(text),
+                Piece::Var(name) => {
+                    if let Some((_, value)) = values.iter().find(|(key, _)| key == name) {
+                        out.push_str(value);
+                    }
+                }
+            }
+        }
+        out.trim().to_string()
+    }
+
+    /// The template's source text, with braces escaped as in a file.
+    pub fn source(&self) -> String {
+        self.pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => text.replace('{', "{{").replace('}', "}}"),
+                Piece::Var(name) => format!("{{{name}}}"),
+            })
+            .collect()
+    }
+}
+
+/// `git ac prompt [name]`: list the templates in effect and where each comes
+/// from, or print one's source as a starting point for a custom file.
+pub fn show(name: Option<&str>) -> Result<()> {
+    let repo = env::current_dir()
+        .ok()
+        .and_then(|path| Repo::new(&path).ok());
+    let workdir = repo.as_ref().and_then(|repo| repo.workdir());
+    let legacy = load_config().ok().and_then(|config| config.deepseek.prompt);
+    let load = |kind: Kind| {
+        let fallback = legacy.as_deref().filter(|_| kind == Kind::Commit);
+        Prompt::load(kind, workdir, fallback)
+    };
+
+    if let Some(name) = name {
+        let kind = Kind::from_name(name).ok_or_else(|| Error::UnknownPrompt(name.to_string()))?;
+        println!("{}", load(kind)?.source().trim());
+        return Ok(());
+    }
+    for kind in Kind::ALL {
+        let origin = match load(kind) {
+            Ok(prompt) => match prompt.origin {
+                Origin::BuiltIn => "built-in".to_string(),
+                Origin::Config => "[deepseek] prompt".to_string(),
+                Origin::File(path) => path.display().to_string(),
+            },
+            Err(e) => style::red(&e.to_string()),
+        };
+        let variables: Vec<String> = kind
+            .variables()
+            .iter()
+            .map(|v| format!("{{{v}}}"))
+            .collect();
+        println!("{} {origin}", style::bold(&format!("{:<14}", kind.name())));
+        println!("{:<14} {}", "", style::dim(&variables.join(" ")));
+    }
+    Ok(())
+}
+
+/// Template files for `kind`, in order of precedence.
+fn candidates(kind: Kind, workdir: Option<&Path>) -> Vec<PathBuf> {
+    let file = format!("{}.md", kind.name());
+    let mut paths = Vec::new();
+    if let Some(workdir) = workdir {
+        paths.push(workdir.join(".git-github").join("prompts").join(&file));
+    }
+    if let Some(dir) = config::config_dir() {
+        paths.push(dir.join("prompts").join(&file));
+    }
+    paths
+}
+
+/// Split `text` into literal text and `{variable}`s, rejecting variables
+/// `kind` doesn't know and unbalanced braces.
+fn parse(text: &str, kind: Kind) -> std::result::Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut literal = String::new();
+    let mut chars = text.char_indices().peekable();
+    let line_of = |at: usize| text[..at].matches('\n').count() + 1;
+
+    while let Some((at, ch)) = chars.next() {
+        match ch {
+            '{' if chars.peek().map(|&(_, c)| c) == Some('{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek().map(|&(_, c)| c) == Some('}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, c)) if c.is_ascii_alphanumeric() || c == '_' => name.push(c),
+                        _ => {
+                            return Err(format!(
+                                "line {}: unclosed `{{` (write `{{{{` for a literal brace)",
+                                line_of(at)
+                            ))
+                        }
+                    }
+                }
+                if !kind.variables().contains(&name.as_str()) {
+                    return Err(format!(
+                        "line {}: unknown variable {{{name}}}; a {} template can use {}",
+                        line_of(at),
+                        kind.name(),
+                        kind.variables()
+                            .iter()
+                            .map(|v| format!("{{{v}}}"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ));
+                }
+                if !literal.is_empty() {
+                    pieces.push(Piece::Text(std::mem::take(&mut literal)));
+                }
+                pieces.push(Piece::Var(name));
+            }
+            '}' => {
+                return Err(format!(
+                    "line {}: unmatched `}}` (write `}}}}` for a literal brace)",
+                    line_of(at)
+                ))
+            }
+            _ => literal.push(ch),
+        }
+    }
+    if !literal.is_empty() {
+        pieces.push(Piece::Text(literal));
+    }
+    Ok(pieces)
+}
+
+const COMMIT: &str = r#"
+You are an AI commit message assistant.
+
+Please generate a commit message with the following format:
+1. Title (one short sentence, 50-72 characters max).
+2. A clear bullet-point list of changes (start each line with "- ").
+3. Each line, including bullets, should be under 100 characters.
+4. Keep it concise, consistent, and professional.
+
+Example:
+
+Improve error handling in user authentication
+
+- Add detailed error messages for login failures
+- Handle timeout errors gracefully
+- Refactor error propagation logic for clarity
+"#;
+
+const PR: &str = r###"You are an assistant that writes GitHub pull request descriptions.
+
+Given the commit list and diff, produce:
+1. A concise PR title on the FIRST line (max 72 characters, no type prefix and no markdown heading).
+2. A blank line.
+3. A markdown body: a short summary paragraph, then a "## Changes" section with a bullet list.
+
+Be professional and concise. Output only the title and body, nothing else."###;
+
+const REVIEW: &str = r#"You are a senior engineer reviewing a change on branch {branch} against {base}.
+
+Point out bugs, risky edge cases and unclear code in the diff, most important first, as a
+markdown bullet list citing file and line. Skip praise and style nits. Write in {language}."#;
+
+const RELEASE_NOTES: &str = r###"You write release notes for version {tag} (previous release: {previous_tag}).
+
+Group the commits below under "## Feature", "## Fix" and "## Breaking" headings (omit empty
+groups), one concise bullet per user-visible change, merging related commits and leaving out
+internal chores. Write in {language}. Output only the markdown.
+
+Commits:
+{commits}"###;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_variables_and_escaped_braces() {
+        let pieces = parse("On {branch}: {{literal}} {diff}", Kind::Commit).unwrap();
+        assert_eq!(
+            pieces,
+            vec![
+                Piece::Text("On ".into()),
+                Piece::Var("branch".into()),
+                Piece::Text(": {literal} ".into()),
+                Piece::Var("diff".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_variables_and_stray_braces() {
+        let unknown = parse("fine\n{branhc}", Kind::Commit).unwrap_err();
+        assert!(
+            unknown.starts_with("line 2: unknown variable {branhc}"),
+            "{unknown}"
+        );
+        assert!(unknown.contains("{recent_commits}"));
+        // Known to one kind, not another.
+        assert!(parse("{issue}", Kind::Commit).is_err());
+        assert!(parse("{issue}", Kind::Pr).is_ok());
+        assert!(parse("{\"json\": 1}", Kind::Commit).is_err());
+        assert!(parse("a } b", Kind::Commit).is_err());
+        assert!(parse("{diff", Kind::Commit).is_err());
+    }
+
+    #[test]
+    fn renders_values_and_round_trips_source() {
+        let prompt = Prompt {
+            kind: Kind::Pr,
+            origin: Origin::BuiltIn,
+            pieces: parse("Into {base} from {branch} {{x}}", Kind::Pr).unwrap(),
+        };
+        assert!(prompt.uses("base") && !prompt.uses("diff"));
+        assert_eq!(
+            prompt.render(&[("base", "main".into())]),
+            "Into main from  {x}"
+        );
+        assert_eq!(prompt.source(), "Into {base} from {branch} {{x}}");
+    }
+
+    #[test]
+    fn builtins_are_valid() {
+        for kind in Kind::ALL {
+            assert_eq!(Prompt::builtin(kind).kind, kind);
+            assert_eq!(Kind::from_name(kind.name()), Some(kind));
+        }
+    }
+}
@@ -0,0 +1,1028 @@
+//! `git release <tag>`: release notes from the commits and merged pull
+//! requests since the previous tag — grouped by their Conventional Commit
+//! types when the history uses them, else written by the model — added to
+//! CHANGELOG.md, then an annotated tag and a GitHub release.
+//!
+//! `git release --suggest` reads the same history to propose the next
+//! semantic version.
+
+use crate::ai;
+use crate::auth;
+use crate::cache::Cache;
+use crate::config::{load_config, AppConfig, Display};
+use crate::error::{Error, Result};
+use crate::github;
+use crate::language::Language;
+use crate::llm::{self, Api, ChatMessage};
+use crate::pr::{edit_in_editor, git_run};
+use crate::prompt::{Kind, Prompt};
+use crate::remote::Remote;
+use crate::repo::{LogCommit, Repo};
+use crate::style;
+use crate::usage;
+use regex::Regex;
+use serde::de::IgnoredAny;
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Share of commits that must follow Conventional Commits for their types to
+/// be trusted over the model.
+const CONVENTIONAL: f32 = 0.6;
+const CHANGELOG: &str = "CHANGELOG.md";
+
+pub struct Options {
+    pub remote: String,
+    pub tag: String,
+    /// The previous release; by default the tag nearest to HEAD.
+    pub since: Option<String>,
+    /// Publish the GitHub release rather than leaving it a draft.
+    pub publish: bool,
+    /// Files to attach to the release.
+    pub assets: Vec<PathBuf>,
+    /// Have the model write the notes even for Conventional Commits.
+    pub ai: bool,
+    pub edit: bool,
+    /// Don't ask before tagging.
+    pub yes: bool,
+    pub no_changelog: bool,
+    /// Tag locally only: no push, no GitHub release.
+    pub no_push: bool,
+    pub lang: Option<String>,
+}
+
+/// A parsed Conventional Commits subject: `type(scope)!: description`.
+#[derive(Debug, PartialEq, Eq)]
+struct Conventional {
+    kind: String,
+    scope: Option<String>,
+    breaking: bool,
+    description: String,
+}
+
+fn conventional(message: &str) -> Option<Conventional> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN
+        .get_or_init(|| Regex::new(r"^(\w+)(?:\(([^)]*)\))?(!)?: (.+)$").expect("a valid pattern"));
+    let subject = message.lines().next().unwrap_or_default().trim();
+    let caps = pattern.captures(subject)?;
+    let footer_breaking = message
+        .lines()
+        .any(|line| line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:"));
+    Some(Conventional {
+        kind: caps[1].to_lowercase(),
+        scope: caps
+            .get(2)
+            .map(|m| m.as_str().to_string())
+            .filter(|s| !s.is_empty()),
+        breaking: caps.get(3).is_some() || footer_breaking,
+        description: caps[4].trim().to_string(),
+    })
+}
+
+/// The pull request a commit brought in: `Merge pull request #12 from …`, or
+/// a squash merge's `… (#12)`.
+fn pull_number(message: &str) -> Option<u64> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| {
+        Regex::new(r"^Merge pull request #(\d+)|\(#(\d+)\)\s*$").expect("a valid pattern")
+    });
+    let subject = message.lines().next().unwrap_or_default();
+    let caps = pattern.captures(subject)?;
+    caps.get(1).or(caps.get(2))?.as_str().parse().ok()
+}
+
+/// What a merged pull request contributes to the notes.
+#[derive(Debug, Clone)]
+struct Pull {
+    title: String,
+    author: Option<String>,
+    labels: Vec<String>,
+}
+
+/// One line of the notes for each commit (or the pull request it merged).
+#[derive(Debug, PartialEq, Eq)]
+struct Change {
+    short_id: String,
+    text: String,
+    parsed: Option<Conventional>,
+    author: Option<String>,
+    labels: Vec<String>,
+}
+
+impl Change {
+    /// `line` credited to the pull request's author, if known.
+    fn credit(&self, line: &str) -> String {
+        match &self.author {
+            Some(author) => format!("{line} by @{author}"),
+            None => line.to_string(),
+        }
+    }
+}
+
+fn changes(commits: &[LogCommit], pulls: &HashMap<u64, Pull>) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for commit in commits {
+        let number = pull_number(&commit.message);
+        let pull = number.and_then(|n| pulls.get(&n));
+        let (text, parsed) = match (pull, number) {
+            // A merge commit's subject says nothing; its pull request does.
+            (Some(pull), Some(number)) if commit.merge => (
+                format!("{} (#{number})", pull.title),
+                conventional(&pull.title),
+            ),
+            _ if commit.merge => continue,
+            _ => (
+                commit
+                    .message
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                conventional(&commit.message),
+            ),
+        };
+        changes.push(Change {
+            short_id: commit.short_id.clone(),
+            text,
+            parsed,
+            author: pull.and_then(|pull| pull.author.clone()),
+            labels: pull.map(|pull| pull.labels.clone()).unwrap_or_default(),
+        });
+    }
+    changes
+}
+
+/// Notes grouped by Conventional Commit type: `feat` under Feature, `fix`
+/// and `perf` under Fix, `!`/`BREAKING CHANGE` under Breaking; chores,
+/// docs, refactors and the like are left out. Pull requests without a typed
+/// title go by their labels.
+fn group(changes: &[Change]) -> String {
+    let (mut features, mut fixes, mut breaking) = (Vec::new(), Vec::new(), Vec::new());
+    for change in changes {
+        let has_label = |names: &[&str]| {
+            change
+                .labels
+                .iter()
+                .any(|label| names.contains(&label.to_lowercase().as_str()))
+        };
+        let (section, text) = match &change.parsed {
+            Some(parsed) => {
+                let text = match &parsed.scope {
+                    Some(scope) => format!("`{scope}`: {}", parsed.description),
+                    None => parsed.description.clone(),
+                };
+                let section = match parsed.kind.as_str() {
+                    _ if parsed.breaking => &mut breaking,
+                    "feat" => &mut features,
+                    "fix" | "perf" => &mut fixes,
+                    _ => continue,
+                };
+                (section, change.credit(&text))
+            }
+            None if change.labels.is_empty() => continue,
+            None if has_label(&["breaking", "breaking change"]) => {
+                (&mut breaking, change.credit(&change.text))
+            }
+            None if has_label(&["bug", "fix", "bugfix"]) => {
+                (&mut fixes, change.credit(&change.text))
+            }
+            None => (&mut features, change.credit(&change.text)),
+        };
+        section.push(format!("- {text}"));
+    }
+
+    let mut notes = String::new();
+    for (title, items) in [
+        ("Feature", features),
+        ("Fix", fixes),
+        ("Breaking", breaking),
+    ] {
+        if items.is_empty() {
+            continue;
+        }
+        notes.push_str(&format!("## {title}\n\n{}\n\n", items.join("\n")));
+    }
+    notes.trim_end().to_string()
+}
+
+/// Whether enough of the history follows Conventional Commits to group it
+/// by type.
+fn is_conventional(changes: &[Change]) -> bool {
+    let typed = changes
+        .iter()
+        .filter(|change| change.parsed.is_some())
+        .count();
+    !changes.is_empty() && typed as f32 / changes.len() as f32 >= CONVENTIONAL
+}
+
+/// The body of a non-empty `# Unreleased` section: notes someone kept by
+/// hand, which win over generated ones.
+fn unreleased(changelog: &str) -> Option<String> {
+    let mut lines = changelog.lines();
+    lines.find(|line| line.trim().eq_ignore_ascii_case("# unreleased"))?;
+    let body = lines
+        .take_while(|line| !line.starts_with("# "))
+        .collect::<Vec<_>>();
+    Some(body.join("\n").trim().to_string()).filter(|body| !body.is_empty())
+}
+
+/// `changelog` with the release's section, in place of `# Unreleased` or
+/// else above the newest release.
+fn update_changelog(changelog: &str, heading: &str, notes: &str) -> String {
+    let lines: Vec<&str> = changelog.lines().collect();
+    let is_heading = |line: &str| line.starts_with("# ");
+    if let Some(at) = lines
+        .iter()
+        .position(|line| line.trim().eq_ignore_ascii_case("# unreleased"))
+    {
+        let end = (at + 1..lines.len())
+            .find(|&i| is_heading(lines[i]))
+            .unwrap_or(lines.len());
+        let mut out: Vec<String> = lines[..at].iter().map(|l| l.to_string()).collect();
+        out.push(format!("{heading}\n\n{notes}\n"));
+        out.extend(lines[end..].iter().map(|l| l.to_string()));
+        return out.join("\n").trim_end().to_string() + "\n";
+    }
+
+    // Releases are the headings with a version number in them; anything
+    // before the first (a `# Changelog` title, an intro) stays on top.
+    let at = lines
+        .iter()
+        .position(|line| is_heading(line) && line.chars().any(|c| c.is_ascii_digit()))
+        .unwrap_or_else(|| match lines.first() {
+            Some(first) if is_heading(first) => 1,
+            _ => 0,
+        });
+    let mut out: Vec<String> = lines[..at].iter().map(|l| l.to_string()).collect();
+    if out.last().is_some_and(|line| !line.trim().is_empty()) {
+        out.push(String::new());
+    }
+    out.push(format!("{heading}\n\n{notes}\n"));
+    out.extend(lines[at..].iter().map(|l| l.to_string()));
+    out.join("\n").trim_end().to_string() + "\n"
+}
+
+/// Fetch the pull requests `commits` merged, for their titles, authors and
+/// labels. Best-effort: a missing one just leaves the commit's own subject.
+fn fetch_pulls(remote: &Remote, commits: &[LogCommit]) -> HashMap<u64, Pull> {
+    let numbers: Vec<u64> = commits
+        .iter()
+        .filter_map(|c| pull_number(&c.message))
+        .collect();
+    if numbers.is_empty() {
+        return HashMap::new();
+    }
+    let (owner, name) = (remote.user.clone(), remote.repo.clone());
+    github::run(|client| async move {
+        let requests = numbers.iter().map(|&number| {
+            let pulls = client.pulls(&owner, &name);
+            async move { (number, pulls.get(number).await) }
+        });
+        let pulls = futures::future::join_all(requests).await;
+        Ok::<_, Error>(
+            pulls
+                .into_iter()
+                .filter_map(|(number, pull)| {
+                    let pull = pull.ok()?;
+                    Some((
+                        number,
+                        Pull {
+                            title: pull.title.unwrap_or_default(),
+                            author: pull.user.map(|user| user.login),
+                            labels: pull
+                                .labels
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(|label| label.name)
+                                .collect(),
+                        },
+                    ))
+                })
+                .collect(),
+        )
+    })
+    .unwrap_or_default()
+}
+
+/// Have the model write the notes from the commit subjects and pull
+/// request titles.
+fn write_notes(
+    repo: &Repo,
+    config: &AppConfig,
+    opts: &Options,
+    previous: &str,
+    changes: &[Change],
+) -> Result<String> {
+    let language = Language::choose(opts.lang.as_deref(), config.language.as_deref());
+    let commits = changes
+        .iter()
+        .map(|change| format!("- {}", change.credit(&change.text)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let prompt = Prompt::load(Kind::ReleaseNotes, repo.workdir(), None)?;
+    let values = [
+        ("tag", opts.tag.clone()),
+        ("previous_tag", previous.to_string()),
+        (
+            "language",
+            language.as_ref().map_or("English", |l| &l.name).to_string(),
+        ),
+        ("commits", commits.clone()),
+    ];
+    let mut messages = vec![ChatMessage::system(prompt.render(&values))];
+    messages.extend(
+        language
+            .as_ref()
+            .map(|l| ChatMessage::system(l.instruction())),
+    );
+    if !prompt.uses("commits") {
+        messages.push(ChatMessage::user(format!("Commits:\n{commits}")));
+    }
+
+    let model = config.deepseek.model.as_deref().unwrap_or("deepseek-chat");
+    let cache = Cache::open(&config.cache);
+    let notes = llm::stream_and_collect(
+        &format!("Release notes for {}", opts.tag),
+        &Api::new(config),
+        model,
+        messages,
+        config.deepseek.temperature,
+        cache.as_ref(),
+        config.ui.display,
+    );
+    usage::settle("release", true);
+    Ok(notes?.trim().to_string())
+}
+
+fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    usage::date(secs)
+}
+
+/// Write `text` to the CHANGELOG at `path` and commit it by itself. Git runs
+/// in `cwd`, any directory of the working tree, so the path is passed whole
+/// rather than as a pathspec git would resolve against `cwd`.
+fn commit_changelog(cwd: &Path, path: &Path, text: &str, tag: &str) -> Result<()> {
+    fs::write(path, text)?;
+    let message = format!("Release {tag}");
+    let status = Command::new("git")
+        .current_dir(cwd)
+        .args(["commit", "-q", "-m", &message, "--"])
+        .arg(path)
+        .status()?;
+    if !status.success() {
+        return Err(Error::GitCommand(format!(
+            "git commit -m '{message}' -- {}",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Write the notes, tag, push and create the release. Nothing is written or
+/// pushed before the notes are settled and confirmed.
+pub fn release(opts: Options) -> Result<()> {
+    let path = env::current_dir().map_err(|_| Error::NoCurrentDir)?;
+    let repo = Repo::new(&path)?;
+    let remote = repo.remote(&opts.remote)?;
+    let mut config = load_config()?;
+    if repo.tag_exists(&opts.tag) {
+        return Err(Error::TagExists(opts.tag));
+    }
+    if let Some(asset) = opts.assets.iter().find(|asset| !asset.is_file()) {
+        return Err(Error::AssetNotFound(asset.clone()));
+    }
+    if !opts.no_push {
+        if github::token(&config)?.is_none() {
+            return Err(Error::NoGitHubToken("creating a release"));
+        }
+        let remote = &remote;
+        github::run(|client| async move {
+            github::writable_repo::<IgnoredAny>(&client, remote, "create releases").await
+        })?;
+    }
+
+    let previous = match &opts.since {
+        Some(since) => Some(since.clone()),
+        None => repo.nearest_tag(|_| Some(()))?,
+    };
+    let commits = repo.commits_since(previous.as_deref())?;
+    if commits.is_empty() {
+        return Err(Error::NothingToRelease(previous.unwrap_or_default()));
+    }
+    let pulls = fetch_pulls(&remote, &commits);
+    let changes = changes(&commits, &pulls);
+
+    let changelog_path = repo.workdir().map(|dir| dir.join(CHANGELOG));
+    let changelog = match &changelog_path {
+        Some(path) if !opts.no_changelog => fs::read_to_string(path).ok(),
+        _ => None,
+    };
+    let heading = format!("# {} {}", opts.tag, today());
+    let curated = changelog.as_deref().and_then(unreleased);
+    let previous_name = previous.as_deref().unwrap_or("the first commit");
+
+    let mut notes = match curated {
+        Some(notes) => {
+            show(
+                &format!("Release notes for {} (from {CHANGELOG})", opts.tag),
+                &notes,
+            );
+            notes
+        }
+        None if !opts.ai && is_conventional(&changes) => {
+            let notes = group(&changes);
+            show(&format!("Release notes for {}", opts.tag), &notes);
+            notes
+        }
+        None => {
+            auth::require_api_key(&mut config)?;
+            let notes = write_notes(&repo, &config, &opts, previous_name, &changes)?;
+            if config.ui.display == Display::Quiet {
+                show(&format!("Release notes for {}", opts.tag), &notes);
+            }
+            notes
+        }
+    };
+    if notes.trim().is_empty() {
+        notes = "No user-visible changes.".to_string();
+    }
+    if opts.edit {
+        notes = edit_in_editor(&notes)?.trim().to_string();
+        if notes.is_empty() {
+            return Err(Error::EmptyMessage);
+        }
+    }
+
+    println!();
+    println!(
+        "{}",
+        style::dim(&format!("{} commits since {previous_name}", commits.len()))
+    );
+    if !opts.yes && io::stdin().is_terminal() && !ai::confirm("Tag and release?")? {
+        return Err(Error::CommitCancelled);
+    }
+
+    if let (Some(changelog_path), Some(text)) = (&changelog_path, &changelog) {
+        let text = update_changelog(text, &heading, &notes);
+        commit_changelog(&path, changelog_path, &text, &opts.tag)?;
+    }
+    // `verbatim` keeps the notes' `## ` headings, which git would strip as
+    // comments.
+    let message = format!("{}\n\n{}", opts.tag, notes);
+    git_run(&["tag", "-a", &opts.tag, "--cleanup=verbatim", "-m", &message])?;
+    style::success(&format!("Tagged {}", opts.tag));
+
+    if opts.no_push {
+        println!("Push it with `git push {} HEAD {}`.", opts.remote, opts.tag);
+        return Ok(());
+    }
+    if changelog.is_some() {
+        git_run(&["push", "-q", &opts.remote, "HEAD"])?;
+    }
+    git_run(&[
+        "push",
+        "-q",
+        &opts.remote,
+        &format!("refs/tags/{}", opts.tag),
+    ])?;
+
+    let (owner, name) = (remote.user.clone(), remote.repo.clone());
+    let (tag, assets, draft) = (opts.tag.clone(), opts.assets.clone(), !opts.publish);
+    let url = github::run(|client| async move {
+        let repo = client.repos(&owner, &name);
+        let releases = repo.releases();
+        let release = releases
+            .create(&tag)
+            .name(&tag)
+            .body(&notes)
+            .draft(draft)
+            .prerelease(tag.contains('-'))
+            .send()
+            .await?;
+        for asset in &assets {
+            let file_name = asset.file_name().unwrap_or_default().to_string_lossy();
+            releases
+                .upload_asset(release.id.into_inner(), &file_name, fs::read(asset)?.into())
+                .send()
+                .await?;
+            println!("Uploaded {file_name}");
+        }
+        Ok::<_, Error>(release.html_url.to_string())
+    })?;
+    let kind = if draft { "Draft release" } else { "Release" };
+    style::success(&format!("{kind} {} created", opts.tag));
+    println!("{url}");
+    Ok(())
+}
+
+/// A release tag such as `v1.2.3` or `1.2.3`. Pre-releases aren't bases
+/// for the next version, so `-rc.1` and `+build` suffixes don't parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Version {
+    prefix: &'static str,
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+impl Version {
+    fn parse(tag: &str) -> Option<Version> {
+        let (prefix, rest) = match tag.strip_prefix('v') {
+            Some(rest) => ("v", rest),
+            None => ("", tag),
+        };
+        let mut parts = rest.split('.').map(|part| {
+            // No leading zeros or signs, as semver has it.
+            let canonical = !part.is_empty() && (part == "0" || !part.starts_with('0'));
+            part.bytes()
+                .all(|b| b.is_ascii_digit())
+                .then_some(())
+                .filter(|_| canonical)?;
+            part.parse::<u64>().ok()
+        });
+        let version = Version {
+            prefix,
+            major: parts.next()??,
+            minor: parts.next()??,
+            patch: parts.next()??,
+        };
+        parts.next().is_none().then_some(version)
+    }
+
+    /// The next version after changes of `level`. Before 1.0 the minor
+    /// number is the one breaking changes bump, as Cargo treats it, and
+    /// features only bump the patch.
+    fn bump(&self, level: Level) -> Version {
+        let (major, minor, patch) = match (level, self.major) {
+            (Level::Breaking, 0) | (Level::Feature, 1..) => (self.major, self.minor + 1, 0),
+            (Level::Breaking, _) => (self.major + 1, 0, 0),
+            _ => (self.major, self.minor, self.patch + 1),
+        };
+        Version {
+            prefix: self.prefix,
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// The version without its `v`, as manifests write it.
+    fn number(&self) -> String {
+        format!("{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.prefix, self.number())
+    }
+}
+
+/// How much a change matters to someone upgrading, least first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    Other,
+    Fix,
+    Feature,
+    Breaking,
+}
+
+impl Level {
+    fn of(parsed: &Conventional) -> Level {
+        match parsed.kind.as_str() {
+            _ if parsed.breaking => Level::Breaking,
+            "feat" => Level::Feature,
+            "fix" | "perf" => Level::Fix,
+            _ => Level::Other,
+        }
+    }
+
+    fn from_name(name: &str) -> Level {
+        match name.to_lowercase().as_str() {
+            "breaking" | "major" => Level::Breaking,
+            "feature" | "feat" | "minor" => Level::Feature,
+            "fix" | "patch" => Level::Fix,
+            _ => Level::Other,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Level::Breaking => "Breaking",
+            Level::Feature => "Feature",
+            Level::Fix => "Fix",
+            Level::Other => "Other",
+        }
+    }
+}
+
+const CLASSIFY: &str = "You classify commits for semantic versioning by their effect on the \
+project's users. For each numbered commit reply with one line `<number>: <kind>`, where kind \
+is `breaking` (removes or changes existing behaviour or API incompatibly), `feature` (adds \
+behaviour), `fix` (corrects behaviour) or `other` (docs, tests, refactoring, tooling). Reply \
+with those lines only.";
+
+/// The model's level for each of `changes`, in order; any it skips count
+/// as `Other`.
+fn classify(config: &AppConfig, changes: &[Change]) -> Result<Vec<Level>> {
+    let commits = changes
+        .iter()
+        .enumerate()
+        .map(|(i, change)| format!("{}. {}", i + 1, change.text))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let messages = vec![ChatMessage::system(CLASSIFY), ChatMessage::user(commits)];
+    let model = config.deepseek.model.as_deref().unwrap_or("deepseek-chat");
+    let cache = Cache::open(&config.cache);
+    let spinner = style::Spinner::start("Classifying commits");
+    let reply = llm::collect(
+        &Api::new(config),
+        model,
+        messages,
+        Some(0.0),
+        Duration::from_secs(120),
+        cache.as_ref(),
+    );
+    spinner.stop();
+    style::clear_line();
+    usage::settle("release", true);
+    Ok(parse_levels(&reply?, changes.len()))
+}
+
+fn parse_levels(reply: &str, n: usize) -> Vec<Level> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern =
+        PATTERN.get_or_init(|| Regex::new(r"^\W*(\d+)\W+(\w+)").expect("a valid pattern"));
+    let mut levels = vec![Level::Other; n];
+    for caps in reply.lines().filter_map(|line| pattern.captures(line)) {
+        let index = caps[1].parse::<usize>().ok().and_then(|i| i.checked_sub(1));
+        if let Some(level) = index.and_then(|i| levels.get_mut(i)) {
+            *level = Level::from_name(&caps[2]);
+        }
+    }
+    levels
+}
+
+/// `manifest` (a `Cargo.toml` or `package.json`) with its own version set
+/// to `version`, formatting kept; `None` when it has none to set, as with a
+/// `version.workspace = true` crate.
+fn bump_manifest(file_name: &str, manifest: &str, version: &str) -> Option<String> {
+    if file_name == "package.json" {
+        let at = top_level_key(manifest, "version")?;
+        let pattern = Regex::new(r#"^"version"(\s*):(\s*)"[^"]*""#).expect("a valid pattern");
+        let value = pattern.find(&manifest[at..])?;
+        let replaced = pattern.replace(value.as_str(), format!(r#""version"$1:$2"{version}""#));
+        return Some(format!(
+            "{}{replaced}{}",
+            &manifest[..at],
+            &manifest[at + value.end()..]
+        ));
+    }
+
+    let pattern = Regex::new(r#"^(\s*version\s*=\s*)"[^"]*"(.*)$"#).expect("a valid pattern");
+    let mut section = "";
+    let mut bumped = false;
+    let mut lines = Vec::new();
+    for line in manifest.lines() {
+        if line.trim_start().starts_with('[') {
+            section = line.trim();
+        }
+        match pattern.captures(line) {
+            Some(caps) if !bumped && matches!(section, "[package]" | "[workspace.package]") => {
+                lines.push(format!(r#"{}"{version}"{}"#, &caps[1], &caps[2]));
+                bumped = true;
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    let trailing = if manifest.ends_with('\n') { "\n" } else { "" };
+    bumped.then(|| lines.join("\n") + trailing)
+}
+
+/// Where `key` is named in the outermost object of `json`, skipping the
+/// same key in nested objects (a dependency's `version`, say).
+fn top_level_key(json: &str, key: &str) -> Option<usize> {
+    let bytes = json.as_bytes();
+    let (mut depth, mut i) = (0, 0);
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth -= 1,
+            b'"' => {
+                let start = i;
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                let is_key = json
+                    .get(i + 1..)
+                    .is_some_and(|rest| rest.trim_start().starts_with(':'));
+                if depth == 1 && is_key && json.get(start + 1..i) == Some(key) {
+                    return Some(start);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Propose the next version from the commits since the last release tag:
+/// the most significant change decides, by Conventional Commit type or, for
+/// other histories (or with `ai`), as the model classifies each commit.
+/// With `bump`, write it into the repository's `Cargo.toml` and
+/// `package.json`.
+pub fn suggest(since: Option<String>, ai: bool, bump: bool) -> Result<()> {
+    let path = env::current_dir().map_err(|_| Error::NoCurrentDir)?;
+    let repo = Repo::new(&path)?;
+    let previous = match since {
+        Some(since) => Some(since),
+        None => repo.nearest_tag(|tag| Version::parse(tag).map(|v| (v.major, v.minor, v.patch)))?,
+    };
+    let base = match &previous {
+        Some(tag) => Some(Version::parse(tag).ok_or_else(|| Error::NotAVersion(tag.clone()))?),
+        None => None,
+    };
+    let commits = repo.commits_since(previous.as_deref())?;
+    if commits.is_empty() {
+        return Err(Error::NothingToRelease(previous.unwrap_or_default()));
+    }
+    let changes = changes(&commits, &HashMap::new());
+
+    let by_model = ai || !is_conventional(&changes);
+    let levels = if by_model {
+        let mut config = load_config()?;
+        auth::require_api_key(&mut config)?;
+        classify(&config, &changes)?
+    } else {
+        changes
+            .iter()
+            .map(|change| change.parsed.as_ref().map_or(Level::Other, Level::of))
+            .collect()
+    };
+    let level = levels.iter().copied().max().unwrap_or(Level::Other);
+    let next = match &base {
+        Some(base) => base.bump(level),
+        // A first release starts development at 0.1.0.
+        None => Version::parse("v0.1.0").expect("a valid version"),
+    };
+
+    let from = previous.as_deref().unwrap_or("no release yet");
+    println!(
+        "{} {}",
+        style::bold(&next.to_string()),
+        style::dim(&format!("(from {from})"))
+    );
+    let reason = match (&base, level) {
+        (None, _) => "the first release".to_string(),
+        (Some(_), Level::Breaking) if next.major == 0 => {
+            "breaking changes, which bump the minor version before 1.0".to_string()
+        }
+        (Some(_), Level::Breaking) => "breaking changes".to_string(),
+        (Some(_), Level::Feature) if next.major == 0 => {
+            "new features, which bump the patch version before 1.0".to_string()
+        }
+        (Some(_), Level::Feature) => "new features".to_string(),
+        (Some(_), Level::Fix) => "fixes only".to_string(),
+        (Some(_), Level::Other) => "no features or fixes".to_string(),
+    };
+    let by = if by_model {
+        "as the model classified them"
+    } else {
+        "by commit type"
+    };
+    println!(
+        "{}",
+        style::dim(&format!("{} commits, {by}: {reason}", changes.len()))
+    );
+
+    for shown in [Level::Breaking, Level::Feature, Level::Fix] {
+        let matching: Vec<&Change> = changes
+            .iter()
+            .zip(&levels)
+            .filter(|(_, &l)| l == shown)
+            .map(|(change, _)| change)
+            .collect();
+        if matching.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{}", style::bold(shown.title()));
+        for change in matching {
+            println!("  {} {}", style::dim(&change.short_id), change.text);
+        }
+    }
+    let others = levels.iter().filter(|&&l| l == Level::Other).count();
+    if others > 0 {
+        println!();
+        let noun = if others == 1 { "commit" } else { "commits" };
+        println!("{}", style::dim(&format!("and {others} other {noun}")));
+    }
+
+    if !bump {
+        return Ok(());
+    }
+    let workdir = repo.workdir().unwrap_or(&path);
+    let mut bumped = Vec::new();
+    for file_name in ["Cargo.toml", "package.json"] {
+        let manifest_path = workdir.join(file_name);
+        let Ok(manifest) = fs::read_to_string(&manifest_path) else {
+            continue;
+        };
+        if let Some(updated) = bump_manifest(file_name, &manifest, &next.number()) {
+            fs::write(&manifest_path, updated)?;
+            bumped.push(file_name);
+        }
+    }
+    if bumped.is_empty() {
+        return Err(Error::NoManifest(workdir.to_path_buf()));
+    }
+    style::success(&format!(
+        "Set the version to {} in {}",
+        next.number(),
+        bumped.join(", ")
+    ));
+    println!("Commit it, then `git release {next}`.");
+    Ok(())
+}
+
+/// Print notes that weren't streamed, under a header like the model's.
+fn show(title: &str, notes: &str) {
+    style::header(title);
+    for line in notes.lines() {
+        println!("{} {line}", style::gutter());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn commits_the_changelog_from_a_subdirectory() {
+        let root = env::temp_dir().join(format!("git-github-release-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        git(&root, &["init", "-q"]);
+        git(&root, &["config", "user.name", "Dev"]);
+        git(&root, &["config", "user.email", "dev@example.com"]);
+        git(&root, &["config", "commit.gpgsign", "false"]);
+        fs::write(root.join(CHANGELOG), "# Unreleased\n").unwrap();
+        git(&root, &["add", CHANGELOG]);
+        git(&root, &["commit", "-q", "-m", "Start"]);
+
+        let text = "# v1.0.0 2024-05-01\n\n- Things\n";
+        commit_changelog(&root.join("src"), &root.join(CHANGELOG), text, "v1.0.0").unwrap();
+        assert_eq!(git(&root, &["log", "-1", "--format=%s"]), "Release v1.0.0");
+        assert_eq!(git(&root, &["status", "--porcelain"]), "");
+        assert_eq!(fs::read_to_string(root.join(CHANGELOG)).unwrap(), text);
+    }
+
+    fn commit(message: &str, merge: bool) -> LogCommit {
+        LogCommit {
+            id: String::new(),
+            short_id: String::new(),
+            message: message.to_string(),
+            merge,
+        }
+    }
+
+    #[test]
+    fn parses_conventional_subjects() {
+        let parsed = conventional("feat(api)!: drop v1 endpoints").unwrap();
+        assert_eq!(parsed.kind, "feat");
+        assert_eq!(parsed.scope.as_deref(), Some("api"));
+        assert!(parsed.breaking);
+        assert!(
+            conventional("fix: x\n\nBREAKING CHANGE: y")
+                .unwrap()
+                .breaking
+        );
+        assert!(!conventional("fix: x").unwrap().breaking);
+        assert_eq!(conventional("Add a thing"), None);
+        assert_eq!(
+            pull_number("Merge pull request #12 from o/branch"),
+            Some(12)
+        );
+        assert_eq!(pull_number("feat: squashed (#34)"), Some(34));
+        assert_eq!(pull_number("fix: see #5 later"), None);
+    }
+
+    #[test]
+    fn groups_changes_by_type() {
+        let pulls = HashMap::from([(
+            7,
+            Pull {
+                title: "Speed up parsing".to_string(),
+                author: Some("octo".to_string()),
+                labels: vec!["bug".to_string()],
+            },
+        )]);
+        let commits = [
+            commit("Merge pull request #7 from octo/speed", true),
+            commit("feat(cli): add --json", false),
+            commit("fix: handle empty input", false),
+            commit("chore: bump deps", false),
+            commit("feat!: rename the config file", false),
+        ];
+        let changes = changes(&commits, &pulls);
+        assert!(is_conventional(&changes));
+        assert_eq!(
+            group(&changes),
+            "## Feature\n\n- `cli`: add --json\n\n\
+             ## Fix\n\n- Speed up parsing (#7) by @octo\n- handle empty input\n\n\
+             ## Breaking\n\n- rename the config file"
+        );
+        assert!(!is_conventional(&super::changes(
+            &[commit("Add a thing", false)],
+            &pulls
+        )));
+    }
+
+    #[test]
+    fn next_versions() {
+        let version = |tag| Version::parse(tag).unwrap();
+        assert_eq!(
+            version("v1.2.3").bump(Level::Breaking).to_string(),
+            "v2.0.0"
+        );
+        assert_eq!(version("v1.2.3").bump(Level::Feature).to_string(), "v1.3.0");
+        assert_eq!(version("1.2.3").bump(Level::Fix).to_string(), "1.2.4");
+        assert_eq!(version("v1.2.3").bump(Level::Other).to_string(), "v1.2.4");
+        assert_eq!(
+            version("v0.4.1").bump(Level::Breaking).to_string(),
+            "v0.5.0"
+        );
+        assert_eq!(version("v0.4.1").bump(Level::Feature).to_string(), "v0.4.2");
+        for tag in ["v1.2", "v1.2.3-rc.1", "v01.2.3", "release-1", "v1.2.3.4"] {
+            assert_eq!(Version::parse(tag), None, "{tag}");
+        }
+        assert_eq!(
+            parse_levels("1: feature\n2. Breaking\nthree: fix\n9: fix", 3),
+            [Level::Feature, Level::Breaking, Level::Other]
+        );
+    }
+
+    #[test]
+    fn bumps_manifest_versions() {
+        let cargo = "[package]\nname = \"a\"\nversion = \"0.1.0\" # ours\n\n\
+                     [dependencies]\nb = { version = \"1\" }\nversion = \"9\"\n";
+        assert_eq!(
+            bump_manifest("Cargo.toml", cargo, "0.2.0").unwrap(),
+            cargo.replace("\"0.1.0\"", "\"0.2.0\"")
+        );
+        let inherited = "[package]\nname = \"a\"\nversion.workspace = true\n";
+        assert_eq!(bump_manifest("Cargo.toml", inherited, "0.2.0"), None);
+        let json = "{\n  \"name\": \"a\",\n  \"dependencies\": {\"version\": \"1.0.0\"},\n  \
+                    \"version\" : \"1.0.0\"\n}\n";
+        let bumped = bump_manifest("package.json", json, "1.1.0").unwrap();
+        assert_eq!(
+            bumped,
+            json.replace("\"version\" : \"1.0.0\"", "\"version\" : \"1.1.0\"")
+        );
+    }
+
+    #[test]
+    fn changelog_sections() {
+        let file = "# Unreleased\n\n## Feature\n\n- by hand\n\n# v0.1.0 2024-01-01\n\n- first\n";
+        let notes = unreleased(file).unwrap();
+        assert_eq!(notes, "## Feature\n\n- by hand");
+        assert_eq!(
+            update_changelog(file, "# v0.2.0 2024-02-01", &notes),
+            "# v0.2.0 2024-02-01\n\n## Feature\n\n- by hand\n\n# v0.1.0 2024-01-01\n\n- first\n"
+        );
+        assert_eq!(unreleased("# Unreleased\n\n# v0.1.0\n"), None);
+
+        let file = "# Changelog\n\n# v0.1.0 2024-01-01\n\n- first\n";
+        assert_eq!(unreleased(file), None);
+        assert_eq!(
+            update_changelog(file, "# v0.2.0 2024-02-01", "## Fix\n\n- x"),
+            "# Changelog\n\n# v0.2.0 2024-02-01\n\n## Fix\n\n- x\n\n\
+             # v0.1.0 2024-01-01\n\n- first\n"
+        );
+        let out = update_changelog("# Changelog\n", "# v1 d", "- x");
+        assert_eq!(out, "# Changelog\n\n# v1 d\n\n- x\n");
+    }
+}
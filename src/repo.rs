@@ -1,8 +1,11 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::error::{Error, Result};
 use crate::remote::Remote;
-use git2::{Delta, DiffDelta, IndexAddOption, Patch, Repository};
+use git2::{
+    BlameOptions, Delta, Diff, DiffDelta, DiffOptions, IndexAddOption, Oid, Patch, Repository,
+};
+use std::collections::HashMap;
 
 /// Per-file diff size cap sent to the AI. Larger diffs are omitted.
 const MAX_FILE_DIFF_BYTES: usize = 16 * 1024;
@@ -44,11 +47,55 @@ fn status_header(delta: &DiffDelta) -> Option<String> {
     }
 }
 
+/// The abbreviated id of `commit` (git's default length), or the full id if
+/// abbreviation is unavailable.
+fn short_id(commit: &git2::Commit) -> String {
+    commit
+        .as_object()
+        .short_id()
+        .ok()
+        .and_then(|buf| std::str::from_utf8(&buf).ok().map(str::to_string))
+        .unwrap_or_else(|| commit.id().to_string())
+}
+
+/// The recent commit most of the staged changes belong to, as found by
+/// [`Repo::fixup_target`].
+pub struct FixupTarget {
+    pub id: String,
+    pub short_id: String,
+    pub summary: String,
+    /// Touched lines last changed by this commit.
+    pub lines: usize,
+    /// Touched lines blamed overall (including older commits).
+    pub total: usize,
+}
+
+/// A commit selected for rewording, as listed by [`Repo::range_commits`].
+pub struct RangeCommit {
+    pub id: String,
+    pub short_id: String,
+    pub message: String,
+}
+
+/// A commit since the previous release, as listed by [`Repo::commits_since`].
+pub struct LogCommit {
+    pub id: String,
+    pub short_id: String,
+    pub message: String,
+    /// Whether it merges a branch, e.g. a pull request.
+    pub merge: bool,
+}
+
 pub struct Repo {
     repository: Repository,
 }
 
 impl Repo {
+    /// The working tree's root; `None` for a bare repository.
+    pub fn workdir(&self) -> Option<&Path> {
+        self.repository.workdir()
+    }
+
     /// Open the repository at `path`, walking up to parent directories.
     pub fn new(path: &Path) -> Result<Self> {
         let mut cwd = path.to_path_buf();
@@ -76,11 +123,31 @@ impl Repo {
         Remote::parse(remote_url).ok_or_else(|| Error::RemoteUrlParse(remote_url.to_string()))
     }
 
+    /// Every remote's name and URL, in git's order.
+    pub fn remote_urls(&self) -> Result<Vec<(String, String)>> {
+        let mut remotes = Vec::new();
+        for name in self.repository.remotes()?.iter().flatten().flatten() {
+            let remote = self.repository.find_remote(name)?;
+            let url = remote.url().unwrap_or_default().to_string();
+            remotes.push((name.to_string(), url));
+        }
+        Ok(remotes)
+    }
+
     pub fn exist(&self, remote: &str, branch: &str) -> bool {
         let reference_name = format!("refs/remotes/{}/{}", remote, branch);
         self.repository.find_reference(&reference_name).is_ok()
     }
 
+    /// The commit local branch `name` points at, if there is one.
+    pub fn local_branch_id(&self, name: &str) -> Option<String> {
+        let branch = self
+            .repository
+            .find_branch(name, git2::BranchType::Local)
+            .ok()?;
+        Some(branch.get().target()?.to_string())
+    }
+
     pub fn current_branch(&self) -> Result<String> {
         let head = self.repository.head()?;
         head.shorthand()
@@ -118,7 +185,31 @@ impl Repo {
         let diff = self
             .repository
             .diff_tree_to_index(head_tree.as_ref(), None, None)?;
+        self.describe_diff(&diff)
+    }
 
+    /// The changes an amended `HEAD` would contain: `HEAD^`'s tree against
+    /// the index, i.e. HEAD's own changes plus whatever is staged on top. A
+    /// root commit is compared against the empty tree.
+    pub fn get_amend_changes(&self) -> Result<String> {
+        let head = self
+            .repository
+            .head()
+            .and_then(|h| h.peel_to_commit())
+            .map_err(|_| Error::NothingToAmend)?;
+        let parent_tree = match head.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let diff = self
+            .repository
+            .diff_tree_to_index(parent_tree.as_ref(), None, None)?;
+        self.describe_diff(&diff)
+    }
+
+    /// Render `diff` for the model: a status header per file, then its patch,
+    /// with binary, generated and oversized bodies omitted.
+    fn describe_diff(&self, diff: &Diff) -> Result<String> {
         let mut changes = String::new();
         let mut total_bytes = 0usize;
         let mut capped = false;
@@ -152,7 +243,7 @@ impl Repo {
                 continue;
             }
 
-            if let Ok(Some(mut patch)) = Patch::from_diff(&diff, idx) {
+            if let Ok(Some(mut patch)) = Patch::from_diff(diff, idx) {
                 let buf = patch.to_buf()?;
 
                 // Guard against binary content libgit2 may emit as raw bytes.
@@ -189,23 +280,379 @@ impl Repo {
         Ok(changes)
     }
 
+    /// Up to `limit` recent commit messages reachable from `HEAD`, newest
+    /// first, as samples of the repository's message style. Merges, bot
+    /// authors (`[bot]`, or any of `ignore_authors` as a name/email substring)
+    /// and `fixup!`/`squash!` commits are skipped as unrepresentative. An
+    /// unborn branch yields no samples rather than an error.
+    pub fn recent_commit_messages(
+        &self,
+        limit: usize,
+        ignore_authors: &[String],
+    ) -> Result<Vec<String>> {
+        let mut messages = Vec::new();
+        if limit == 0 {
+            return Ok(messages);
+        }
+        let mut walk = self.repository.revwalk()?;
+        if walk.push_head().is_err() {
+            return Ok(messages);
+        }
+
+        // Bound the walk so a history dominated by skipped commits stays cheap.
+        for oid in walk.take(limit.saturating_mul(10)) {
+            let commit = self.repository.find_commit(oid?)?;
+            if commit.parent_count() > 1 {
+                continue;
+            }
+            let author = commit.author();
+            let name = author.name().unwrap_or_default();
+            let email = author.email().unwrap_or_default();
+            let is_bot = [name, email].iter().any(|field| {
+                field.contains("[bot]")
+                    || ignore_authors
+                        .iter()
+                        .any(|a| !a.is_empty() && field.contains(a.as_str()))
+            });
+            if is_bot {
+                continue;
+            }
+            let Ok(message) = commit.message() else {
+                continue;
+            };
+            let message = message.trim();
+            if message.is_empty() || message.starts_with("fixup!") || message.starts_with("squash!")
+            {
+                continue;
+            }
+            messages.push(message.to_string());
+            if messages.len() == limit {
+                break;
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Which of the last `depth` first-parent commits the staged hunks most
+    /// likely belong to: blame `HEAD`'s version of every modified or deleted
+    /// file over the lines the hunks touch, and pick the commit that last
+    /// changed most of them. Pure insertions credit the line they follow. New
+    /// files have no history and cast no vote.
+    pub fn fixup_target(&self, depth: usize) -> Result<Option<FixupTarget>> {
+        let head = self.repository.head()?.peel_to_commit()?;
+
+        let mut walk = self.repository.revwalk()?;
+        walk.push(head.id())?;
+        walk.simplify_first_parent()?;
+        let recent: Vec<Oid> = walk.take(depth).collect::<std::result::Result<_, _>>()?;
+
+        // No context lines, so hunks cover exactly the changed lines.
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.context_lines(0);
+        let diff =
+            self.repository
+                .diff_tree_to_index(Some(&head.tree()?), None, Some(&mut diff_opts))?;
+        let mut votes: HashMap<Oid, usize> = HashMap::new();
+        let mut total = 0;
+
+        for (idx, delta) in diff.deltas().enumerate() {
+            if !matches!(delta.status(), Delta::Modified | Delta::Deleted) {
+                continue;
+            }
+            let Some(path) = delta.old_file().path() else {
+                continue;
+            };
+            let mut opts = BlameOptions::new();
+            opts.newest_commit(head.id());
+            let Ok(blame) = self.repository.blame_file(path, Some(&mut opts)) else {
+                continue;
+            };
+            let Ok(Some(patch)) = Patch::from_diff(&diff, idx) else {
+                continue;
+            };
+            for hunk_idx in 0..patch.num_hunks() {
+                let (hunk, _) = patch.hunk(hunk_idx)?;
+                let start = hunk.old_start() as usize;
+                let count = (hunk.old_lines() as usize).max(1);
+                for line in (start..start + count).filter(|&l| l > 0) {
+                    let Some(blamed) = blame.get_line(line) else {
+                        continue;
+                    };
+                    total += 1;
+                    let id = blamed.final_commit_id();
+                    if recent.contains(&id) {
+                        *votes.entry(id).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        // Ties go to the most recent commit.
+        let best = recent
+            .iter()
+            .filter_map(|id| votes.get(id).map(|&n| (*id, n)))
+            .fold(None, |best: Option<(Oid, usize)>, (id, n)| match best {
+                Some((_, top)) if top >= n => best,
+                _ => Some((id, n)),
+            });
+        let Some((id, lines)) = best else {
+            return Ok(None);
+        };
+
+        let commit = self.repository.find_commit(id)?;
+        Ok(Some(FixupTarget {
+            id: id.to_string(),
+            short_id: short_id(&commit),
+            summary: commit
+                .summary()
+                .ok()
+                .flatten()
+                .unwrap_or_default()
+                .to_string(),
+            lines,
+            total,
+        }))
+    }
+
+    /// Resolve `range` (`base..HEAD`, or just `base`) to the id of the commit
+    /// the range forks from — `base` itself, or its merge base with `HEAD` when
+    /// the branch has fallen behind — and the commits after it up to `HEAD`,
+    /// oldest first. The range must end at `HEAD` and be linear, since
+    /// rewording replays it onto that fork point.
+    pub fn range_commits(&self, range: &str) -> Result<(String, Vec<RangeCommit>)> {
+        let invalid = || Error::InvalidRange(range.to_string());
+        let (base, tip) = range.split_once("..").unwrap_or((range, "HEAD"));
+        let tip = if tip.is_empty() { "HEAD" } else { tip };
+
+        let head = self.repository.head()?.peel_to_commit()?.id();
+        let base = self
+            .repository
+            .revparse_single(base)
+            .and_then(|o| o.peel_to_commit())
+            .map_err(|_| invalid())?
+            .id();
+        let tip = self
+            .repository
+            .revparse_single(tip)
+            .and_then(|o| o.peel_to_commit())
+            .map_err(|_| invalid())?
+            .id();
+        if tip != head {
+            return Err(invalid());
+        }
+        // Replaying onto `base` itself would also rebase a diverged branch.
+        let fork = self
+            .repository
+            .merge_base(base, head)
+            .map_err(|_| invalid())?;
+
+        let mut walk = self.repository.revwalk()?;
+        walk.push(head)?;
+        walk.hide(fork)?;
+        walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+
+        let mut commits = Vec::new();
+        for oid in walk {
+            let commit = self.repository.find_commit(oid?)?;
+            if commit.parent_count() > 1 {
+                return Err(Error::RangeHasMerges);
+            }
+            commits.push(RangeCommit {
+                id: commit.id().to_string(),
+                short_id: short_id(&commit),
+                message: commit.message().unwrap_or_default().to_string(),
+            });
+        }
+        Ok((fork.to_string(), commits))
+    }
+
+    /// The tag on the commit nearest to `HEAD`, like `git describe --tags
+    /// --abbrev=0`, among the tags `rank` gives a rank. Of several on one
+    /// commit, the highest ranked, then the greatest by name — so a semver
+    /// rank puts `v1.10.0` above `v1.9.0`.
+    pub fn nearest_tag<K: Ord>(&self, rank: impl Fn(&str) -> Option<K>) -> Result<Option<String>> {
+        let mut tags: HashMap<Oid, Vec<(K, String)>> = HashMap::new();
+        for name in self.repository.tag_names(None)?.iter().flatten().flatten() {
+            let Some(key) = rank(name) else {
+                continue;
+            };
+            let target = self
+                .repository
+                .revparse_single(&format!("refs/tags/{name}"))
+                .and_then(|object| object.peel_to_commit());
+            if let Ok(commit) = target {
+                tags.entry(commit.id())
+                    .or_default()
+                    .push((key, name.to_string()));
+            }
+        }
+        if tags.is_empty() {
+            return Ok(None);
+        }
+
+        let mut walk = self.repository.revwalk()?;
+        if walk.push_head().is_err() {
+            return Ok(None);
+        }
+        walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        for oid in walk {
+            if let Some(names) = tags.remove(&oid?) {
+                return Ok(names.into_iter().max().map(|(_, name)| name));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn tag_exists(&self, name: &str) -> bool {
+        self.repository
+            .find_reference(&format!("refs/tags/{name}"))
+            .is_ok()
+    }
+
+    /// The commits reachable from `HEAD` but not from `since` (a tag or any
+    /// revision; the whole history when `None`), newest first.
+    pub fn commits_since(&self, since: Option<&str>) -> Result<Vec<LogCommit>> {
+        let mut walk = self.repository.revwalk()?;
+        walk.push_head()?;
+        if let Some(since) = since {
+            let base = self
+                .repository
+                .revparse_single(since)
+                .and_then(|object| object.peel_to_commit())
+                .map_err(|_| Error::InvalidRange(format!("{since}..HEAD")))?;
+            walk.hide(base.id())?;
+        }
+        walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+
+        let mut commits = Vec::new();
+        for oid in walk {
+            let commit = self.repository.find_commit(oid?)?;
+            commits.push(LogCommit {
+                id: commit.id().to_string(),
+                short_id: short_id(&commit),
+                message: commit.message().unwrap_or_default().trim().to_string(),
+                merge: commit.parent_count() > 1,
+            });
+        }
+        Ok(commits)
+    }
+
+    /// The changes introduced by commit `id` (against its first parent),
+    /// rendered like the staged changes.
+    pub fn commit_changes(&self, id: &str) -> Result<String> {
+        let commit = self.repository.find_commit(Oid::from_str(id)?)?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let diff =
+            self.repository
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+        self.describe_diff(&diff)
+    }
+
+    /// How many of `ids` are already reachable from a remote-tracking branch,
+    /// i.e. published, so rewriting them would diverge from the remote.
+    pub fn count_pushed(&self, ids: &[String]) -> Result<usize> {
+        let mut remote_tips = Vec::new();
+        for reference in self.repository.references_glob("refs/remotes/*")? {
+            if let Some(target) = reference?.peel_to_commit().ok().map(|c| c.id()) {
+                remote_tips.push(target);
+            }
+        }
+
+        let mut pushed = 0;
+        for id in ids {
+            let oid = Oid::from_str(id)?;
+            let reachable = remote_tips.iter().any(|&tip| {
+                tip == oid
+                    || self
+                        .repository
+                        .graph_descendant_of(tip, oid)
+                        .unwrap_or(false)
+            });
+            if reachable {
+                pushed += 1;
+            }
+        }
+        Ok(pushed)
+    }
+
+    /// The committer identity from git config, as `Name <email>` (used for
+    /// `Signed-off-by`).
+    pub fn identity(&self) -> Result<String> {
+        let signature = self.repository.signature()?;
+        Ok(format!(
+            "{} <{}>",
+            signature.name().unwrap_or_default(),
+            signature.email().unwrap_or_default()
+        ))
+    }
+
+    /// Where git looks for hooks: `core.hooksPath` when set (relative paths
+    /// are taken from the top of the working tree, as git does), else the
+    /// `hooks` directory of the common git dir, shared by all worktrees.
+    pub fn hooks_dir(&self) -> Result<PathBuf> {
+        match self.repository.config()?.get_path("core.hooksPath") {
+            Ok(path) if path.is_absolute() => Ok(path),
+            Ok(path) => {
+                let root = self
+                    .repository
+                    .workdir()
+                    .unwrap_or(self.repository.commondir());
+                Ok(root.join(path))
+            }
+            Err(_) => Ok(self.repository.commondir().join("hooks")),
+        }
+    }
+
+    /// The git dir shared by all worktrees (`.git` in a plain checkout).
+    pub fn common_dir(&self) -> &Path {
+        self.repository.commondir()
+    }
+
+    /// Where git-github keeps per-repository state: `git-github/` in the
+    /// common git dir, shared by all worktrees.
+    pub fn data_dir(&self) -> PathBuf {
+        self.repository.commondir().join("git-github")
+    }
+
+    /// The id of the tree the index would commit. Identifies the staged
+    /// changes independently of how the diff is rendered.
+    pub fn staged_tree_id(&self) -> Result<String> {
+        Ok(self.repository.index()?.write_tree()?.to_string())
+    }
+
+    /// The contents of the repository's `.mailmap`, if it has one.
+    pub fn mailmap(&self) -> Option<String> {
+        let workdir = self.repository.workdir()?;
+        std::fs::read_to_string(workdir.join(".mailmap")).ok()
+    }
+
     /// The full id of the current `HEAD` commit (used to build permalinks).
     pub fn head_commit_id(&self) -> Result<String> {
         let commit = self.repository.head()?.peel_to_commit()?;
         Ok(commit.id().to_string())
     }
 
+    /// The name of the working tree's top-level directory.
+    pub fn name(&self) -> Option<String> {
+        let workdir = self.repository.workdir()?;
+        Some(workdir.file_name()?.to_string_lossy().into_owned())
+    }
+
+    /// The message of the current `HEAD` commit, e.g. as left by the editor.
+    pub fn head_message(&self) -> Result<String> {
+        let commit = self.repository.head()?.peel_to_commit()?;
+        Ok(commit.message()?.to_string())
+    }
+
     /// The abbreviated id of the current `HEAD` commit (git's default length),
     /// for reporting after a commit. Falls back to the full id if abbreviation
     /// is unavailable.
     pub fn head_short_id(&self) -> Result<String> {
         let commit = self.repository.head()?.peel_to_commit()?;
-        let short = commit
-            .as_object()
-            .short_id()
-            .ok()
-            .and_then(|buf| std::str::from_utf8(&buf).ok().map(str::to_string));
-        Ok(short.unwrap_or_else(|| commit.id().to_string()))
+        Ok(short_id(&commit))
     }
 
     /// Convert `input` (absolute, or relative to the current directory) into a
@@ -236,7 +683,79 @@ impl Repo {
 
 #[cfg(test)]
 mod test {
-    use super::is_excluded;
+    use super::*;
+    use git2::{Signature, Time};
+    use std::fs;
+
+    /// A fresh repository under the temp dir, named for the test using it.
+    fn scratch(name: &str) -> Repo {
+        let dir =
+            std::env::temp_dir().join(format!("git-github-repo-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let repository = Repository::init(&dir).expect("a scratch repository");
+        Repo { repository }
+    }
+
+    impl Repo {
+        /// Write `files` and stage them.
+        fn stage(&self, files: &[(&str, &str)]) -> git2::Index {
+            let workdir = self.workdir().expect("a working tree");
+            let mut index = self.repository.index().unwrap();
+            for (path, content) in files {
+                fs::write(workdir.join(path), content).unwrap();
+                index.add_path(Path::new(path)).unwrap();
+            }
+            index.write().unwrap();
+            index
+        }
+
+        /// Write `files` and commit them on top of `HEAD`, returning the id.
+        fn commit_files(&self, files: &[(&str, &str)], message: &str) -> Oid {
+            let mut index = self.stage(files);
+            let tree = self
+                .repository
+                .find_tree(index.write_tree().unwrap())
+                .unwrap();
+            let signature = Signature::new("Dev", "dev@example.com", &Time::new(0, 0)).unwrap();
+            let parent = self
+                .repository
+                .head()
+                .ok()
+                .and_then(|h| h.peel_to_commit().ok());
+            let parents: Vec<&git2::Commit> = parent.iter().collect();
+            self.repository
+                .commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    message,
+                    &tree,
+                    &parents,
+                )
+                .unwrap()
+        }
+    }
+
+    #[test]
+    fn reword_range_forks_from_the_merge_base() {
+        let repo = scratch("range");
+        let fork = repo.commit_files(&[("a.txt", "a\n")], "Start");
+        let topic = repo.commit_files(&[("b.txt", "b\n")], "Topic work");
+        // `main` moves on past the fork point while the branch stays behind.
+        let main = repo.repository.find_commit(fork).unwrap();
+        repo.repository.branch("main", &main, true).unwrap();
+        repo.repository.set_head("refs/heads/main").unwrap();
+        repo.commit_files(&[("c.txt", "c\n")], "Main work");
+        repo.repository
+            .branch("topic", &repo.repository.find_commit(topic).unwrap(), true)
+            .unwrap();
+        repo.repository.set_head("refs/heads/topic").unwrap();
+
+        let (base, commits) = repo.range_commits("main..HEAD").unwrap();
+        assert_eq!(base, fork.to_string());
+        let ids: Vec<&str> = commits.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, [topic.to_string()]);
+    }
 
     #[test]
     fn excludes_lock_and_generated_files() {
@@ -254,4 +773,87 @@ mod test {
         assert!(!is_excluded("README.md"));
         assert!(!is_excluded("locksmith.rs"));
     }
+
+    #[test]
+    fn nearest_tag_ranks_tags_on_one_commit() {
+        let repo = scratch("tags");
+        let first = repo.commit_files(&[("a.txt", "a\n")], "Start");
+        let head = repo.commit_files(&[("b.txt", "b\n")], "More");
+        let tag = |name: &str, id: Oid| {
+            let object = repo.repository.find_object(id, None).unwrap();
+            repo.repository
+                .tag_lightweight(name, &object, false)
+                .unwrap();
+        };
+        tag("v2.0.0", first);
+        tag("v1.9.0", head);
+        tag("v1.10.0", head);
+        tag("nightly", head);
+
+        let by_name = repo.nearest_tag(|_| Some(())).unwrap();
+        assert_eq!(by_name.as_deref(), Some("v1.9.0"));
+        let by_version = repo
+            .nearest_tag(|name| {
+                let mut parts = name.strip_prefix('v')?.split('.').map(|n| n.parse::<u64>());
+                Some((parts.next()?.ok()?, parts.next()?.ok()?))
+            })
+            .unwrap();
+        assert_eq!(by_version.as_deref(), Some("v1.10.0"));
+    }
+
+    #[test]
+    fn fixup_target_goes_by_the_touched_lines() {
+        let repo = scratch("fixup");
+        let lines = "one\ntwo\nthree\n";
+        let first = repo.commit_files(&[("a.txt", lines)], "Add a");
+        let second = repo.commit_files(&[("b.txt", lines)], "Add b");
+        repo.commit_files(&[("c.txt", lines)], "Add c");
+
+        repo.stage(&[("a.txt", "one\n2\nthree\n")]);
+        let target = repo.fixup_target(20).unwrap().unwrap();
+        assert_eq!(target.id, first.to_string());
+        assert_eq!(
+            (target.summary.as_str(), target.lines, target.total),
+            ("Add a", 1, 1)
+        );
+
+        // With a's change still staged, most of the touched lines are b's; a
+        // pure insertion credits the line it follows.
+        repo.stage(&[("b.txt", "1\n2\nthree\nfour\n")]);
+        let target = repo.fixup_target(20).unwrap().unwrap();
+        assert_eq!(target.id, second.to_string());
+        assert_eq!((target.lines, target.total), (3, 4));
+
+        // Commits past `depth` are out of reach.
+        assert!(repo.fixup_target(1).unwrap().is_none());
+    }
+
+    #[test]
+    fn fixup_target_ignores_new_files() {
+        let repo = scratch("fixup-new");
+        repo.commit_files(&[("a.txt", "a\n")], "Add a");
+        repo.stage(&[("new.txt", "new\n")]);
+        assert!(repo.fixup_target(20).unwrap().is_none());
+    }
+
+    #[test]
+    fn amend_changes_cover_head_and_the_index() {
+        let repo = scratch("amend");
+        assert!(matches!(
+            repo.get_amend_changes(),
+            Err(Error::NothingToAmend)
+        ));
+
+        repo.commit_files(&[("a.txt", "a\n")], "Add a");
+        // A root commit is compared against the empty tree.
+        let changes = repo.get_amend_changes().unwrap();
+        assert!(changes.contains("Staged (new): a.txt"));
+
+        repo.commit_files(&[("b.txt", "b\n")], "Add b");
+        repo.stage(&[("c.txt", "c\n")]);
+        let changes = repo.get_amend_changes().unwrap();
+        assert!(changes.contains("Staged (new): b.txt"));
+        assert!(changes.contains("Staged (new): c.txt"));
+        assert!(!changes.contains("a.txt"));
+    }
 }
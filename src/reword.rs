@@ -0,0 +1,225 @@
+//! `git ac --reword-range`: regenerate the messages of a branch's commits from
+//! their own diffs, review old vs new side by side, and rewrite them with a
+//! non-interactive rebase.
+
+use crate::ai;
+use crate::auth;
+use crate::cache::Cache;
+use crate::config::load_config;
+use crate::error::{Error, Result};
+use crate::language::Language;
+use crate::llm::{self, Api, ChatMessage};
+use crate::repo::{RangeCommit, Repo};
+use crate::style;
+use crate::trailer;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Reword every commit in `range` (`base..HEAD`). Commits already on a remote
+/// are refused unless `force` is set. The rewrite is confirmed first, unless
+/// `yes`; without a terminal to ask on, `yes` is required. `no_cache`
+/// bypasses the completion cache, which otherwise makes a retry after a
+/// failed rebase free. `lang` overrides the configured language.
+pub fn run(range: &str, force: bool, yes: bool, no_cache: bool, lang: Option<&str>) -> Result<()> {
+    let result = reword(range, force, yes, no_cache, lang);
+    crate::usage::settle("reword", true);
+    result
+}
+
+fn reword(range: &str, force: bool, yes: bool, no_cache: bool, lang: Option<&str>) -> Result<()> {
+    // Refuse before spending tokens on messages nobody can approve.
+    if !yes && !io::stdin().is_terminal() {
+        return Err(Error::NeedsConfirmation("rewriting history"));
+    }
+    let path = env::current_dir().map_err(|_| Error::NoCurrentDir)?;
+    let repo = Repo::new(&path)?;
+
+    let (base, commits) = repo.range_commits(range)?;
+    if commits.is_empty() {
+        println!("No commits in {range}; nothing to reword.");
+        return Ok(());
+    }
+    if !force {
+        let ids: Vec<String> = commits.iter().map(|c| c.id.clone()).collect();
+        let pushed = repo.count_pushed(&ids)?;
+        if pushed > 0 {
+            return Err(Error::AlreadyPushed(pushed));
+        }
+    }
+
+    let mut config = load_config()?;
+    auth::require_api_key(&mut config)?;
+    let model = config.deepseek.model.as_deref().unwrap_or("deepseek-chat");
+    let language = Language::choose(lang, config.language.as_deref());
+    let prompt = ai::load_prompt(&repo, &config)?;
+    let values = ai::prompt_values(&prompt, &repo, &config, language.as_ref());
+    let conventions = ai::conventions(&repo, &config);
+    let cache = if no_cache {
+        None
+    } else {
+        Cache::open(&config.cache)
+    };
+
+    let mut rewritten = Vec::with_capacity(commits.len());
+    for commit in &commits {
+        let changes = repo.commit_changes(&commit.id)?;
+        let mut messages = ai::build_prompt_messages(
+            &prompt,
+            &values,
+            &changes,
+            conventions.as_deref(),
+            language.as_ref(),
+        );
+        messages.push(ChatMessage::user(format!(
+            "The commit's current message, for context (it may be vague or a placeholder):\n{}",
+            commit.message.trim()
+        )));
+
+        let generated = llm::stream_and_collect(
+            &format!("Rewording {}", commit.short_id),
+            &Api::new(&config),
+            model,
+            messages,
+            config.deepseek.temperature,
+            cache.as_ref(),
+            config.ui.display,
+        )?;
+        if generated.trim().is_empty() {
+            return Err(Error::EmptyMessage);
+        }
+
+        // The commit's own trailers (sign-offs, co-authors, …) carry over
+        // untouched; the model only rewrites the prose.
+        let kept = trailer::existing(&commit.message);
+        let (message, _) = trailer::apply(&trailer::strip_managed(&generated, &kept), None, &kept);
+        rewritten.push(message);
+    }
+
+    print_review(&commits, &rewritten);
+
+    if !yes && !ai::confirm("Rewrite these commits")? {
+        println!("Aborted; history unchanged.");
+        return Ok(());
+    }
+
+    rebase(&base, &commits, &rewritten)?;
+    style::success(&format!("Reworded {} commits", commits.len()));
+    Ok(())
+}
+
+/// List each commit's old and new subject side by side.
+fn print_review(commits: &[RangeCommit], messages: &[String]) {
+    let id_width = commits.iter().map(|c| c.short_id.len()).max().unwrap_or(7);
+    let (before, after) = layout("Before", "After", style::width(), id_width);
+    println!();
+    println!(
+        "{:id_width$}  {} {} {}",
+        "",
+        style::bold(&before),
+        style::gutter(),
+        style::bold(&after)
+    );
+    for (commit, message) in commits.iter().zip(messages) {
+        let old = commit.message.lines().next().unwrap_or_default();
+        let new = message.lines().next().unwrap_or_default();
+        let (old, new) = layout(old, new, style::width(), id_width);
+        println!(
+            "{}  {} {} {}",
+            style::cyan(&commit.short_id),
+            style::dim(&old),
+            style::gutter(),
+            new
+        );
+    }
+    println!();
+}
+
+/// Fit `old` and `new` into two equal columns of a `width`-wide line that
+/// starts with an `id_width` commit id. The left column is padded so the
+/// separators line up.
+fn layout(old: &str, new: &str, width: usize, id_width: usize) -> (String, String) {
+    // id, two spaces, then `left │ right`.
+    let column = width.saturating_sub(id_width + 5).max(20) / 2;
+    let old = style::truncate(old, column);
+    let pad = column.saturating_sub(old.chars().count());
+    (
+        format!("{old}{}", " ".repeat(pad)),
+        style::truncate(new, column),
+    )
+}
+
+/// Rewrite the commits with `git rebase -i`, driven by a prepared todo list:
+/// each commit is picked unchanged, then amended with its new message. Hooks
+/// still run. Any failure aborts the rebase, leaving the branch as it was.
+fn rebase(base: &str, commits: &[RangeCommit], messages: &[String]) -> Result<()> {
+    let dir = env::temp_dir().join(format!("git-github-reword-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+
+    let mut todo = String::new();
+    for (idx, (commit, message)) in commits.iter().zip(messages).enumerate() {
+        let file = dir.join(format!("{idx}.txt"));
+        fs::write(&file, message)?;
+        todo.push_str(&format!("pick {}\n", commit.id));
+        todo.push_str(&format!(
+            "exec git commit --amend --allow-empty -q -F {}\n",
+            shell_quote(&file)
+        ));
+    }
+    let todo_file = dir.join("todo");
+    fs::write(&todo_file, todo)?;
+
+    // Git invokes the sequence editor as `<editor> <todo-path>`, so `cp` swaps
+    // in our list without any interaction.
+    let status = Command::new("git")
+        .args(["rebase", "-i", "--autostash", base])
+        .env(
+            "GIT_SEQUENCE_EDITOR",
+            format!("cp {}", shell_quote(&todo_file)),
+        )
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::inherit())
+        .status();
+    let succeeded = matches!(&status, Ok(s) if s.success());
+    if !succeeded {
+        let _ = Command::new("git")
+            .args(["rebase", "--abort"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+
+    let _ = fs::remove_dir_all(&dir);
+    if !succeeded {
+        status?;
+        return Err(Error::RebaseFailed);
+    }
+    Ok(())
+}
+
+/// Single-quote `path` for the `sh` git runs editors and `exec` lines through.
+fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.display().to_string().replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lays_out_aligned_columns() {
+        let (old, new) = layout("wip", "Add login form validation", 40, 7);
+        // (40 - 12) / 2 = 14 columns each.
+        assert_eq!(old, "wip           ");
+        assert_eq!(new, "Add login for…");
+    }
+
+    #[test]
+    fn quotes_paths_for_the_shell() {
+        assert_eq!(shell_quote(Path::new("/tmp/a b")), "'/tmp/a b'");
+        assert_eq!(shell_quote(Path::new("/tmp/it's")), r"'/tmp/it'\''s'");
+    }
+}
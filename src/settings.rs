@@ -0,0 +1,219 @@
+//! `git-github config`: read and change settings across the configuration
+//! layers (see [`crate::config::layers`]), in the spirit of `git config`.
+
+use crate::auth::mask;
+pub use crate::config::Scope;
+use crate::config::{self, Layer};
+use crate::error::{Error, Result};
+use std::collections::BTreeMap;
+use std::fs;
+
+/// The effective value of every setting, with the layer it comes from.
+fn effective(layers: &[Layer]) -> Result<BTreeMap<String, (String, String)>> {
+    let mut settings = BTreeMap::new();
+    for layer in layers {
+        for (key, value) in layer.entries()? {
+            settings.insert(key, (value, layer.origin()));
+        }
+    }
+    Ok(settings)
+}
+
+/// The layer the effective value of `key` comes from.
+pub(crate) fn origin_of(key: &str) -> Option<String> {
+    let layers = config::layers().ok()?;
+    effective(&layers)
+        .ok()?
+        .remove(key)
+        .map(|(_, origin)| origin)
+}
+
+/// `config list`: every setting that is set somewhere, secrets masked.
+pub fn list(show_origin: bool) -> Result<()> {
+    for (key, (value, origin)) in effective(&config::layers()?)? {
+        let value = if is_secret(&key) { mask(&value) } else { value };
+        print_setting(&key, &value, &origin, show_origin);
+    }
+    Ok(())
+}
+
+/// `config get <key>`.
+pub fn get(key: &str, show_origin: bool) -> Result<()> {
+    let settings = effective(&config::layers()?)?;
+    let (value, origin) = settings
+        .get(key)
+        .ok_or_else(|| Error::SettingNotFound(key.to_string()))?;
+    if show_origin {
+        println!("{origin}\t{value}");
+    } else {
+        println!("{value}");
+    }
+    Ok(())
+}
+
+fn print_setting(key: &str, value: &str, origin: &str, show_origin: bool) {
+    if show_origin {
+        println!("{origin}\t{key}={value}");
+    } else {
+        println!("{key}={value}");
+    }
+}
+
+/// `config set <key> <value>` in the file of `scope`. The edit keeps the
+/// file's comments and layout, and is undone if the config files no longer
+/// make a valid configuration.
+pub fn set(key: &str, value: &str, scope: Scope) -> Result<()> {
+    let invalid = |reason: &str| Error::InvalidSetting(key.to_string(), reason.to_string());
+    if key.is_empty()
+        || !key.split('.').all(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_".contains(c))
+        })
+    {
+        return Err(invalid("keys are dot-separated names, e.g. deepseek.model"));
+    }
+    let layer = config::layers()?
+        .into_iter()
+        .find(|layer| layer.scope == scope && layer.path.is_some())
+        .ok_or_else(|| invalid(&format!("no {} config file here", scope.name())))?;
+    let path = layer.path.clone().expect("a file layer");
+
+    let before = match fs::read_to_string(&path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    let after = set_in_toml(before.as_deref().unwrap_or_default(), key, &literal(value));
+    after
+        .parse::<toml::Table>()
+        .map_err(|e| invalid(e.message()))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, &after)?;
+    if let Err(e) = config::check_files() {
+        match before {
+            Some(text) => fs::write(&path, text)?,
+            None => fs::remove_file(&path)?,
+        }
+        return Err(invalid(&e.to_string()));
+    }
+    if layer.validate()?.iter().any(|unknown| unknown == key) {
+        eprintln!("warning: `{key}` is not a setting git-github reads");
+    }
+    Ok(())
+}
+
+/// `value` as a TOML literal: as written when it is one (`0.7`, `true`,
+/// `["a"]`), else quoted as a string.
+fn literal(value: &str) -> String {
+    match format!("v = {value}").parse::<toml::Table>() {
+        Ok(_) => value.to_string(),
+        Err(_) => toml::Value::String(value.to_string()).to_string(),
+    }
+}
+
+/// `text` with `key` (dotted; all but the last part name the table) set to
+/// `literal`: the existing line replaced, else a line added to the table,
+/// else a new table appended.
+fn set_in_toml(text: &str, key: &str, literal: &str) -> String {
+    let (table, name) = match key.rsplit_once('.') {
+        Some((table, name)) => (Some(table), name),
+        None => (None, key),
+    };
+    let line = format!("{name} = {literal}");
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+
+    let start = match table {
+        None => Some(0),
+        Some(table) => {
+            let header = format!("[{table}]");
+            lines.iter().position(|l| l.trim() == header).map(|i| i + 1)
+        }
+    };
+    let Some(start) = start else {
+        let mut out = text.trim_end().to_string();
+        if !out.is_empty() {
+            out.push_str("\n\n");
+        }
+        out.push_str(&format!("[{}]\n{line}\n", table.unwrap_or_default()));
+        return out;
+    };
+    let end = lines[start..]
+        .iter()
+        .position(|l| l.trim_start().starts_with('['))
+        .map_or(lines.len(), |i| start + i);
+
+    let existing = (start..end).find(|&i| {
+        lines[i]
+            .split_once('=')
+            .is_some_and(|(k, _)| k.trim() == name)
+    });
+    match existing {
+        Some(i) => lines[i] = line,
+        None => {
+            let at = (start..end)
+                .rev()
+                .find(|&i| !lines[i].trim().is_empty())
+                .map_or(start, |i| i + 1);
+            lines.insert(at, line);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+fn is_secret(key: &str) -> bool {
+    let name = key.rsplit('.').next().unwrap_or(key);
+    name.ends_with("api_key") || name.ends_with("token")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FILE: &str = "# my settings\n[deepseek]\napi_key = \"\" # fill in\nmodel = \"deepseek-chat\"\n\n[ui]\ndisplay = \"stream\"\n";
+
+    #[test]
+    fn replaces_existing_keys_in_place() {
+        let out = set_in_toml(FILE, "deepseek.model", "\"deepseek-reasoner\"");
+        assert_eq!(out, FILE.replace("deepseek-chat", "deepseek-reasoner"));
+    }
+
+    #[test]
+    fn adds_keys_to_their_table_or_a_new_one() {
+        let out = set_in_toml(FILE, "deepseek.temperature", "0.2");
+        assert!(
+            out.contains("model = \"deepseek-chat\"\ntemperature = 0.2\n\n[ui]"),
+            "{out}"
+        );
+        let out = set_in_toml(FILE, "http.proxy", "\"http://p:3128\"");
+        assert!(out.ends_with("display = \"stream\"\n\n[http]\nproxy = \"http://p:3128\"\n"));
+        let out = set_in_toml(FILE, "language", "\"zh-CN\"");
+        assert!(
+            out.starts_with("# my settings\nlanguage = \"zh-CN\"\n[deepseek]"),
+            "{out}"
+        );
+        assert_eq!(
+            set_in_toml("", "cache.enabled", "false"),
+            "[cache]\nenabled = false\n"
+        );
+    }
+
+    #[test]
+    fn quotes_values_that_are_not_literals() {
+        assert_eq!(literal("0.7"), "0.7");
+        assert_eq!(literal("true"), "true");
+        assert_eq!(literal("[\"a\"]"), "[\"a\"]");
+        assert_eq!(literal("deepseek-chat"), "\"deepseek-chat\"");
+        assert_eq!(literal("sk-\"x\""), "'sk-\"x\"'");
+    }
+
+    #[test]
+    fn recognises_secrets() {
+        assert!(is_secret("deepseek.api_key") && is_secret("github.token"));
+        assert!(!is_secret("deepseek.model"));
+    }
+}
@@ -38,6 +38,12 @@ pub fn green(s: &str) -> String {
 pub fn cyan(s: &str) -> String {
     paint("36", s)
 }
+pub fn red(s: &str) -> String {
+    paint("31", s)
+}
+pub fn yellow(s: &str) -> String {
+    paint("33", s)
+}
 
 /// A section header introducing AI/streamed output, e.g.
 /// "Suggested commit message". Renders as `✦ title` when styled, else plain.
@@ -83,6 +89,34 @@ pub fn erase_lines(n: usize) {
     }
 }
 
+/// The terminal's width in columns: `COLUMNS` when set, else the size of the
+/// terminal on stdout, else 80.
+pub fn width() -> usize {
+    if let Some(columns) = std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()) {
+        return columns;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::io::AsRawFd;
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        let fd = std::io::stdout().as_raw_fd();
+        if unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) } == 0 && size.ws_col > 0 {
+            return size.ws_col as usize;
+        }
+    }
+    80
+}
+
+/// `text` cut to at most `max` characters, ending in `…` when shortened.
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(max.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
 /// Braille frames for the loading spinner (same family Claude Code uses).
 const SPIN_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
@@ -228,6 +262,122 @@ pub fn reveal(title: &str, message: &str) {
     let _ = std::io::stdout().flush();
 }
 
+/// Renders a message live as it streams in, instead of [`reveal`]ing it once
+/// complete. The spinner runs until the first visible text arrives; the text
+/// then replaces it on the header line (`✦ first line`), with further lines
+/// under the gutter — the same layout `reveal` ends in. Lines are wrapped at
+/// the terminal width on word boundaries: a word that overflows is erased
+/// and moved to the next row in place.
+///
+/// Only meaningful when styling is enabled; callers fall back to `reveal`
+/// otherwise.
+pub struct Live {
+    title: String,
+    spinner: Option<Spinner>,
+    /// Rows printed below the header line.
+    rows: usize,
+    /// What the current row holds, after its `✦ ` / `│ ` prefix.
+    row: String,
+    /// Newlines held back until more text follows, so trailing blank lines
+    /// never reach the screen.
+    pending_newlines: usize,
+    width: usize,
+}
+
+impl Live {
+    pub fn start(title: &str) -> Live {
+        Live {
+            title: title.to_string(),
+            spinner: Some(Spinner::start(title)),
+            rows: 0,
+            row: String::new(),
+            pending_newlines: 0,
+            // The prefix takes two columns; keep the last one free so the
+            // terminal never auto-wraps on its own.
+            width: width().saturating_sub(3).max(20),
+        }
+    }
+
+    /// Print a chunk of streamed text.
+    pub fn push(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.push_char(ch);
+        }
+        let _ = std::io::stdout().flush();
+    }
+
+    fn push_char(&mut self, ch: char) {
+        if let Some(spinner) = self.spinner.take() {
+            // Leading whitespace would only push the message off its line.
+            if ch.is_whitespace() {
+                self.spinner = Some(spinner);
+                return;
+            }
+            spinner.stop();
+            print!("\r\x1b[2K{} ", cyan("✦"));
+        }
+
+        if ch == '\n' {
+            self.pending_newlines += 1;
+            return;
+        }
+        for _ in 0..std::mem::take(&mut self.pending_newlines) {
+            self.new_row();
+        }
+        if self.row.chars().count() >= self.width {
+            if ch == ' ' {
+                self.new_row();
+                return;
+            }
+            match self.row.rfind(' ').map(|at| self.row[at + 1..].to_string()) {
+                Some(word) if !word.is_empty() => {
+                    // Erase the partial word and carry it to the next row.
+                    print!("\x1b[{}D\x1b[K", word.chars().count());
+                    self.new_row();
+                    print!("{word}");
+                    self.row = word;
+                }
+                _ => self.new_row(),
+            }
+        }
+        print!("{ch}");
+        self.row.push(ch);
+    }
+
+    fn new_row(&mut self) {
+        print!("\n{} ", gutter());
+        self.rows += 1;
+        self.row.clear();
+    }
+
+    /// Discard everything printed so far and go back to the spinner, for a
+    /// stream that broke off and is being requested again.
+    pub fn restart(&mut self) {
+        if self.spinner.is_some() {
+            return;
+        }
+        // Up to the blank line above the header, then clear to the end; the
+        // new spinner prints that blank line again.
+        print!("\r\x1b[{}A\x1b[0J", self.rows + 1);
+        self.rows = 0;
+        self.row.clear();
+        self.pending_newlines = 0;
+        self.spinner = Some(Spinner::start(&self.title));
+    }
+
+    /// End the message; wipes the spinner if nothing visible ever arrived.
+    pub fn finish(mut self) {
+        match self.spinner.take() {
+            Some(spinner) => {
+                spinner.stop();
+                clear_line();
+            }
+            None => println!(),
+        }
+        let _ = std::io::stdout().flush();
+    }
+}
+
 /// Clear the cursor's current line and return to its start. Used to wipe a
 /// stopped spinner when there's nothing to reveal. No-op when disabled.
 pub fn clear_line() {
@@ -0,0 +1,159 @@
+//! Ticket / issue references derived from the branch name, e.g.
+//! `feature/PROJ-1234-thing` → `Refs PROJ-1234`, `fix/567-bug` → `Fixes #567`.
+
+use crate::config::TicketConfig;
+use crate::error::{Error, Result};
+use regex::Regex;
+
+/// What a branch refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reference {
+    /// A GitHub issue number.
+    Issue(u64),
+    /// An external tracker key such as `PROJ-1234`.
+    Key(String),
+}
+
+/// A reference found in the branch name, and whether the branch's work
+/// closes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ticket {
+    pub reference: Reference,
+    pub closes: bool,
+}
+
+impl Ticket {
+    /// The reference as written in messages: `#567` or `PROJ-1234`.
+    pub fn display(&self) -> String {
+        match &self.reference {
+            Reference::Issue(number) => format!("#{number}"),
+            Reference::Key(key) => key.clone(),
+        }
+    }
+
+    /// The trailer line for a commit message: `Fixes #567` for an issue the
+    /// branch closes, `Refs …` otherwise. Tracker keys are never closed by
+    /// GitHub, so they always get `Refs`.
+    pub fn trailer(&self) -> String {
+        match self.reference {
+            Reference::Issue(_) if self.closes => format!("Fixes {}", self.display()),
+            _ => format!("Refs {}", self.display()),
+        }
+    }
+}
+
+/// Parse the ticket from `branch` using the configured patterns; `None` when
+/// disabled or nothing matches. A pattern that is not a valid regex is an
+/// error, so a typo in the config doesn't silently disable the feature.
+pub fn from_branch(branch: &str, config: &TicketConfig) -> Result<Option<Ticket>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+    for pattern in &config.patterns {
+        let regex = Regex::new(pattern)
+            .map_err(|e| Error::InvalidPattern(pattern.clone(), e.to_string()))?;
+        let Some(found) = regex
+            .captures(branch)
+            .and_then(|caps| caps.get(1).or_else(|| caps.get(0)))
+        else {
+            continue;
+        };
+        let text = found.as_str();
+        let reference = match text.parse::<u64>() {
+            Ok(number) => Reference::Issue(number),
+            Err(_) => Reference::Key(text.to_string()),
+        };
+        let kind = branch
+            .split_once('/')
+            .map(|(kind, _)| kind)
+            .unwrap_or_default();
+        let closes = config.close_prefixes.iter().any(|p| p == kind);
+        return Ok(Some(Ticket { reference, closes }));
+    }
+    Ok(None)
+}
+
+/// Append the ticket's trailer as the last paragraph of `message`, unless the message
+/// already mentions the reference (e.g. the model or the author put it in the
+/// subject).
+pub fn append_reference(message: &str, ticket: &Ticket) -> String {
+    let message = message.trim_end();
+    if mentions(message, &ticket.display()) {
+        return message.to_string();
+    }
+    format!("{message}\n\n{}", ticket.trailer())
+}
+
+/// Whether `reference` occurs in `text` as a whole token (so `#5` is not
+/// found in `#56`).
+fn mentions(text: &str, reference: &str) -> bool {
+    text.match_indices(reference).any(|(at, _)| {
+        let after = text[at + reference.len()..].chars().next();
+        !after.is_some_and(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(branch: &str) -> Option<Ticket> {
+        from_branch(branch, &TicketConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn parses_tracker_keys_and_issue_numbers() {
+        assert_eq!(
+            parse("feature/PROJ-1234-thing"),
+            Some(Ticket {
+                reference: Reference::Key("PROJ-1234".into()),
+                closes: false
+            })
+        );
+        assert_eq!(
+            parse("fix/567-bug"),
+            Some(Ticket {
+                reference: Reference::Issue(567),
+                closes: true
+            })
+        );
+        assert_eq!(
+            parse("feature/567"),
+            Some(Ticket {
+                reference: Reference::Issue(567),
+                closes: false
+            })
+        );
+        assert_eq!(parse("main"), None);
+        assert_eq!(parse("release-v2"), None);
+    }
+
+    #[test]
+    fn trailers_use_closing_keyword_only_for_closing_issues() {
+        assert_eq!(parse("fix/567-bug").unwrap().trailer(), "Fixes #567");
+        assert_eq!(parse("docs/567-typo").unwrap().trailer(), "Refs #567");
+        assert_eq!(parse("fix/PROJ-9-x").unwrap().trailer(), "Refs PROJ-9");
+    }
+
+    #[test]
+    fn does_not_duplicate_an_existing_reference() {
+        let ticket = parse("fix/56-bug").unwrap();
+        assert_eq!(
+            append_reference("Fix crash (#56)", &ticket),
+            "Fix crash (#56)"
+        );
+        assert_eq!(
+            append_reference("Fix crash (#567)\n", &ticket),
+            "Fix crash (#567)\n\nFixes #56"
+        );
+    }
+
+    #[test]
+    fn invalid_pattern_is_an_error() {
+        let config = TicketConfig {
+            patterns: vec!["(".into()],
+            ..TicketConfig::default()
+        };
+        assert!(from_branch("fix/1", &config).is_err());
+    }
+}
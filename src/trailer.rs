@@ -0,0 +1,224 @@
+//! Commit trailers (`Signed-off-by`, `Co-authored-by`, configured always-on
+//! lines, the branch's ticket reference). They are computed from config and
+//! the CLI, never by the model, and appended after its output.
+
+use crate::error::{Error, Result};
+use crate::ticket::Ticket;
+use std::collections::HashMap;
+
+/// Trailer keys we own: any the model emits are dropped before ours are added.
+const MANAGED_KEYS: &[&str] = &["signed-off-by", "co-authored-by"];
+
+/// A `Co-authored-by:` line for `alias`, looked up in the team roster first,
+/// then `.mailmap` (by name, email, or the email's user part). A literal
+/// `Name <email>` is taken as-is.
+pub fn co_author(
+    alias: &str,
+    team: &HashMap<String, String>,
+    mailmap: Option<&str>,
+) -> Result<String> {
+    let identity = team
+        .get(alias)
+        .cloned()
+        .or_else(|| (alias.contains('<') && alias.ends_with('>')).then(|| alias.to_string()))
+        .or_else(|| mailmap.and_then(|text| mailmap_lookup(text, alias)))
+        .ok_or_else(|| Error::UnknownCoAuthor(alias.to_string()))?;
+    Ok(format!("Co-authored-by: {}", identity.trim()))
+}
+
+/// Find `alias` among the canonical identities of a `.mailmap`.
+fn mailmap_lookup(mailmap: &str, alias: &str) -> Option<String> {
+    let alias = alias.to_lowercase();
+    mailmap_identities(mailmap)
+        .into_iter()
+        .find_map(|(name, email)| {
+            let user = email.split('@').next().unwrap_or_default();
+            let matches = [name.as_str(), email.as_str(), user]
+                .iter()
+                .any(|field| field.to_lowercase() == alias);
+            (matches && !name.is_empty()).then(|| format!("{name} <{email}>"))
+        })
+}
+
+/// The canonical `(name, email)` of each `.mailmap` entry — the first name
+/// and email on the line (`Proper Name <proper@email> [Commit Name]
+/// <commit@email>`). Comments and lines without an email are skipped.
+fn mailmap_identities(mailmap: &str) -> Vec<(String, String)> {
+    mailmap
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter_map(|line| {
+            let (name, rest) = line.split_once('<')?;
+            let (email, _) = rest.split_once('>')?;
+            Some((name.trim().to_string(), email.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Drop any lines the model produced for trailers we own (sign-offs,
+/// co-authors, and the keys of configured trailers), so it can neither add
+/// nor alter them. Only the trailing trailer block is touched: a body line
+/// that merely starts with such a key is prose, and stays.
+pub fn strip_managed(message: &str, configured: &[String]) -> String {
+    let configured_keys: Vec<String> = configured
+        .iter()
+        .filter_map(|t| t.split_once(':'))
+        .map(|(key, _)| key.trim().to_lowercase())
+        .collect();
+    let lines: Vec<&str> = message.lines().collect();
+    let block = trailer_block(&lines);
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(i, line)| {
+            if !block.contains(i) {
+                return true;
+            }
+            let Some((key, _)) = line.split_once(':') else {
+                return true;
+            };
+            let key = key.trim().to_lowercase();
+            !MANAGED_KEYS.contains(&key.as_str()) && !configured_keys.contains(&key)
+        })
+        .map(|(_, line)| *line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The indices of `lines` forming the trailer block: the last paragraph, when
+/// it follows another one and every line in it is a trailer.
+fn trailer_block(lines: &[&str]) -> std::ops::Range<usize> {
+    let end = lines
+        .iter()
+        .rposition(|l| !l.trim().is_empty())
+        .map_or(0, |i| i + 1);
+    let Some(blank) = lines[..end].iter().rposition(|l| l.trim().is_empty()) else {
+        return end..end;
+    };
+    if lines[blank + 1..end].iter().all(|l| is_trailer(l)) {
+        blank + 1..end
+    } else {
+        end..end
+    }
+}
+
+/// Whether `line` has the `Key: value` shape of a git trailer.
+pub fn is_trailer(line: &str) -> bool {
+    line.split_once(": ").is_some_and(|(key, value)| {
+        !key.is_empty()
+            && !value.trim().is_empty()
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// The trailer block already ending `message`: its last paragraph, when every
+/// line is a `Key: value` trailer. Used to carry trailers over when a commit's
+/// message is rewritten.
+pub fn existing(message: &str) -> Vec<String> {
+    let last = message.trim_end().rsplit("\n\n").next().unwrap_or_default();
+    let lines: Vec<&str> = last.lines().collect();
+    // A single-paragraph message is all subject, never trailers.
+    if message.trim().contains("\n\n") && !lines.is_empty() && lines.iter().all(|l| is_trailer(l)) {
+        lines.iter().map(|l| l.to_string()).collect()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Append `ticket`'s reference (unless the message already mentions it) and
+/// `trailers` as one block after the model's `message`. Returns the message
+/// and the lines that were added, for display.
+pub fn apply(message: &str, ticket: Option<&Ticket>, trailers: &[String]) -> (String, Vec<String>) {
+    let message = message.trim_end().to_string();
+    let mut lines: Vec<String> = Vec::new();
+    if let Some(ticket) = ticket {
+        if crate::ticket::append_reference(&message, ticket) != message {
+            lines.push(ticket.trailer());
+        }
+    }
+    for trailer in trailers {
+        if !lines.contains(trailer) {
+            lines.push(trailer.clone());
+        }
+    }
+    if lines.is_empty() {
+        return (message, lines);
+    }
+    (format!("{message}\n\n{}", lines.join("\n")), lines)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MAILMAP: &str = "\
+# Canonical identities
+Alice Smith <alice@example.com> <alice@old.example.com>
+Bob Jones <bob@example.com> Bobby <bobby@laptop>
+";
+
+    #[test]
+    fn resolves_co_authors_from_team_then_mailmap() {
+        let mut team = HashMap::new();
+        team.insert("al".to_string(), "Alice S <a@team.io>".to_string());
+
+        assert_eq!(
+            co_author("al", &team, Some(MAILMAP)).unwrap(),
+            "Co-authored-by: Alice S <a@team.io>"
+        );
+        assert_eq!(
+            co_author("bob", &team, Some(MAILMAP)).unwrap(),
+            "Co-authored-by: Bob Jones <bob@example.com>"
+        );
+        assert_eq!(
+            co_author("Alice Smith", &HashMap::new(), Some(MAILMAP)).unwrap(),
+            "Co-authored-by: Alice Smith <alice@example.com>"
+        );
+        assert_eq!(
+            co_author("Eve <eve@x.io>", &HashMap::new(), None).unwrap(),
+            "Co-authored-by: Eve <eve@x.io>"
+        );
+        assert!(co_author("carol", &team, Some(MAILMAP)).is_err());
+    }
+
+    #[test]
+    fn strips_model_written_trailers() {
+        let message = "Add thing\n\n- detail\n\nSigned-off-by: Model <m@x>\nReviewed-by: Someone";
+        assert_eq!(
+            strip_managed(message, &["Reviewed-by: Team".to_string()]),
+            "Add thing\n\n- detail\n"
+        );
+        // Prose in the body is not a trailer, even when it looks like one.
+        let message = "Credit helpers\n\nReviewed-by: lines are now added for us.\n\nFixes #3";
+        assert_eq!(
+            strip_managed(message, &["Reviewed-by: Team".to_string()]),
+            message
+        );
+        let message = "Add thing\n\nSigned-off-by: Model <m@x>\nNote that this is prose";
+        assert_eq!(strip_managed(message, &[]), message);
+    }
+
+    #[test]
+    fn finds_existing_trailer_block() {
+        assert_eq!(
+            existing("wip\n\nmore\n\nSigned-off-by: A <a@x>\nCo-authored-by: B <b@x>\n"),
+            vec!["Signed-off-by: A <a@x>", "Co-authored-by: B <b@x>"]
+        );
+        assert!(existing("Fix: the thing").is_empty());
+        assert!(existing("wip\n\n- Note: not a trailer\nSigned-off-by: A <a@x>").is_empty());
+    }
+
+    #[test]
+    fn appends_trailers_as_one_block() {
+        let trailers = vec![
+            "Co-authored-by: A <a@x>".to_string(),
+            "Signed-off-by: Me <me@x>".to_string(),
+        ];
+        let (message, added) = apply("Add thing\n", None, &trailers);
+        assert_eq!(
+            message,
+            "Add thing\n\nCo-authored-by: A <a@x>\nSigned-off-by: Me <me@x>"
+        );
+        assert_eq!(added, trailers);
+    }
+}
@@ -0,0 +1,378 @@
+//! Token usage and cost. After a command that called the model, its usage is
+//! appended to a ledger (`$XDG_DATA_HOME/git-github/usage.jsonl`, else
+//! `~/.local/share/git-github/usage.jsonl`) and summed up in one line;
+//! `git ac usage` aggregates the ledger by day, repository or model.
+
+use crate::config::{load_config, Price};
+use crate::error::Result;
+use crate::llm::{self, Usage};
+use crate::repo::Repo;
+use crate::style;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One completed request in the ledger.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Record {
+    /// Seconds since the Unix epoch.
+    time: u64,
+    /// The command that made it: `ac`, `pr`, `reword`, `hook`.
+    command: String,
+    /// The repository's directory name.
+    repo: String,
+    model: String,
+    #[serde(flatten)]
+    usage: Usage,
+    /// Estimated USD cost at the prices configured when it was made; `None`
+    /// for a model without a price.
+    cost: Option<f64>,
+}
+
+/// How `git ac usage` groups the ledger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Calendar day (UTC).
+    Day,
+    Repo,
+    Model,
+}
+
+/// Record the usage of the requests `command` made and, when `show` is set,
+/// print a one-line summary. Best-effort: usage accounting never fails a
+/// command.
+pub fn settle(command: &str, show: bool) {
+    let requests = llm::take_usage();
+    if requests.is_empty() {
+        return;
+    }
+    let pricing = load_config().map(|c| c.pricing).unwrap_or_default();
+    let repo = env::current_dir()
+        .ok()
+        .and_then(|path| Repo::new(&path).ok())
+        .and_then(|repo| repo.name())
+        .unwrap_or_default();
+    let time = now();
+
+    let records: Vec<Record> = requests
+        .into_iter()
+        .map(|(model, usage)| Record {
+            time,
+            command: command.to_string(),
+            repo: repo.clone(),
+            cost: pricing.get(&model).map(|price| cost(&usage, price)),
+            model,
+            usage,
+        })
+        .collect();
+    let _ = append(&records);
+
+    if show {
+        println!("{}", style::dim(&summary(&records)));
+    }
+}
+
+/// `Tokens: 1,200 prompt (1,000 cached) + 56 completion · ~$0.0004`.
+fn summary(records: &[Record]) -> String {
+    let total = sum(records.iter().map(|r| &r.usage));
+    let mut line = format!("Tokens: {} prompt", thousands(total.prompt_tokens));
+    if total.cached_tokens > 0 {
+        line.push_str(&format!(" ({} cached)", thousands(total.cached_tokens)));
+    }
+    line.push_str(&format!(
+        " + {} completion",
+        thousands(total.completion_tokens)
+    ));
+    if let Some(cost) = records.iter().map(|r| r.cost).sum::<Option<f64>>() {
+        line.push_str(&format!(" · ~{}", dollars(cost)));
+    }
+    line
+}
+
+/// The estimated USD cost of `usage` at `price`.
+fn cost(usage: &Usage, price: &Price) -> f64 {
+    let cached = usage.cached_tokens.min(usage.prompt_tokens);
+    let uncached = usage.prompt_tokens - cached;
+    (uncached as f64 * price.input
+        + cached as f64 * price.cached_input.unwrap_or(price.input)
+        + usage.completion_tokens as f64 * price.output)
+        / 1_000_000.0
+}
+
+fn sum<'a>(usages: impl Iterator<Item = &'a Usage>) -> Usage {
+    usages.fold(Usage::default(), |total, usage| Usage {
+        prompt_tokens: total.prompt_tokens + usage.prompt_tokens,
+        completion_tokens: total.completion_tokens + usage.completion_tokens,
+        cached_tokens: total.cached_tokens + usage.cached_tokens,
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// `$XDG_DATA_HOME/git-github/usage.jsonl`, falling back to
+/// `~/.local/share/git-github/usage.jsonl`.
+fn ledger_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| {
+            env::var_os("HOME")
+                .or_else(|| env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".local").join("share"))
+        })?;
+    Some(base.join("git-github").join("usage.jsonl"))
+}
+
+fn append(records: &[Record]) -> Result<()> {
+    let Some(path) = ledger_path() else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    for record in records {
+        let line = serde_json::to_string(record).map_err(io::Error::from)?;
+        writeln!(file, "{line}")?;
+    }
+    Ok(())
+}
+
+fn load() -> Result<Vec<Record>> {
+    let Some(path) = ledger_path() else {
+        return Ok(Vec::new());
+    };
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(text
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// One row of the report.
+#[derive(Debug, Default, PartialEq)]
+struct Row {
+    requests: u64,
+    usage: Usage,
+    /// Sum of the priced requests' costs.
+    cost: f64,
+}
+
+/// Sum `records` per group, keyed and ordered by the group's label.
+fn aggregate(records: &[Record], by: GroupBy) -> BTreeMap<String, Row> {
+    let mut rows: BTreeMap<String, Row> = BTreeMap::new();
+    for record in records {
+        let key = match by {
+            GroupBy::Day => date(record.time),
+            GroupBy::Repo => record.repo.clone(),
+            GroupBy::Model => record.model.clone(),
+        };
+        let row = rows.entry(key).or_default();
+        row.requests += 1;
+        row.usage = sum([&row.usage, &record.usage].into_iter());
+        row.cost += record.cost.unwrap_or_default();
+    }
+    rows
+}
+
+/// `git ac usage`: the ledger's last `days` days (all of it for 0), grouped.
+pub fn report(by: GroupBy, days: u64) -> Result<()> {
+    let since = if days == 0 {
+        0
+    } else {
+        now().saturating_sub(days * 86_400)
+    };
+    let records: Vec<Record> = load()?.into_iter().filter(|r| r.time >= since).collect();
+    if records.is_empty() {
+        println!("No usage recorded yet.");
+        return Ok(());
+    }
+
+    let rows = aggregate(&records, by);
+    let mut total = Row::default();
+    for row in rows.values() {
+        total.requests += row.requests;
+        total.usage = sum([&total.usage, &row.usage].into_iter());
+        total.cost += row.cost;
+    }
+
+    let label = match by {
+        GroupBy::Day => "Day (UTC)",
+        GroupBy::Repo => "Repository",
+        GroupBy::Model => "Model",
+    };
+    let mut lines = vec![[
+        label.to_string(),
+        "Requests".to_string(),
+        "Prompt".to_string(),
+        "Completion".to_string(),
+        "Cost".to_string(),
+    ]];
+    let total = ("Total".to_string(), total);
+    for (key, row) in rows.iter().chain(std::iter::once((&total.0, &total.1))) {
+        lines.push([
+            if key.is_empty() {
+                "?".to_string()
+            } else {
+                key.clone()
+            },
+            thousands(row.requests),
+            thousands(row.usage.prompt_tokens),
+            thousands(row.usage.completion_tokens),
+            dollars(row.cost),
+        ]);
+    }
+
+    let widths: Vec<usize> = (0..5)
+        .map(|col| {
+            lines
+                .iter()
+                .map(|l| l[col].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let last = lines.len() - 1;
+    for (idx, line) in lines.iter().enumerate() {
+        let mut text = format!("{:<w$}", line[0], w = widths[0]);
+        for col in 1..5 {
+            text.push_str(&format!("  {:>w$}", line[col], w = widths[col]));
+        }
+        match idx {
+            0 => println!("{}", style::dim(&text)),
+            _ if idx == last => println!("{}", style::bold(&text)),
+            _ => println!("{text}"),
+        }
+    }
+    Ok(())
+}
+
+/// The UTC calendar date (`YYYY-MM-DD`) of `secs` since the epoch.
+pub(crate) fn date(secs: u64) -> String {
+    // Howard Hinnant's `civil_from_days`.
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// `1234567` → `1,234,567`.
+fn thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (idx, ch) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// Dollars with enough precision for fractions of a cent.
+fn dollars(amount: f64) -> String {
+    if amount >= 1.0 {
+        format!("${amount:.2}")
+    } else {
+        format!("${amount:.4}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(time: u64, repo: &str, model: &str, prompt: u64, cost: Option<f64>) -> Record {
+        Record {
+            time,
+            command: "ac".into(),
+            repo: repo.into(),
+            model: model.into(),
+            usage: Usage {
+                prompt_tokens: prompt,
+                completion_tokens: 10,
+                cached_tokens: 0,
+            },
+            cost,
+        }
+    }
+
+    #[test]
+    fn costs_cached_tokens_at_their_own_rate() {
+        let price = Price {
+            input: 1.0,
+            cached_input: Some(0.1),
+            output: 2.0,
+        };
+        let usage = Usage {
+            prompt_tokens: 1_000_000,
+            completion_tokens: 500_000,
+            cached_tokens: 400_000,
+        };
+        // 600k × 1.0 + 400k × 0.1 + 500k × 2.0, per million.
+        assert!((cost(&usage, &price) - 1.64).abs() < 1e-9);
+    }
+
+    #[test]
+    fn aggregates_by_group() {
+        let records = vec![
+            record(0, "api", "deepseek-chat", 100, Some(0.5)),
+            record(86_400 + 5, "api", "deepseek-reasoner", 200, None),
+            record(86_400 + 9, "web", "deepseek-chat", 300, Some(0.25)),
+        ];
+        let by_day = aggregate(&records, GroupBy::Day);
+        assert_eq!(
+            by_day.keys().collect::<Vec<_>>(),
+            ["1970-01-01", "1970-01-02"]
+        );
+        assert_eq!(by_day["1970-01-02"].requests, 2);
+        assert_eq!(by_day["1970-01-02"].usage.prompt_tokens, 500);
+
+        let by_model = aggregate(&records, GroupBy::Model);
+        assert_eq!(by_model["deepseek-chat"].cost, 0.75);
+        assert_eq!(aggregate(&records, GroupBy::Repo)["api"].requests, 2);
+    }
+
+    #[test]
+    fn summarises_usage() {
+        let mut records = vec![record(0, "api", "deepseek-chat", 1200, Some(0.0004))];
+        records[0].usage.cached_tokens = 1000;
+        assert_eq!(
+            summary(&records),
+            "Tokens: 1,200 prompt (1,000 cached) + 10 completion · ~$0.0004"
+        );
+        records.push(record(0, "api", "unknown", 1, None));
+        assert!(!summary(&records).contains('$'));
+    }
+
+    #[test]
+    fn formats_dates_and_numbers() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(1_709_164_800), "2024-02-29");
+        assert_eq!(thousands(0), "0");
+        assert_eq!(thousands(1_234_567), "1,234,567");
+        assert_eq!(dollars(12.345), "$12.35");
+    }
+}